@@ -0,0 +1,43 @@
+use std::str::FromStr;
+use futures::executor::block_on;
+use rsdbc_core::connection::{Connection, ConnectionFactory, ConnectionFactoryOptions};
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::{OptionValue, Result};
+use rsdbc_mysql::MySqlConnection;
+use rsdbc_postgres::PostgresqlConnection;
+use rsdbc_sqlite::options::SqliteConnectOptions;
+
+/// Opens a connection to `url`, either `driver[:protocol]://[user:password@]host[:port][/path]`
+/// or a sqlite URL such as `sqlite::memory:` or `sqlite://data.db?mode=ro`.
+///
+/// Anything else is the path of a sqlite file, which is created if it doesn't exist.
+pub fn connect(url: &str) -> Result<Box<dyn Connection>> {
+    // sqlite URLs name a file rather than a host so they're parsed by the driver itself
+    if url.starts_with("sqlite:") || !url.contains("://") {
+        return connect_sqlite(url);
+    }
+
+    let options = ConnectionFactoryOptions::parse(url)?;
+    let driver = match options.get_value("driver") {
+        Some(OptionValue::String(driver)) => driver.as_str(),
+        _ => "",
+    };
+    // the drivers take the URL without the protocol, e.g. postgresql:failover://
+    let url = match url.find("://") {
+        Some(authority) => format!("{}{}", driver, &url[authority..]),
+        None => url.to_string(),
+    };
+    match driver {
+        "postgres" | "postgresql" => Ok(Box::new(PostgresqlConnection::connect(&url)?)),
+        "mysql" => Ok(Box::new(MySqlConnection::connect(&url)?)),
+        _ => Err(RsdbcErrors::Unsupported(format!("no driver for `{}`", driver))),
+    }
+}
+
+fn connect_sqlite(url: &str) -> Result<Box<dyn Connection>> {
+    let options = match url.starts_with("sqlite:") {
+        true => SqliteConnectOptions::from_str(url)?,
+        false => SqliteConnectOptions::new().filename(url).create_if_missing(true),
+    };
+    block_on(options.connect())
+}
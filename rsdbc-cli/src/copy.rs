@@ -0,0 +1,340 @@
+use std::io::Write;
+use rsdbc_core::connection::Connection;
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::{Dialect, Nullability, RsdbcType};
+use crate::error::CliError;
+use crate::import::{coerce_row, hex_or_text, insert, map_columns, table_columns, Column, DEFAULT_BATCH_SIZE};
+use crate::table::read_text;
+
+/// Copies the rows of tables from one database to another, which may be of a different kind.
+///
+/// Rows are read as text and converted to the types of the target's columns, then inserted in
+/// batches the way [crate::import::Import] inserts them. Only the batch being built is held by
+/// the copy as the rows of the source are read as they arrive. Each table is copied in a
+/// transaction of its own, so a table which fails to copy is left as it was, or dropped if the
+/// copy created it, while those before it are kept.
+pub struct TableCopy {
+    tables: Vec<String>,
+    filter: Option<String>,
+    create_tables: bool,
+    batch_size: usize,
+}
+
+impl TableCopy {
+    pub fn new(tables: Vec<String>) -> Self {
+        Self {
+            tables,
+            filter: None,
+            create_tables: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the condition of a `WHERE` clause rows of every table are filtered by
+    pub fn with_where(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Sets whether tables missing from the target are created, with the columns of the source
+    /// table mapped to the target's types through [RsdbcType]. False by default.
+    pub fn with_create_tables(mut self, create_tables: bool) -> Self {
+        self.create_tables = create_tables;
+        self
+    }
+
+    /// Sets the number of rows inserted at a time, [DEFAULT_BATCH_SIZE] by default
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Copies each table in turn, writing progress to `log`, and returns the number of rows
+    /// copied. Stops at the first table which fails.
+    pub fn run(&self, from: &mut dyn Connection, to: &mut dyn Connection, log: &mut dyn Write) -> Result<u64, CliError> {
+        let mut copied = 0;
+        for table in &self.tables {
+            let columns = table_columns(from, table)?;
+            let created = self.create_tables && match table_columns(to, table) {
+                Err(CliError::Rsdbc(RsdbcErrors::NoSuchElement(_))) => true,
+                Err(e) => return Err(e),
+                Ok(_) => false,
+            };
+            if created {
+                let sql = create_table_sql(to.dialect(), table, &columns)?;
+                to.execute_unprepared(&sql)?;
+                writeln!(log, "created table {}", table)?;
+            }
+
+            match self.copy_table_in_transaction(from, to, table, &columns, log) {
+                Ok(rows) => {
+                    writeln!(log, "copied {} rows into {}", rows, table)?;
+                    copied += rows;
+                },
+                Err(e) => {
+                    // MySQL commits DDL implicitly so the table is dropped rather than created in
+                    // the transaction, and the error which stopped the copy is the one returned
+                    if created && to.execute_unprepared(&format!("DROP TABLE {}", to.dialect().quote_identifier(table))).is_ok() {
+                        let _ = writeln!(log, "dropped table {}", table);
+                    }
+                    return Err(e);
+                },
+            }
+        }
+        Ok(copied)
+    }
+
+    fn copy_table_in_transaction(
+        &self,
+        from: &mut dyn Connection,
+        to: &mut dyn Connection,
+        table: &str,
+        columns: &[Column],
+        log: &mut dyn Write,
+    ) -> Result<u64, CliError> {
+        let target_columns = table_columns(to, table)?;
+        to.begin_transaction()?;
+        match self.copy_table(from, to, table, columns, &target_columns, log) {
+            Ok(rows) => {
+                to.commit_transaction()?;
+                Ok(rows)
+            },
+            Err(e) => {
+                // the error which stopped the copy is more useful than one from the rollback
+                let _ = to.rollback_transaction();
+                Err(e)
+            },
+        }
+    }
+
+    fn copy_table(
+        &self,
+        from: &mut dyn Connection,
+        to: &mut dyn Connection,
+        table: &str,
+        columns: &[Column],
+        target_columns: &[Column],
+        log: &mut dyn Write,
+    ) -> Result<u64, CliError> {
+        let names: Vec<Option<String>> = columns.iter().map(|c| Some(c.name.clone())).collect();
+        let targets: Vec<&Column> = map_columns(table, target_columns, &names)?.into_iter().map(|i| &target_columns[i]).collect();
+
+        let source = from.dialect().quote_identifier(table);
+        let sql = match &self.filter {
+            Some(filter) => format!("SELECT * FROM {} WHERE {}", source, filter),
+            None => format!("SELECT * FROM {}", source),
+        };
+        let mut stmt = from.create_statement(&sql)?;
+        let mut rs = stmt.execute_query(&[])?;
+
+        let mut copied = 0;
+        let mut batch = Vec::with_capacity(self.batch_size);
+        let mut row_number = 0;
+        while rs.next() {
+            row_number += 1;
+            let mut fields = Vec::with_capacity(columns.len());
+            for (i, column) in columns.iter().enumerate() {
+                fields.push(read_text(rs.as_ref(), i as u64, column.r#type)?);
+            }
+            let row = coerce_row(&targets, &fields, hex_or_text)
+                .map_err(|reason| RsdbcErrors::IllegalArgument(format!("row {} of {}: {}", row_number, table, reason)))?;
+            batch.push(row);
+            if batch.len() == self.batch_size {
+                copied += insert(to, table, &targets, std::mem::take(&mut batch))?;
+                writeln!(log, "{}: {} rows copied", table, copied)?;
+            }
+        }
+        if !batch.is_empty() {
+            copied += insert(to, table, &targets, batch)?;
+        }
+        Ok(copied)
+    }
+}
+
+/// A `CREATE TABLE` statement for `table` in `dialect` with `columns` read from another database
+fn create_table_sql(dialect: Dialect, table: &str, columns: &[Column]) -> Result<String, CliError> {
+    let definitions = columns
+        .iter()
+        .map(|column| column_definition(dialect, column))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("CREATE TABLE {} ({})", dialect.quote_identifier(table), definitions.join(", ")))
+}
+
+fn column_definition(dialect: Dialect, column: &Column) -> Result<String, CliError> {
+    let r#type = match (column.r#type, column.precision) {
+        // sqlite columns without a declared type may hold values of any type
+        _ if column.type_name.is_empty() => RsdbcType::Clob,
+        // MySQL strings need a length
+        (RsdbcType::Char | RsdbcType::Varchar | RsdbcType::Nchar | RsdbcType::Nvarchar, None) if dialect == Dialect::MySql => RsdbcType::Clob,
+        (RsdbcType::Binary | RsdbcType::Varbinary, None) if dialect == Dialect::MySql => RsdbcType::Blob,
+        (t, _) => t,
+    };
+    let type_name = declared_type(dialect, r#type)
+        .ok_or_else(|| CliError::Usage(format!("column {} of type {:?} has no equivalent to create", column.name, column.r#type)))?;
+    let size = match (r#type, column.precision, column.scale) {
+        (RsdbcType::Char | RsdbcType::Varchar | RsdbcType::Nchar | RsdbcType::Nvarchar, Some(length), _) => format!("({})", length),
+        // bytea has no length
+        (RsdbcType::Binary | RsdbcType::Varbinary, Some(length), _) if dialect != Dialect::Postgres => format!("({})", length),
+        (RsdbcType::Numeric | RsdbcType::Decimal, Some(precision), Some(scale)) => format!("({}, {})", precision, scale),
+        (RsdbcType::Numeric | RsdbcType::Decimal, Some(precision), None) => format!("({})", precision),
+        _ => String::new(),
+    };
+    let not_null = match column.nullability {
+        Nullability::NonNull => " NOT NULL",
+        _ => "",
+    };
+    Ok(format!("{} {}{}{}", dialect.quote_identifier(&column.name), type_name, size, not_null))
+}
+
+/// The type columns of `t` are declared with in `dialect`, as mapped by the driver
+fn declared_type(dialect: Dialect, t: RsdbcType) -> Option<String> {
+    match dialect {
+        Dialect::Postgres => rsdbc_postgres::types::to_pg_type(t).map(|pg_type| pg_type.name().to_string()),
+        Dialect::MySql => rsdbc_mysql::types::to_declared_type(t).map(str::to_string),
+        _ => rsdbc_sqlite::types::to_declared_type(t).map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use rsdbc_core::connection::{Connection, ConnectionFactory};
+    use rsdbc_core::{Dialect, Nullability, RsdbcType};
+    use rsdbc_sqlite::options::SqliteConnectOptions;
+    use crate::copy::{create_table_sql, TableCopy};
+    use crate::export::Format;
+    use crate::import::Column;
+    use crate::query;
+
+    fn connection() -> Box<dyn Connection> {
+        block_on(SqliteConnectOptions::new().connect()).unwrap()
+    }
+
+    fn rows(connection: &mut dyn Connection, sql: &str) -> String {
+        let mut out = Vec::new();
+        query::run(connection, sql, Format::Csv, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn filtered_rows_should_be_copied_into_created_tables() {
+        let mut from = connection();
+        from.execute_script("\
+            CREATE TABLE person (id INTEGER NOT NULL, name VARCHAR(20), active BOOLEAN, score DECIMAL(5, 2), photo BLOB);\
+            INSERT INTO person VALUES (1, 'Ada', 1, 12.5, x'000102'), (2, NULL, 0, NULL, NULL), (3, 'Bob', 1, 1, x'ff');\
+            CREATE TABLE pet (name TEXT);\
+            INSERT INTO pet VALUES ('Rex');").unwrap();
+        let mut to = connection();
+        to.execute_script("CREATE TABLE pet (name TEXT, age INTEGER)").unwrap();
+
+        let mut log = Vec::new();
+        let copied = TableCopy::new(vec!["person".to_string(), "pet".to_string()])
+            .with_where("1 = 1 AND rowid < 3")
+            .with_create_tables(true)
+            .with_batch_size(1)
+            .run(from.as_mut(), to.as_mut(), &mut log)
+            .unwrap();
+        assert_eq!(3, copied);
+        assert_eq!(
+            "created table person\nperson: 1 rows copied\nperson: 2 rows copied\ncopied 2 rows into person\npet: 1 rows copied\ncopied 1 rows into pet\n",
+            String::from_utf8(log).unwrap()
+        );
+
+        assert_eq!(
+            "id,name,active,score,photo\n1,Ada,1,12.5,\\x000102\n2,,0,,\n",
+            rows(to.as_mut(), "SELECT * FROM person ORDER BY id")
+        );
+        assert_eq!("name,age\nRex,\n", rows(to.as_mut(), "SELECT * FROM pet"));
+        assert_eq!(
+            "sql\n\"CREATE TABLE \"\"person\"\" (\"\"id\"\" INTEGER NOT NULL, \"\"name\"\" VARCHAR(20), \"\"active\"\" BOOLEAN, \"\"score\"\" DECIMAL(5, 2), \"\"photo\"\" BLOB)\"\n",
+            rows(to.as_mut(), "SELECT sql FROM sqlite_master WHERE name = 'person'")
+        );
+    }
+
+    #[test]
+    fn tables_should_be_copied_in_a_transaction() {
+        let mut from = connection();
+        from.execute_script("CREATE TABLE t (id INTEGER); INSERT INTO t VALUES (1), (2), (1);").unwrap();
+        let mut to = connection();
+        to.execute_script("CREATE TABLE t (id INTEGER PRIMARY KEY)").unwrap();
+
+        let mut log = Vec::new();
+        assert!(TableCopy::new(vec!["t".to_string()]).run(from.as_mut(), to.as_mut(), &mut log).is_err());
+        assert_eq!("id\n", rows(to.as_mut(), "SELECT * FROM t"));
+    }
+
+    #[test]
+    fn tables_created_for_a_failed_copy_should_be_dropped() {
+        let mut from = connection();
+        from.execute_script("CREATE TABLE t (id INTEGER, n INTEGER); INSERT INTO t VALUES (1, 2), (2, 'x');").unwrap();
+        let mut to = connection();
+
+        let mut log = Vec::new();
+        let err = TableCopy::new(vec!["t".to_string()])
+            .with_create_tables(true)
+            .run(from.as_mut(), to.as_mut(), &mut log)
+            .unwrap_err();
+        assert_eq!(1, err.exit_code());
+        assert!(err.to_string().contains("row 2 of t: column n: invalid integer `x`"), "{}", err);
+        assert_eq!("created table t\ndropped table t\n", String::from_utf8(log).unwrap());
+        assert_eq!("count\n0\n", rows(to.as_mut(), "SELECT count(*) AS count FROM sqlite_master WHERE name = 't'"));
+    }
+
+    #[test]
+    fn table_names_should_be_quoted() {
+        let mut from = connection();
+        from.execute_script("CREATE TABLE \"Order\" (id INTEGER); INSERT INTO \"Order\" VALUES (1), (2);").unwrap();
+        let mut to = connection();
+
+        let mut log = Vec::new();
+        let copied = TableCopy::new(vec!["Order".to_string()])
+            .with_create_tables(true)
+            .run(from.as_mut(), to.as_mut(), &mut log)
+            .unwrap();
+        assert_eq!(2, copied);
+        assert_eq!("id\n1\n2\n", rows(to.as_mut(), "SELECT * FROM \"Order\" ORDER BY id"));
+    }
+
+    #[test]
+    fn tables_should_only_be_created_when_missing() {
+        let mut from = connection();
+        from.execute_script("CREATE TABLE t (id INTEGER)").unwrap();
+        let mut to = connection();
+        to.close().unwrap();
+
+        let mut log = Vec::new();
+        let err = TableCopy::new(vec!["t".to_string()])
+            .with_create_tables(true)
+            .run(from.as_mut(), to.as_mut(), &mut log)
+            .unwrap_err();
+        assert!(err.to_string().contains("connection is closed"), "{}", err);
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn column_types_should_be_mapped_to_each_dialect() {
+        let column = |name: &str, r#type, precision, scale| Column {
+            name: name.to_string(),
+            r#type,
+            type_name: format!("{:?}", r#type),
+            precision,
+            scale,
+            nullability: Nullability::Nullable,
+        };
+        let columns = [
+            column("name", RsdbcType::Varchar, None, None),
+            column("code", RsdbcType::Char, Some(3), None),
+            column("amount", RsdbcType::Decimal, Some(10), Some(2)),
+            column("data", RsdbcType::Varbinary, Some(16), None),
+            column("at", RsdbcType::Timestamp, None, None),
+        ];
+        assert_eq!(
+            "CREATE TABLE \"t\" (\"name\" varchar, \"code\" bpchar(3), \"amount\" numeric(10, 2), \"data\" bytea, \"at\" timestamp)",
+            create_table_sql(Dialect::Postgres, "t", &columns).unwrap()
+        );
+        assert_eq!(
+            "CREATE TABLE `t` (`name` LONGTEXT, `code` CHAR(3), `amount` DECIMAL(10, 2), `data` VARBINARY(16), `at` DATETIME)",
+            create_table_sql(Dialect::MySql, "t", &columns).unwrap()
+        );
+    }
+}
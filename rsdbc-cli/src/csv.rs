@@ -0,0 +1,133 @@
+use std::io::{self, BufRead};
+
+/// Reads the records of comma separated values as described by RFC 4180, the format written by
+/// `query --format csv`.
+///
+/// Quoted fields may contain delimiters, doubled quotes and line breaks. An empty field which
+/// isn't quoted is read as NULL whereas `""` is an empty string.
+pub struct CsvReader<R> {
+    input: R,
+    line: String,
+    /// line number of the last line read, starting at 1
+    line_number: usize,
+}
+
+/// A record along with the line it starts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<Option<String>>,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            line: String::new(),
+            line_number: 0,
+        }
+    }
+
+    /// Reads the next record, [None] at the end of the input. Empty lines are skipped.
+    pub fn read_record(&mut self) -> io::Result<Option<Record>> {
+        loop {
+            if !self.read_line()? {
+                return Ok(None);
+            }
+            if !self.line.trim_end_matches(['\r', '\n']).is_empty() {
+                break;
+            }
+        }
+
+        let line = self.line_number;
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        // within a quoted field, which may continue on the next line
+        let mut in_quotes = false;
+        let mut pos = 0;
+        loop {
+            let rest = &self.line[pos..];
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None if in_quotes => {
+                    if !self.read_line()? {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unterminated quoted field starting on line {}", line)));
+                    }
+                    pos = 0;
+                    continue;
+                },
+                None => break,
+            };
+            pos += c.len_utf8();
+
+            match (in_quotes, c) {
+                (true, '"') if self.line[pos..].starts_with('"') => {
+                    field.push('"');
+                    pos += 1;
+                },
+                (true, '"') => in_quotes = false,
+                (true, c) => field.push(c),
+                (false, '"') if field.is_empty() && !quoted => {
+                    quoted = true;
+                    in_quotes = true;
+                },
+                (false, ',') => {
+                    fields.push(to_field(std::mem::take(&mut field), quoted));
+                    quoted = false;
+                },
+                (false, '\r' | '\n') => break,
+                (false, c) => field.push(c),
+            }
+        }
+        fields.push(to_field(field, quoted));
+        Ok(Some(Record { line, fields }))
+    }
+
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+        let read = self.input.read_line(&mut self.line)?;
+        self.line_number += 1;
+        Ok(read > 0)
+    }
+}
+
+fn to_field(field: String, quoted: bool) -> Option<String> {
+    match field.is_empty() && !quoted {
+        true => None,
+        false => Some(field),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::{CsvReader, Record};
+
+    fn fields(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values.iter().map(|v| v.map(str::to_string)).collect()
+    }
+
+    #[test]
+    fn quoted_fields_should_span_delimiters_and_lines() {
+        let mut reader = CsvReader::new("id,name,note\r\n1,\"Ada, \"\"the first\"\"\",\n\n2,\"\",\"two\nlines\"\n".as_bytes());
+        assert_eq!(
+            Some(Record { line: 1, fields: fields(&[Some("id"), Some("name"), Some("note")]) }),
+            reader.read_record().unwrap()
+        );
+        assert_eq!(
+            Some(Record { line: 2, fields: fields(&[Some("1"), Some("Ada, \"the first\""), None]) }),
+            reader.read_record().unwrap()
+        );
+        assert_eq!(
+            Some(Record { line: 4, fields: fields(&[Some("2"), Some(""), Some("two\nlines")]) }),
+            reader.read_record().unwrap()
+        );
+        assert_eq!(None, reader.read_record().unwrap());
+    }
+
+    #[test]
+    fn unterminated_quotes_should_be_an_error() {
+        let mut reader = CsvReader::new("1,\"open\n".as_bytes());
+        assert!(reader.read_record().is_err());
+    }
+}
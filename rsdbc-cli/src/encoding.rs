@@ -0,0 +1,85 @@
+//! Text encodings of binary values: hex as written by postgres, e.g. `\x0102`, and base64 for
+//! JSON.
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Hex prefixed by `\x`
+pub fn hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\\x{}", digits)
+}
+
+/// Decodes hex with or without a `\x` prefix
+pub fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits = text.strip_prefix("\\x").unwrap_or(text);
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("invalid hex `{}`", text));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(digits.get(i..i + 2).unwrap_or_default(), 16).map_err(|_| format!("invalid hex `{}`", text)))
+        .collect()
+}
+
+/// Standard base64 with padding
+pub fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes standard base64, with or without padding
+pub fn from_base64(text: &str) -> Result<Vec<u8>, String> {
+    let invalid = || format!("invalid base64 `{}`", text);
+    let digits = text.trim_end_matches('=');
+    if digits.len() % 4 == 1 || text.len() - digits.len() > 2 {
+        return Err(invalid());
+    }
+
+    let mut bytes = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, digit) in chunk.iter().enumerate() {
+            let value = BASE64_ALPHABET.iter().position(|b| b == digit).ok_or_else(invalid)?;
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        // 4 digits hold 3 bytes, fewer digits one byte less than their number
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::encoding::{base64, from_base64, from_hex, hex};
+
+    #[test]
+    fn bytes_should_be_base64_encoded() {
+        for (bytes, encoded) in [(&b""[..], ""), (b"f", "Zg=="), (b"fo", "Zm8="), (b"foobar", "Zm9vYmFy"), (&[0xff, 0], "/wA=")] {
+            assert_eq!(encoded, base64(bytes));
+            assert_eq!(bytes, from_base64(encoded).unwrap().as_slice());
+        }
+        assert_eq!(b"fo".to_vec(), from_base64("Zm8").unwrap());
+        assert!(from_base64("Z").is_err());
+        assert!(from_base64("Zm8*").is_err());
+    }
+
+    #[test]
+    fn bytes_should_be_hex_encoded() {
+        assert_eq!("\\x00ff", hex(&[0, 255]));
+        assert_eq!(vec![0, 255], from_hex("\\x00ff").unwrap());
+        assert_eq!(vec![0xab], from_hex("AB").unwrap());
+        assert!(from_hex("\\x0").is_err());
+        assert!(from_hex("zz").is_err());
+    }
+}
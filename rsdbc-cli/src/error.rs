@@ -0,0 +1,82 @@
+use std::io;
+use thiserror::Error;
+use rsdbc_core::error::RsdbcErrors;
+use crate::export::json_string;
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("IO error: `{0}`")]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Rsdbc(#[from] RsdbcErrors),
+}
+
+impl CliError {
+    /// The exit code of the process when it fails with this error, 2 for invalid arguments and
+    /// 1 otherwise
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            _ => 1,
+        }
+    }
+
+    /// This error as a single line JSON object for scripts to parse, e.g.
+    ///
+    /// ```text
+    /// {"error":{"message":"...","category":"BadGrammar","sql_state":"42P01","line":3,"sql":"SELECT * FROM missing"}}
+    /// ```
+    ///
+    /// The database's diagnostics are included when the error was reported by the database, as
+    /// are the line and SQL of the statement which failed.
+    pub fn to_json(&self) -> String {
+        let mut members = vec![("message", json_string(&self.to_string()))];
+        if let CliError::Rsdbc(e) = self {
+            members.push(("category", json_string(&format!("{:?}", e.category()))));
+            if let Some(db) = e.as_database_error() {
+                members.push(("kind", json_string(&format!("{:?}", db.kind))));
+                let optional = [
+                    ("sql_state", db.sql_state.as_deref().map(json_string)),
+                    ("vendor_code", db.vendor_code.map(|code| code.to_string())),
+                    ("detail", db.detail.as_deref().map(json_string)),
+                    ("hint", db.hint.as_deref().map(json_string)),
+                    ("constraint", db.constraint.as_deref().map(json_string)),
+                    ("position", db.position.map(|position| position.to_string())),
+                ];
+                members.extend(optional.into_iter().filter_map(|(name, value)| value.map(|value| (name, value))));
+            }
+            if let RsdbcErrors::Script { line, sql, .. } = e {
+                members.push(("line", line.to_string()));
+                members.push(("sql", json_string(sql)));
+            }
+        }
+        let members: Vec<String> = members.iter().map(|(name, value)| format!("\"{}\":{}", name, value)).collect();
+        format!("{{\"error\":{{{}}}}}", members.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsdbc_core::error::{DatabaseError, RsdbcErrors};
+    use crate::error::CliError;
+
+    #[test]
+    fn database_errors_should_be_written_as_json() {
+        let mut db = DatabaseError::from_sql_state("42P01", "relation \"missing\" does not exist");
+        db.position = Some(15);
+        let err = CliError::Rsdbc(RsdbcErrors::Script {
+            line: 3,
+            sql: "SELECT * FROM missing".to_string(),
+            source: Box::new(RsdbcErrors::Database(Box::new(db))),
+        });
+        let json = err.to_json();
+        assert!(json.starts_with("{\"error\":{\"message\":\"Statement at line 3 failed: Database error: relation \\\"missing\\\" does not exist"), "{}", json);
+        assert!(json.ends_with("\"sql_state\":\"42P01\",\"position\":15,\"line\":3,\"sql\":\"SELECT * FROM missing\"}}"), "{}", json);
+
+        assert_eq!("{\"error\":{\"message\":\"unknown format\"}}", CliError::Usage("unknown format".to_string()).to_json());
+    }
+}
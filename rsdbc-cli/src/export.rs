@@ -0,0 +1,269 @@
+use std::io::Write;
+use std::str::FromStr;
+use rsdbc_core::{ResultSet, RsdbcType};
+use crate::encoding::base64;
+use crate::error::CliError;
+use crate::table::{read_text, Table};
+
+/// How the rows of a query are written
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Comma separated values with a header, quoted as described by RFC 4180
+    Csv,
+    /// Tab separated values with a header, with tabs, newlines and backslashes escaped
+    Tsv,
+    /// A JSON array of an object per row
+    Json,
+    /// A JSON object per line
+    Ndjson,
+    /// Aligned columns, see [Table]
+    Table,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 5] = ["csv", "tsv", "json", "ndjson", "table"];
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "tsv" => Ok(Format::Tsv),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "table" => Ok(Format::Table),
+            _ => Err(format!("unknown format {}, expected one of {}", s, Format::NAMES.join(", "))),
+        }
+    }
+}
+
+/// How the values of a column are written as JSON, derived from the column's metadata
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Kind {
+    Boolean,
+    Number,
+    Bytes,
+    Text,
+    /// Columns without a declared type, e.g. sqlite expressions, whose values are numbers or
+    /// strings depending on their text
+    Untyped,
+}
+
+impl Kind {
+    fn of(r#type: RsdbcType, type_name: &str) -> Self {
+        if type_name.is_empty() {
+            return Kind::Untyped;
+        }
+        match r#type {
+            RsdbcType::Boolean => Kind::Boolean,
+            RsdbcType::Tinyint
+            | RsdbcType::Smallint
+            | RsdbcType::Integer
+            | RsdbcType::Bigint
+            | RsdbcType::Numeric
+            | RsdbcType::Decimal
+            | RsdbcType::Float
+            | RsdbcType::Real
+            | RsdbcType::Double => Kind::Number,
+            RsdbcType::Binary | RsdbcType::Varbinary | RsdbcType::Blob => Kind::Bytes,
+            _ => Kind::Text,
+        }
+    }
+}
+
+/// Writes the rows of `rs` to `out` in `format` as they are read, returning the number of rows.
+///
+/// Only [Format::Table] holds on to the rows, as it needs all of them to align the columns.
+pub fn write(rs: &mut dyn ResultSet, format: Format, out: &mut dyn Write) -> Result<u64, CliError> {
+    if format == Format::Table {
+        let table = Table::read(rs)?;
+        table.write_aligned(out)?;
+        return Ok(table.rows.len() as u64);
+    }
+
+    let metadata = rs.meta_data()?;
+    let columns: Vec<(String, RsdbcType, Kind)> = (0..metadata.num_columns())
+        .map(|i| {
+            let r#type = metadata.column_type(i);
+            (metadata.column_name(i), r#type, Kind::of(r#type, &metadata.column_type_name(i)))
+        })
+        .collect();
+    let names: Vec<&str> = columns.iter().map(|(name, ..)| name.as_str()).collect();
+
+    match format {
+        Format::Csv => writeln!(out, "{}", names.iter().map(|n| csv_field(n)).collect::<Vec<_>>().join(","))?,
+        Format::Tsv => writeln!(out, "{}", names.iter().map(|n| tsv_field(n)).collect::<Vec<_>>().join("\t"))?,
+        Format::Json => write!(out, "[")?,
+        _ => {},
+    }
+
+    let mut rows = 0;
+    while rs.next() {
+        match format {
+            Format::Csv | Format::Tsv => {
+                let mut fields = Vec::with_capacity(columns.len());
+                for (i, (_, r#type, _)) in columns.iter().enumerate() {
+                    let text = read_text(rs, i as u64, *r#type)?;
+                    fields.push(match format {
+                        Format::Csv => text.as_deref().map(csv_field).unwrap_or_default(),
+                        _ => text.as_deref().map(tsv_field).unwrap_or_default(),
+                    });
+                }
+                writeln!(out, "{}", fields.join(if format == Format::Csv { "," } else { "\t" }))?;
+            },
+            _ => {
+                let mut members = Vec::with_capacity(columns.len());
+                for (i, (name, r#type, kind)) in columns.iter().enumerate() {
+                    members.push(format!("{}:{}", json_string(name), json_value(rs, i as u64, *r#type, *kind)?));
+                }
+                let object = format!("{{{}}}", members.join(","));
+                match format {
+                    Format::Json => write!(out, "{}\n{}", if rows == 0 { "" } else { "," }, object)?,
+                    _ => writeln!(out, "{}", object)?,
+                }
+            },
+        }
+        rows += 1;
+    }
+
+    if format == Format::Json {
+        writeln!(out, "{}]", if rows == 0 { "" } else { "\n" })?;
+    }
+    out.flush()?;
+    Ok(rows)
+}
+
+fn json_value(rs: &dyn ResultSet, i: u64, r#type: RsdbcType, kind: Kind) -> Result<String, CliError> {
+    let json = match kind {
+        Kind::Boolean => match rs.get_bool(i) {
+            Ok(value) => value.map(|v| v.to_string()),
+            Err(_) => read_text(rs, i, r#type)?.map(|text| json_string(&text)),
+        },
+        Kind::Bytes => rs.get_bytes(i)?.map(|bytes| json_string(&base64(&bytes))),
+        Kind::Text => read_text(rs, i, r#type)?.map(|text| json_string(&text)),
+        Kind::Number | Kind::Untyped => match rs.get_string(i) {
+            Ok(text) => text.map(|text| json_number(&text)),
+            // sqlite untyped expressions may be blobs
+            Err(_) if kind == Kind::Untyped => rs.get_bytes(i)?.map(|bytes| json_string(&base64(&bytes))),
+            Err(_) => read_text(rs, i, r#type)?.map(|text| json_number(&text)),
+        },
+    };
+    Ok(json.unwrap_or_else(|| "null".to_string()))
+}
+
+/// The text as a JSON number if it is one, which keeps the precision of exact numerics, or as
+/// a string otherwise, e.g. `NaN`
+fn json_number(text: &str) -> String {
+    match is_json_number(text) {
+        true => text.to_string(),
+        false => json_string(text),
+    }
+}
+
+/// Whether `text` matches the JSON number grammar `-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`
+fn is_json_number(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let digits = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let mut i = usize::from(bytes.first() == Some(&b'-'));
+    match digits(i) {
+        0 => return false,
+        n if n > 1 && bytes[i] == b'0' => return false,
+        n => i += n,
+    }
+    if bytes.get(i) == Some(&b'.') {
+        match digits(i + 1) {
+            0 => return false,
+            n => i += n + 1,
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        match digits(i) {
+            0 => return false,
+            n => i += n,
+        }
+    }
+    i == bytes.len()
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn csv_field(s: &str) -> String {
+    match s.is_empty() || s.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", s.replace('"', "\"\"")),
+        false => s.to_string(),
+    }
+}
+
+fn tsv_field(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use rsdbc_core::connection::ConnectionFactory;
+    use rsdbc_sqlite::options::SqliteConnectOptions;
+    use crate::export::{csv_field, is_json_number, write, Format};
+
+    fn export(format: Format) -> String {
+        let mut connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+        connection.execute_script("\
+            CREATE TABLE person (id INTEGER, name VARCHAR(20), active BOOLEAN, score DECIMAL(5, 2), photo BLOB);\
+            INSERT INTO person VALUES (1, 'Ada, \"the first\"', 1, 12.5, x'000102');\
+            INSERT INTO person VALUES (2, NULL, 0, NULL, NULL);").unwrap();
+        let mut stmt = connection.create_statement("SELECT *, id * 2 AS twice FROM person ORDER BY id").unwrap();
+        let mut rs = stmt.execute_query(&[]).unwrap();
+        let mut out = Vec::new();
+        assert_eq!(2, write(rs.as_mut(), format, &mut out).unwrap());
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn json_values_should_follow_the_column_types() {
+        let first = "{\"id\":1,\"name\":\"Ada, \\\"the first\\\"\",\"active\":true,\"score\":12.5,\"photo\":\"AAEC\",\"twice\":2}";
+        let second = "{\"id\":2,\"name\":null,\"active\":false,\"score\":null,\"photo\":null,\"twice\":4}";
+        assert_eq!(format!("[\n{},\n{}\n]\n", first, second), export(Format::Json));
+        assert_eq!(format!("{}\n{}\n", first, second), export(Format::Ndjson));
+    }
+
+    #[test]
+    fn delimited_values_should_be_quoted_or_escaped() {
+        let expected = "id,name,active,score,photo,twice\n1,\"Ada, \"\"the first\"\"\",1,12.5,\\x000102,2\n2,,0,,,4\n";
+        assert_eq!(expected, export(Format::Csv));
+        assert!(export(Format::Tsv).starts_with("id\tname\tactive\tscore\tphoto\ttwice\n1\tAda, \"the first\"\t1\t12.5\t"));
+        assert_eq!("\"a\nb\"", csv_field("a\nb"));
+    }
+
+    #[test]
+    fn numbers_should_match_the_json_grammar() {
+        for number in ["0", "-1", "12.50", "1e10", "-0.5E-3"] {
+            assert!(is_json_number(number), "{}", number);
+        }
+        for text in ["", "-", "01", "1.", ".5", "1e", "NaN", "inf", "1 "] {
+            assert!(!is_json_number(text), "{}", text);
+        }
+    }
+}
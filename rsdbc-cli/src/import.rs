@@ -0,0 +1,531 @@
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use rsdbc_core::connection::Connection;
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::{find_by_name, Dialect, Nullability, RsdbcType, Value};
+use crate::csv::CsvReader;
+use crate::encoding::{from_base64, from_hex};
+use crate::error::CliError;
+
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// MySQL's limit on the number of placeholders in a statement
+const MYSQL_MAX_PLACEHOLDERS: usize = 65535;
+
+/// How the records of an import are read
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// Comma separated values whose header names the columns, see [CsvReader]
+    Csv,
+    /// A JSON array of an object per row, which is read as a whole
+    Json,
+    /// A JSON object per line
+    Ndjson,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 3] = ["csv", "json", "ndjson"];
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            _ => Err(format!("unknown format {}, expected one of {}", s, Format::NAMES.join(", "))),
+        }
+    }
+}
+
+/// A column of a table as described by the metadata of a query on it
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub name: String,
+    pub r#type: RsdbcType,
+    /// The name of the type in the database, empty for columns without a declared type, e.g.
+    /// in sqlite
+    pub type_name: String,
+    pub precision: Option<u64>,
+    pub scale: Option<u64>,
+    pub nullability: Nullability,
+}
+
+/// The columns of `table`, read from the metadata of a query which returns no rows. Returns
+/// [RsdbcErrors::NoSuchElement] if the database reports there is no such table.
+pub fn table_columns(connection: &mut dyn Connection, table: &str) -> Result<Vec<Column>, CliError> {
+    let sql = format!("SELECT * FROM {} WHERE 1 = 0", connection.dialect().quote_identifier(table));
+    let mut stmt = connection.create_statement(&sql).map_err(|e| missing_table(e, table))?;
+    let rs = stmt.execute_query(&[]).map_err(|e| missing_table(e, table))?;
+    let metadata = rs.meta_data()?;
+    Ok((0..metadata.num_columns())
+        .map(|i| Column {
+            name: metadata.column_name(i),
+            r#type: metadata.column_type(i),
+            type_name: metadata.column_type_name(i),
+            precision: metadata.precision(i),
+            scale: metadata.scale(i),
+            nullability: metadata.is_nullable(i),
+        })
+        .collect())
+}
+
+fn missing_table(e: RsdbcErrors, table: &str) -> RsdbcErrors {
+    let missing = e.as_database_error().is_some_and(|db_error| match db_error.sql_state.as_deref() {
+        // postgres' undefined_table and MySQL's ER_NO_SUCH_TABLE
+        Some(sql_state) => sql_state == "42P01" || sql_state == "42S02",
+        // SQLite has no SQLSTATE
+        None => db_error.message.starts_with("no such table"),
+    });
+    if missing {
+        RsdbcErrors::NoSuchElement(format!("table {}", table))
+    } else {
+        e
+    }
+}
+
+/// The number of rows an import inserted and rejected
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub imported: u64,
+    pub rejected: u64,
+}
+
+/// Loads records into a table, converting their fields to the types of the columns they are
+/// mapped to by name: by the header of CSV or by the keys of each JSON object, whose missing
+/// keys leave their columns to their defaults.
+///
+/// Records whose fields can't be converted are rejected and reported while the others are
+/// inserted in batches, each with the fastest way the driver offers: `COPY` for postgres,
+/// multi-row `INSERT`s for MySQL and a prepared `INSERT` otherwise. The whole import is a single
+/// transaction, so nothing is inserted when the database fails.
+pub struct Import {
+    table: String,
+    format: Format,
+    batch_size: usize,
+}
+
+impl Import {
+    pub fn new(table: impl Into<String>, format: Format) -> Self {
+        Self {
+            table: table.into(),
+            format,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the number of rows inserted at a time, [DEFAULT_BATCH_SIZE] by default
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Imports the records read from `input`, writing progress and rejected records to `log`
+    pub fn run(&self, connection: &mut dyn Connection, input: &mut dyn BufRead, log: &mut dyn Write) -> Result<Summary, CliError> {
+        let columns = table_columns(connection, &self.table)?;
+        connection.begin_transaction()?;
+        match self.load(connection, &columns, input, log) {
+            Ok(summary) => {
+                connection.commit_transaction()?;
+                writeln!(log, "imported {} rows into {}, {} rejected", summary.imported, self.table, summary.rejected)?;
+                Ok(summary)
+            },
+            Err(e) => {
+                // the error which stopped the import is more useful than one from the rollback
+                let _ = connection.rollback_transaction();
+                Err(e)
+            },
+        }
+    }
+
+    fn load(&self, connection: &mut dyn Connection, columns: &[Column], input: &mut dyn BufRead, log: &mut dyn Write) -> Result<Summary, CliError> {
+        let mut source = Source::open(self.format, input, &self.table, columns)?;
+        let mut summary = Summary::default();
+        let mut batch = Vec::with_capacity(self.batch_size);
+        // the columns of the rows in the batch, which is inserted before a row with other columns
+        let mut target_columns: Vec<&Column> = Vec::new();
+        while let Some(record) = source.next_record()? {
+            let row = record.fields.and_then(|(targets, fields)| {
+                let targets: Vec<&Column> = targets.iter().map(|i| &columns[*i]).collect();
+                let row = coerce_row(&targets, &fields, source.bytes())?;
+                Ok((targets, row))
+            });
+            match row {
+                Ok((targets, row)) => {
+                    if !same_columns(&targets, &target_columns) {
+                        if !batch.is_empty() {
+                            summary.imported += insert(connection, &self.table, &target_columns, std::mem::take(&mut batch))?;
+                        }
+                        target_columns = targets;
+                    }
+                    batch.push(row);
+                },
+                Err(reason) => {
+                    summary.rejected += 1;
+                    writeln!(log, "rejected {}: {}", record.location, reason)?;
+                },
+            }
+            if batch.len() == self.batch_size {
+                summary.imported += insert(connection, &self.table, &target_columns, std::mem::take(&mut batch))?;
+                writeln!(log, "{} rows imported, {} rejected", summary.imported, summary.rejected)?;
+            }
+        }
+        if !batch.is_empty() {
+            summary.imported += insert(connection, &self.table, &target_columns, batch)?;
+        }
+        Ok(summary)
+    }
+}
+
+fn same_columns(a: &[&Column], b: &[&Column]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| std::ptr::eq(*a, *b))
+}
+
+/// The index of the column each of `names` refers to
+pub(crate) fn map_columns(table: &str, columns: &[Column], names: &[Option<String>]) -> Result<Vec<usize>, CliError> {
+    let mut targets = Vec::with_capacity(names.len());
+    for name in names {
+        let name = name.as_deref().ok_or_else(|| CliError::Usage("column names can't be empty".to_string()))?;
+        let target = find_by_name(columns.iter().map(|c| c.name.as_str()), name)
+            .ok_or_else(|| CliError::Usage(format!("column {} not found in table {}", name, table)))?;
+        if targets.contains(&target) {
+            return Err(CliError::Usage(format!("column {} is named more than once", name)));
+        }
+        targets.push(target);
+    }
+    Ok(targets)
+}
+
+/// A record read from the input along with where it was found, e.g. `line 3`. The fields are
+/// paired with the indexes of the columns they are in, or are an error describing why the
+/// record is rejected when it doesn't fit the columns.
+struct SourceRecord {
+    location: String,
+    fields: Result<(Vec<usize>, Vec<Option<String>>), String>,
+}
+
+/// The records of the input, with the fields of each mapped to the columns of the table
+enum Source<'a> {
+    Csv {
+        reader: CsvReader<&'a mut dyn BufRead>,
+        /// the columns named by the header
+        targets: Vec<usize>,
+    },
+    Json {
+        values: Box<dyn Iterator<Item = serde_json::Result<serde_json::Value>> + 'a>,
+        table: &'a str,
+        columns: &'a [Column],
+        count: usize,
+    },
+}
+
+impl<'a> Source<'a> {
+    /// Opens the input, reading the header of CSV and mapping its names to `columns`
+    fn open(format: Format, input: &'a mut dyn BufRead, table: &'a str, columns: &'a [Column]) -> Result<Self, CliError> {
+        let values: Box<dyn Iterator<Item = serde_json::Result<serde_json::Value>> + 'a> = match format {
+            Format::Csv => {
+                let mut reader = CsvReader::new(input);
+                let targets = match reader.read_record()? {
+                    Some(header) => map_columns(table, columns, &header.fields)?,
+                    None => Vec::new(),
+                };
+                return Ok(Source::Csv { reader, targets });
+            },
+            Format::Json => match serde_json::from_reader(input).map_err(io::Error::from)? {
+                serde_json::Value::Array(values) => Box::new(values.into_iter().map(Ok)),
+                _ => return Err(CliError::Usage("expected a JSON array of objects".to_string())),
+            },
+            Format::Ndjson => Box::new(serde_json::Deserializer::from_reader(input).into_iter()),
+        };
+        Ok(Source::Json { values, table, columns, count: 0 })
+    }
+
+    /// How binary values are written, hex prefixed by `\x` in CSV and base64 in JSON
+    fn bytes(&self) -> fn(&str) -> Result<Vec<u8>, String> {
+        match self {
+            Source::Csv { .. } => hex_or_text,
+            Source::Json { .. } => from_base64,
+        }
+    }
+
+    fn next_record(&mut self) -> Result<Option<SourceRecord>, CliError> {
+        match self {
+            Source::Csv { reader, targets } => {
+                let width = targets.len();
+                Ok(reader.read_record()?.map(|record| SourceRecord {
+                    location: format!("line {}", record.line),
+                    fields: match record.fields.len() == width {
+                        true => Ok((targets.clone(), record.fields)),
+                        false => Err(format!("expected {} fields, found {}", width, record.fields.len())),
+                    },
+                }))
+            },
+            Source::Json { values, table, columns, count } => {
+                let value = match values.next().transpose().map_err(io::Error::from)? {
+                    Some(value) => value,
+                    None => return Ok(None),
+                };
+                *count += 1;
+                Ok(Some(SourceRecord {
+                    location: format!("record {}", count),
+                    fields: json_fields(value, table, columns),
+                }))
+            },
+        }
+    }
+}
+
+/// Decodes hex prefixed by `\x`, as written by [crate::table::read_text], and takes any other
+/// text as it is
+pub(crate) fn hex_or_text(text: &str) -> Result<Vec<u8>, String> {
+    match text.starts_with("\\x") {
+        true => from_hex(text),
+        false => Ok(text.as_bytes().to_vec()),
+    }
+}
+
+/// The members of a JSON object as text along with the indexes of the columns of `table` their
+/// keys name, in the order of the columns
+fn json_fields(value: serde_json::Value, table: &str, columns: &[Column]) -> Result<(Vec<usize>, Vec<Option<String>>), String> {
+    let object = match value {
+        serde_json::Value::Object(object) => object,
+        _ => return Err("expected a JSON object".to_string()),
+    };
+    let names: Vec<Option<String>> = object.keys().map(|key| Some(key.clone())).collect();
+    let targets = map_columns(table, columns, &names).map_err(|e| e.to_string())?;
+    let mut fields: Vec<(usize, Option<String>)> = targets
+        .into_iter()
+        .zip(object.into_iter().map(|(_, value)| value))
+        .map(|(target, value)| {
+            let field = match value {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(s) => Some(s),
+                // arrays and objects are kept as JSON, e.g. for json columns
+                value => Some(value.to_string()),
+            };
+            (target, field)
+        })
+        .collect();
+    fields.sort_by_key(|(target, _)| *target);
+    Ok(fields.into_iter().unzip())
+}
+
+pub(crate) fn coerce_row(columns: &[&Column], fields: &[Option<String>], bytes: fn(&str) -> Result<Vec<u8>, String>) -> Result<Vec<Option<Value>>, String> {
+    columns
+        .iter()
+        .zip(fields)
+        .map(|(column, field)| match field {
+            Some(text) => coerce(text, column, bytes).map(Some).map_err(|reason| format!("column {}: {}", column.name, reason)),
+            None => Ok(None),
+        })
+        .collect()
+}
+
+/// Converts `text` to the value bound for `column`.
+///
+/// Exact numerics are checked but bound as text so no precision is lost, as are dates, times
+/// and any other type the database parses itself.
+fn coerce(text: &str, column: &Column, bytes: fn(&str) -> Result<Vec<u8>, String>) -> Result<Value, String> {
+    if column.type_name.is_empty() {
+        return Ok(Value::String(text.to_string()));
+    }
+    match column.r#type {
+        RsdbcType::Boolean => match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "t" | "yes" | "y" | "1" => Ok(Value::Bool(true)),
+            "false" | "f" | "no" | "n" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!("invalid boolean `{}`", text)),
+        },
+        RsdbcType::Tinyint | RsdbcType::Smallint | RsdbcType::Integer | RsdbcType::Bigint => {
+            text.trim().parse().map(Value::Int64).map_err(|_| format!("invalid integer `{}`", text))
+        },
+        RsdbcType::Float | RsdbcType::Real | RsdbcType::Double => {
+            text.trim().parse().map(Value::Double).map_err(|_| format!("invalid number `{}`", text))
+        },
+        RsdbcType::Numeric | RsdbcType::Decimal => match text.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(Value::String(text.trim().to_string())),
+            _ => Err(format!("invalid number `{}`", text)),
+        },
+        RsdbcType::Binary | RsdbcType::Varbinary | RsdbcType::Blob => bytes(text).map(Value::Bytes),
+        _ => Ok(Value::String(text.to_string())),
+    }
+}
+
+/// Inserts `rows` into the `columns` of `table`, returning the number of rows inserted
+pub(crate) fn insert(connection: &mut dyn Connection, table: &str, columns: &[&Column], rows: Vec<Vec<Option<Value>>>) -> Result<u64, CliError> {
+    let dialect = connection.dialect();
+    let names: Vec<String> = columns.iter().map(|c| dialect.quote_identifier(&c.name)).collect();
+    let names = names.join(", ");
+    let table = dialect.quote_identifier(table);
+    match dialect {
+        Dialect::Postgres => {
+            let sql = format!("COPY {} ({}) FROM STDIN", table, names);
+            Ok(connection.copy_in(&sql, &mut copy_text(&rows).as_bytes())?)
+        },
+        Dialect::MySql => {
+            let mut inserted = 0;
+            for (nulls, rows) in group_by_nulls(rows) {
+                let placeholders = nulls.iter().filter(|null| !**null).count().max(1);
+                for chunk in rows.chunks(MYSQL_MAX_PLACEHOLDERS / placeholders) {
+                    let values = vec![values_sql(&nulls); chunk.len()].join(", ");
+                    let sql = format!("INSERT INTO {} ({}) VALUES {}", table, names, values);
+                    inserted += connection.create_statement(&sql)?.execute_update(&chunk.concat())?;
+                }
+            }
+            Ok(inserted)
+        },
+        _ => {
+            let mut inserted = 0;
+            for (nulls, rows) in group_by_nulls(rows) {
+                let sql = format!("INSERT INTO {} ({}) VALUES {}", table, names, values_sql(&nulls));
+                let mut stmt = connection.create_statement(&sql)?;
+                for row in rows {
+                    inserted += stmt.execute_update(&row)?;
+                }
+            }
+            Ok(inserted)
+        },
+    }
+}
+
+/// Groups rows by which of their fields are NULL, which can't be bound and so is written in the
+/// statement instead, along with the values of the other fields
+fn group_by_nulls(rows: Vec<Vec<Option<Value>>>) -> Vec<(Vec<bool>, Vec<Vec<Value>>)> {
+    let mut groups: Vec<(Vec<bool>, Vec<Vec<Value>>)> = Vec::new();
+    for row in rows {
+        let nulls: Vec<bool> = row.iter().map(Option::is_none).collect();
+        let values = row.into_iter().flatten().collect();
+        match groups.iter_mut().find(|(group, _)| *group == nulls) {
+            Some((_, rows)) => rows.push(values),
+            None => groups.push((nulls, vec![values])),
+        }
+    }
+    groups
+}
+
+fn values_sql(nulls: &[bool]) -> String {
+    let values: Vec<&str> = nulls.iter().map(|null| if *null { "NULL" } else { "?" }).collect();
+    format!("({})", values.join(", "))
+}
+
+/// The rows in the text format of postgres `COPY`, a line per row with tab separated fields and
+/// `\N` for NULL
+fn copy_text(rows: &[Vec<Option<Value>>]) -> String {
+    let mut text = String::new();
+    for row in rows {
+        let fields: Vec<String> = row
+            .iter()
+            .map(|value| match value {
+                None => "\\N".to_string(),
+                Some(Value::Bool(b)) => if *b { "t" } else { "f" }.to_string(),
+                Some(Value::Int64(n)) => n.to_string(),
+                Some(Value::Double(n)) => n.to_string(),
+                Some(Value::String(s)) => copy_escape(s),
+                Some(Value::Bytes(bytes)) => copy_escape(&crate::encoding::hex(bytes)),
+                Some(v) => copy_escape(&format!("{:?}", v)),
+            })
+            .collect();
+        text.push_str(&fields.join("\t"));
+        text.push('\n');
+    }
+    text
+}
+
+fn copy_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use rsdbc_core::connection::{Connection, ConnectionFactory};
+    use rsdbc_core::Value;
+    use rsdbc_sqlite::options::SqliteConnectOptions;
+    use crate::error::CliError;
+    use crate::export::Format as ExportFormat;
+    use crate::import::{copy_text, Format, Import, Summary};
+    use crate::query;
+
+    fn connection() -> Box<dyn Connection> {
+        let mut connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+        connection
+            .execute_script("CREATE TABLE person (id INTEGER, name VARCHAR(20), active BOOLEAN, score DECIMAL(5, 2), photo BLOB, note)")
+            .unwrap();
+        connection
+    }
+
+    fn rows(connection: &mut dyn Connection) -> String {
+        let mut out = Vec::new();
+        query::run(connection, "SELECT * FROM person ORDER BY id", ExportFormat::Csv, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_records_should_be_mapped_to_columns_by_header() {
+        let mut connection = connection();
+        let input = "NAME,id,Active,score,photo\n\
+            \"Ada, \"\"the first\"\"\",1,yes,12.50,\\x000102\n\
+            Bob,two,no,,\n\
+            \"\",3,f,1e2,\n\
+            ,4,true,,raw\n\
+            5\n";
+        let mut log = Vec::new();
+        let summary = Import::new("person", Format::Csv)
+            .with_batch_size(2)
+            .run(connection.as_mut(), &mut input.as_bytes(), &mut log)
+            .unwrap();
+        assert_eq!(Summary { imported: 3, rejected: 2 }, summary);
+
+        let log = String::from_utf8(log).unwrap();
+        assert!(log.contains("rejected line 3: column id: invalid integer `two`\n"), "{}", log);
+        assert!(log.contains("rejected line 6: expected 5 fields, found 1\n"), "{}", log);
+        assert!(log.contains("2 rows imported, 1 rejected\n"), "{}", log);
+        assert!(log.ends_with("imported 3 rows into person, 2 rejected\n"), "{}", log);
+
+        // sqlite's numeric affinity stores decimals as numbers
+        let expected = "id,name,active,score,photo,note\n\
+            1,\"Ada, \"\"the first\"\"\",1,12.5,\\x000102,\n\
+            3,\"\",0,100,,\n\
+            4,,1,,\\x726177,\n";
+        assert_eq!(expected, rows(connection.as_mut()));
+    }
+
+    #[test]
+    fn json_records_should_be_mapped_to_columns_by_key() {
+        for (format, input) in [
+            (Format::Json, "[{\"id\": 1, \"note\": {\"a\": [1]}, \"photo\": \"AAEC\"}, {\"id\": 2, \"NAME\": \"Bob\"}, {\"id\": 3, \"photo\": \"*\"}, {\"id\": 4, \"age\": 2}, {\"id\": 5, \"name\": \"Eve\"}]"),
+            (Format::Ndjson, "{\"id\": 1, \"note\": {\"a\": [1]}, \"photo\": \"AAEC\"}\n{\"id\": 2, \"NAME\": \"Bob\"}\n{\"id\": 3, \"photo\": \"*\"}\n{\"id\": 4, \"age\": 2}\n{\"id\": 5, \"name\": \"Eve\"}\n"),
+        ] {
+            let mut connection = connection();
+            let mut log = Vec::new();
+            let summary = Import::new("person", format).run(connection.as_mut(), &mut input.as_bytes(), &mut log).unwrap();
+            assert_eq!(Summary { imported: 3, rejected: 2 }, summary);
+
+            let log = String::from_utf8(log).unwrap();
+            assert!(log.contains("rejected record 3: column photo: invalid base64 `*`\n"), "{}", log);
+            assert!(log.contains("rejected record 4: column age not found in table person\n"), "{}", log);
+            assert_eq!(
+                "id,name,active,score,photo,note\n1,,,,\\x000102,\"{\"\"a\"\":[1]}\"\n2,Bob,,,,\n5,Eve,,,,\n",
+                rows(connection.as_mut())
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_columns_should_stop_the_import() {
+        let mut connection = connection();
+        let mut log = Vec::new();
+        let err = Import::new("person", Format::Csv)
+            .run(connection.as_mut(), &mut "id,age\n1,2\n".as_bytes(), &mut log)
+            .unwrap_err();
+        assert!(matches!(&err, CliError::Usage(message) if message == "column age not found in table person"), "{:?}", err);
+        assert_eq!("id,name,active,score,photo,note\n", rows(connection.as_mut()));
+    }
+
+    #[test]
+    fn copy_text_should_escape_fields() {
+        let rows = vec![vec![Some(Value::Int64(1)), None, Some(Value::String("a\tb\\c\n".to_string())), Some(Value::Bool(true)), Some(Value::Bytes(vec![1]))]];
+        assert_eq!("1\t\\N\ta\\tb\\\\c\\n\tt\t\\\\x01\n", copy_text(&rows));
+    }
+}
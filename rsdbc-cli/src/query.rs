@@ -0,0 +1,106 @@
+use std::io::Write;
+use rsdbc_core::connection::Connection;
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::script::split;
+use rsdbc_core::sql::words;
+use rsdbc_core::Dialect;
+use crate::error::CliError;
+use crate::export::{self, Format};
+
+/// Executes the statements of `script` in order, writing the rows of each query to `out` in
+/// `format`, see [export::write]. Stops at the first statement which fails.
+pub fn run(connection: &mut dyn Connection, script: &str, format: Format, out: &mut dyn Write) -> Result<(), CliError> {
+    for statement in split(script, connection.dialect()) {
+        execute(connection, &statement.sql, format, out).map_err(|e| match e {
+            CliError::Rsdbc(e) => CliError::Rsdbc(RsdbcErrors::Script {
+                line: statement.line,
+                sql: statement.sql.clone(),
+                source: Box::new(e.with_sql(&statement.sql)),
+            }),
+            e => e,
+        })?;
+    }
+    Ok(())
+}
+
+fn execute(connection: &mut dyn Connection, sql: &str, format: Format, out: &mut dyn Write) -> Result<(), CliError> {
+    let dialect = connection.dialect();
+    let mut stmt = connection.create_statement(sql)?;
+    match returns_rows(sql, dialect) {
+        true => {
+            export::write(stmt.execute_query(&[])?.as_mut(), format, out)?;
+        },
+        false => {
+            stmt.execute_update(&[])?;
+        },
+    }
+    Ok(())
+}
+
+/// Whether `sql` returns rows and so is executed as a query.
+///
+/// Statements don't describe their results until they are executed so this is decided by the
+/// first keyword, or a `RETURNING` clause, outside of quoted text and comments.
+pub fn returns_rows(sql: &str, dialect: Dialect) -> bool {
+    let mut words = words(dialect, sql).into_iter().map(str::to_ascii_uppercase);
+    match words.next().as_deref() {
+        Some("SELECT" | "WITH" | "VALUES" | "TABLE" | "SHOW" | "PRAGMA" | "EXPLAIN" | "DESCRIBE" | "DESC") => true,
+        _ => words.any(|word| word == "RETURNING"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use rsdbc_core::connection::ConnectionFactory;
+    use rsdbc_core::error::RsdbcErrors;
+    use rsdbc_sqlite::options::SqliteConnectOptions;
+    use crate::error::CliError;
+    use crate::export::Format;
+    use rsdbc_core::Dialect;
+    use crate::query::{returns_rows, run};
+
+    #[test]
+    fn rows_of_each_query_should_be_written() {
+        let mut connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+        let script = "CREATE TABLE t (id INTEGER);\nINSERT INTO t VALUES (1), (2);\nSELECT id FROM t ORDER BY id;\nSELECT COUNT(*) AS n FROM t;";
+        let mut out = Vec::new();
+        run(connection.as_mut(), script, Format::Ndjson, &mut out).unwrap();
+        assert_eq!("{\"id\":1}\n{\"id\":2}\n{\"n\":2}\n", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn statements_mentioning_returning_should_not_write_rows() {
+        let script = "CREATE TABLE t (note TEXT); INSERT INTO t VALUES ('no returning here'); SELECT note FROM t";
+        for (format, expected) in [
+            (Format::Json, "[\n{\"note\":\"no returning here\"}\n]\n"),
+            (Format::Csv, "note\nno returning here\n"),
+        ] {
+            let mut connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+            let mut out = Vec::new();
+            run(connection.as_mut(), script, format, &mut out).unwrap();
+            assert_eq!(expected, String::from_utf8(out).unwrap());
+        }
+    }
+
+    #[test]
+    fn failed_statements_should_report_their_line() {
+        let mut connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+        let mut out = Vec::new();
+        let err = run(connection.as_mut(), "SELECT 1;\n\nSELECT * FROM missing;\nSELECT 2;", Format::Csv, &mut out).unwrap_err();
+        assert!(matches!(err, CliError::Rsdbc(RsdbcErrors::Script { line: 3, .. })), "{:?}", err);
+        assert_eq!("1\n1\n", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn queries_should_be_recognised_by_their_first_keyword() {
+        assert!(returns_rows("select 1", Dialect::Generic));
+        assert!(returns_rows("  WITH t AS (SELECT 1) SELECT * FROM t", Dialect::Generic));
+        assert!(returns_rows("-- a comment\nSELECT 1", Dialect::Generic));
+        assert!(returns_rows("INSERT INTO t VALUES (1) RETURNING id", Dialect::Postgres));
+        assert!(!returns_rows("INSERT INTO t VALUES (1)", Dialect::Generic));
+        assert!(!returns_rows("CREATE TABLE selected (id INT)", Dialect::Generic));
+        assert!(!returns_rows("INSERT INTO t VALUES ('returning') -- returning", Dialect::Postgres));
+        assert!(!returns_rows("UPDATE \"returning\" SET a = 1", Dialect::Postgres));
+    }
+}
@@ -0,0 +1,403 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+use rsdbc_core::connection::Connection;
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::script::{is_complete, split};
+use rsdbc_core::{Dialect, Value};
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+use crate::error::CliError;
+use crate::query::returns_rows;
+use crate::table::Table;
+
+const PROMPT: &str = "rsdbc> ";
+const CONTINUATION_PROMPT: &str = "  ...> ";
+
+const HELP: &str = "\
+\\dt               list tables
+\\d NAME           describe table NAME
+\\timing [on|off]  show how long each statement takes
+\\x [on|off]       show rows as records
+\\s                show the history
+\\?                show this help
+\\q                quit
+Statements end with ; and may span several lines.
+";
+
+/// An interactive session which reads statements and meta-commands, such as `\dt`, line by line
+/// and writes their results as tables
+pub struct Repl {
+    connection: Box<dyn Connection>,
+    timing: bool,
+    expanded: bool,
+    history: Vec<String>,
+    history_file: Option<PathBuf>,
+}
+
+/// What a line source returned
+enum Input {
+    Line(String),
+    /// The line being edited was abandoned, e.g. with Ctrl-C
+    Interrupted,
+    End,
+}
+
+/// Where the lines of a session are read from
+trait Lines {
+    fn read_line(&mut self, prompt: &str) -> Result<Input, CliError>;
+
+    /// Makes an entry available for recall while editing
+    fn add_history(&mut self, _entry: &str) {}
+}
+
+/// Lines piped in, which are read without prompts
+struct Piped<'a> {
+    input: &'a mut dyn BufRead,
+}
+
+impl Lines for Piped<'_> {
+    fn read_line(&mut self, _prompt: &str) -> Result<Input, CliError> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(Input::End);
+        }
+        let len = line.trim_end_matches(&['\r', '\n'][..]).len();
+        line.truncate(len);
+        Ok(Input::Line(line))
+    }
+}
+
+impl Lines for Editor<()> {
+    fn read_line(&mut self, prompt: &str) -> Result<Input, CliError> {
+        match self.readline(prompt) {
+            Ok(line) => Ok(Input::Line(line)),
+            Err(ReadlineError::Interrupted) => Ok(Input::Interrupted),
+            Err(ReadlineError::Eof) => Ok(Input::End),
+            Err(ReadlineError::Io(e)) => Err(CliError::Io(e)),
+            Err(e) => Err(CliError::Io(io::Error::other(e))),
+        }
+    }
+
+    fn add_history(&mut self, entry: &str) {
+        self.add_history_entry(entry);
+    }
+}
+
+impl Repl {
+    pub fn new(connection: Box<dyn Connection>) -> Self {
+        Self {
+            connection,
+            timing: false,
+            expanded: false,
+            history: Vec::new(),
+            history_file: None,
+        }
+    }
+
+    /// Sets the file the history is read from when the session starts and appended to
+    pub fn with_history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.history_file = Some(path.into());
+        self
+    }
+
+    /// Reads from `input` until it ends or `\q`. Statements which fail are reported to `out`
+    /// without ending the session.
+    pub fn run(&mut self, input: &mut dyn BufRead, out: &mut dyn Write) -> Result<(), CliError> {
+        self.read(&mut Piped { input }, out)
+    }
+
+    /// Reads from the terminal with prompts, line editing and recall of the history until
+    /// Ctrl-D or `\q`. Ctrl-C abandons the statement being entered.
+    pub fn run_interactive(&mut self, out: &mut dyn Write) -> Result<(), CliError> {
+        self.read(&mut Editor::<()>::new(), out)
+    }
+
+    fn read(&mut self, lines: &mut dyn Lines, out: &mut dyn Write) -> Result<(), CliError> {
+        self.load_history()?;
+        self.history.iter().for_each(|entry| lines.add_history(entry));
+        let dialect = self.connection.dialect();
+        let mut buffer = String::new();
+        loop {
+            let line = match lines.read_line(if buffer.is_empty() { PROMPT } else { CONTINUATION_PROMPT })? {
+                Input::Line(line) => line,
+                Input::Interrupted => {
+                    buffer.clear();
+                    continue;
+                },
+                Input::End => break,
+            };
+
+            let command = line.trim();
+            if buffer.is_empty() && command.is_empty() {
+                continue;
+            }
+            // meta-commands are only recognised at the start of a statement
+            if buffer.is_empty() && command.starts_with('\\') {
+                self.remember(command, lines)?;
+                match self.meta_command(command, out)? {
+                    true => return Ok(()),
+                    false => continue,
+                }
+            }
+
+            buffer.push_str(&line);
+            buffer.push('\n');
+            if is_complete(&buffer, dialect) {
+                self.remember(buffer.trim(), lines)?;
+                self.execute_buffer(&buffer, dialect, out)?;
+                buffer.clear();
+            }
+        }
+
+        // a statement missing its delimiter at the end of the input
+        if !buffer.trim().is_empty() {
+            self.remember(buffer.trim(), lines)?;
+            self.execute_buffer(&buffer, dialect, out)?;
+        }
+        Ok(())
+    }
+
+    fn execute_buffer(&mut self, buffer: &str, dialect: Dialect, out: &mut dyn Write) -> Result<(), CliError> {
+        for statement in split(buffer, dialect) {
+            let start = Instant::now();
+            if let Err(e) = self.execute(&statement.sql, out) {
+                writeln!(out, "ERROR: {}", e.with_sql(&statement.sql).display_with_sql())?;
+            }
+            if self.timing {
+                writeln!(out, "Time: {:.3} ms", start.elapsed().as_secs_f64() * 1000.0)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, sql: &str, out: &mut dyn Write) -> Result<(), RsdbcErrors> {
+        if returns_rows(sql, self.connection.dialect()) {
+            let table = Table::read(self.connection.create_statement(sql)?.execute_query(&[])?.as_mut())?;
+            return self.write_table(&table, out);
+        }
+        let rows = self.connection.create_statement(sql)?.execute_update(&[])?;
+        writeln!(out, "{} {} affected", rows, if rows == 1 { "row" } else { "rows" }).map_err(io_error)
+    }
+
+    fn write_table(&self, table: &Table, out: &mut dyn Write) -> Result<(), RsdbcErrors> {
+        let written = match self.expanded {
+            true => table.write_expanded(out),
+            false => table.write_aligned(out),
+        };
+        written.map_err(io_error)
+    }
+
+    /// Runs a meta-command, returning whether the session should end
+    fn meta_command(&mut self, command: &str, out: &mut dyn Write) -> Result<bool, CliError> {
+        let mut parts = command.split_whitespace();
+        let name = parts.next().unwrap_or_default();
+        let argument = parts.next();
+        let result = match name {
+            "\\q" => return Ok(true),
+            "\\?" => write!(out, "{}", HELP).map_err(io_error),
+            "\\dt" => self.list_tables(out),
+            "\\d" => match argument {
+                Some(table) => self.describe_table(table, out),
+                None => self.list_tables(out),
+            },
+            "\\timing" => toggle(&mut self.timing, argument)
+                .map(|on| writeln!(out, "Timing is {}.", on_off(on)))
+                .and_then(|written| written.map_err(io_error)),
+            "\\x" => toggle(&mut self.expanded, argument)
+                .map(|on| writeln!(out, "Expanded display is {}.", on_off(on)))
+                .and_then(|written| written.map_err(io_error)),
+            "\\s" => self.history.iter().try_for_each(|entry| writeln!(out, "{}", entry)).map_err(io_error),
+            _ => Err(RsdbcErrors::IllegalArgument(format!("invalid command {}, try \\? for help", name))),
+        };
+        if let Err(e) = result {
+            writeln!(out, "ERROR: {}", e)?;
+        }
+        Ok(false)
+    }
+
+    fn list_tables(&mut self, out: &mut dyn Write) -> Result<(), RsdbcErrors> {
+        let sql = match self.connection.dialect() {
+            Dialect::Sqlite => "SELECT name, type FROM sqlite_master \
+                WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            Dialect::Postgres => "SELECT table_schema AS schema, table_name AS name, table_type AS type \
+                FROM information_schema.tables \
+                WHERE table_schema NOT IN ('pg_catalog', 'information_schema') ORDER BY 1, 2",
+            Dialect::MySql => "SELECT table_name AS name, table_type AS type FROM information_schema.tables \
+                WHERE table_schema = DATABASE() ORDER BY 1",
+            _ => return Err(RsdbcErrors::Unsupported("listing tables is not supported for this database".to_string())),
+        };
+        let table = Table::read(self.connection.create_statement(sql)?.execute_query(&[])?.as_mut())?;
+        self.write_table(&table, out)
+    }
+
+    fn describe_table(&mut self, name: &str, out: &mut dyn Write) -> Result<(), RsdbcErrors> {
+        let sql = match self.connection.dialect() {
+            Dialect::Sqlite => "SELECT name AS column_name, type AS data_type, \
+                CASE \"notnull\" WHEN 0 THEN 'YES' ELSE 'NO' END AS is_nullable, dflt_value AS column_default \
+                FROM pragma_table_info(:table) ORDER BY cid",
+            Dialect::Postgres => "SELECT column_name, data_type, is_nullable, column_default \
+                FROM information_schema.columns \
+                WHERE table_name = :table AND table_schema NOT IN ('pg_catalog', 'information_schema') \
+                ORDER BY ordinal_position",
+            Dialect::MySql => "SELECT column_name AS column_name, column_type AS data_type, \
+                is_nullable AS is_nullable, column_default AS column_default \
+                FROM information_schema.columns \
+                WHERE table_name = :table AND table_schema = DATABASE() ORDER BY ordinal_position",
+            _ => return Err(RsdbcErrors::Unsupported("describing tables is not supported for this database".to_string())),
+        };
+        let params = [("table", Value::String(name.to_string()))];
+        let table = Table::read(self.connection.create_named_statement(sql)?.execute_query(&params)?.as_mut())?;
+        if table.rows.is_empty() {
+            return Err(RsdbcErrors::NoSuchElement(format!("table {}", name)));
+        }
+        self.write_table(&table, out)
+    }
+
+    fn load_history(&mut self) -> io::Result<()> {
+        let path = match &self.history_file {
+            Some(path) if path.exists() => path,
+            _ => return Ok(()),
+        };
+        self.history = fs::read_to_string(path)?.lines().map(unescape).collect();
+        Ok(())
+    }
+
+    /// Adds an entry to the history, appending it to the history file if there is one
+    fn remember(&mut self, entry: &str, lines: &mut dyn Lines) -> io::Result<()> {
+        lines.add_history(entry);
+        self.history.push(entry.to_string());
+        if let Some(path) = &self.history_file {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", escape(entry))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sets `setting` from an `on` or `off` argument, or flips it without one
+fn toggle(setting: &mut bool, argument: Option<&str>) -> Result<bool, RsdbcErrors> {
+    *setting = match argument {
+        None => !*setting,
+        Some("on") => true,
+        Some("off") => false,
+        Some(argument) => return Err(RsdbcErrors::IllegalArgument(format!("expected on or off but was {}", argument))),
+    };
+    Ok(*setting)
+}
+
+fn on_off(on: bool) -> &'static str {
+    match on {
+        true => "on",
+        false => "off",
+    }
+}
+
+fn io_error(e: io::Error) -> RsdbcErrors {
+    RsdbcErrors::General(e.to_string())
+}
+
+/// History entries may span several lines so each is written to a single line of the file
+fn escape(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(line: &str) -> String {
+    let mut entry = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            entry.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => entry.push('\n'),
+            Some(c) => entry.push(c),
+            None => entry.push('\\'),
+        }
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use futures::executor::block_on;
+    use rsdbc_core::connection::ConnectionFactory;
+    use rsdbc_sqlite::options::SqliteConnectOptions;
+    use crate::repl::{escape, unescape, Repl};
+
+    fn run(input: &str) -> String {
+        let connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+        let mut out = Vec::new();
+        Repl::new(connection).run(&mut input.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn statements_should_span_lines_until_their_delimiter() {
+        let out = run("CREATE TABLE person (id INTEGER, name TEXT);\nINSERT INTO person\n  VALUES (1, 'a;b');\nSELECT id, name\nFROM person;");
+        assert_eq!("0 rows affected\n1 row affected\n id | name\n----+------\n 1  | a;b\n(1 row)\n", out);
+    }
+
+    #[test]
+    fn returning_in_literals_should_not_make_a_query() {
+        let out = run("CREATE TABLE t (name TEXT, b BLOB);\nINSERT INTO t (name, b) VALUES ('a', x'0102'), ('returning', NULL);");
+        assert_eq!("0 rows affected\n2 rows affected\n", out);
+    }
+
+    #[test]
+    fn failed_statements_should_not_end_the_session() {
+        let out = run("SELECT * FROM missing;\nSELECT 1 AS one;\n");
+        assert!(out.starts_with("ERROR: "), "{}", out);
+        assert!(out.ends_with(" one\n-----\n 1\n(1 row)\n"), "{}", out);
+    }
+
+    #[test]
+    fn meta_commands_should_describe_tables() {
+        let out = run("CREATE TABLE person (id INTEGER NOT NULL, name VARCHAR(20));\n\\dt\n\\x\n\\d person\n\\d missing\n\\q\nSELECT 1;");
+        let expected = "0 rows affected\n \
+            name   | type\n\
+            --------+-------\n \
+            person | table\n\
+            (1 row)\n\
+            Expanded display is on.\n\
+            -[ RECORD 1 ]\n\
+            column_name    | id\n\
+            data_type      | INTEGER\n\
+            is_nullable    | NO\n\
+            column_default | NULL\n\
+            -[ RECORD 2 ]\n\
+            column_name    | name\n\
+            data_type      | VARCHAR(20)\n\
+            is_nullable    | YES\n\
+            column_default | NULL\n\
+            ERROR: No such element: `table missing`\n";
+        assert_eq!(expected, out);
+    }
+
+    #[test]
+    fn history_should_be_kept_in_a_file() {
+        let path = std::env::temp_dir().join(format!("rsdbc-cli-history-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+        let mut out = Vec::new();
+        Repl::new(connection)
+            .with_history_file(&path)
+            .run(&mut "SELECT 1\n, 2;\n\\timing on\n".as_bytes(), &mut out)
+            .unwrap();
+
+        let connection = block_on(SqliteConnectOptions::new().connect()).unwrap();
+        let mut out = Vec::new();
+        Repl::new(connection).with_history_file(&path).run(&mut "\\s\n".as_bytes(), &mut out).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!("SELECT 1\n, 2;\n\\timing on\n\\s\n", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn history_entries_should_be_escaped() {
+        assert_eq!("a\\\\b\\nc", escape("a\\b\nc"));
+        assert_eq!("a\\b\nc", unescape(&escape("a\\b\nc")));
+    }
+}
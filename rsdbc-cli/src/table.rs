@@ -0,0 +1,143 @@
+use std::io::{self, Write};
+use rsdbc_core::{Result, ResultSet, RsdbcType, Value};
+use crate::encoding::hex;
+
+/// Rows of a result read as text for display, with the column names taken from the result's
+/// metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+impl Table {
+    pub fn read(rs: &mut dyn ResultSet) -> Result<Self> {
+        let metadata = rs.meta_data()?;
+        let columns: Vec<String> = (0..metadata.num_columns()).map(|i| metadata.column_name(i)).collect();
+        let types: Vec<RsdbcType> = (0..metadata.num_columns()).map(|i| metadata.column_type(i)).collect();
+
+        let mut rows = Vec::new();
+        while rs.next() {
+            let row = types
+                .iter()
+                .enumerate()
+                .map(|(i, r#type)| read_text(rs, i as u64, *r#type))
+                .collect::<Result<Vec<_>>>()?;
+            rows.push(row);
+        }
+        Ok(Self { columns, rows })
+    }
+
+    /// Writes the rows as aligned columns under a header followed by the number of rows
+    pub fn write_aligned(&self, out: &mut dyn Write) -> io::Result<()> {
+        let mut widths: Vec<usize> = self.columns.iter().map(|c| width(c)).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(width(display(cell)));
+            }
+        }
+
+        let header: Vec<String> = self.columns.iter().enumerate().map(|(i, c)| pad(c, widths[i])).collect();
+        writeln!(out, " {}", header.join(" | ").trim_end())?;
+        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+        writeln!(out, "{}", rule.join("+"))?;
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().enumerate().map(|(i, c)| pad(display(c), widths[i])).collect();
+            writeln!(out, " {}", cells.join(" | ").trim_end())?;
+        }
+        writeln!(out, "{}", row_count(self.rows.len()))
+    }
+
+    /// Writes each row as a record of `column | value` lines, for rows too wide to align
+    pub fn write_expanded(&self, out: &mut dyn Write) -> io::Result<()> {
+        let width = self.columns.iter().map(|c| width(c)).max().unwrap_or_default();
+        for (number, row) in self.rows.iter().enumerate() {
+            writeln!(out, "-[ RECORD {} ]", number + 1)?;
+            for (column, cell) in self.columns.iter().zip(row) {
+                writeln!(out, "{} | {}", pad(column, width), display(cell))?;
+            }
+        }
+        if self.rows.is_empty() {
+            writeln!(out, "{}", row_count(0))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads column `i` as text, [None] for NULL.
+///
+/// Columns whose text can't be read, e.g. postgres dates and binary columns, are read as the
+/// [Value] for their type instead. Bytes are written as hex prefixed by `\x`.
+pub fn read_text(rs: &dyn ResultSet, i: u64, r#type: RsdbcType) -> Result<Option<String>> {
+    if let Ok(text) = rs.get_string(i) {
+        return Ok(text);
+    }
+    match rs.get_value(i, r#type) {
+        Ok(value) => Ok(value.map(|value| value_text(&value))),
+        // sqlite reports expressions without a declared type as BLOB whatever their value
+        Err(_) => Ok(rs.get_bytes(i)?.map(|bytes| hex(&bytes))),
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Bool(v) => v.to_string(),
+        Value::Int32(v) => v.to_string(),
+        Value::UInt32(v) => v.to_string(),
+        Value::Int64(v) => v.to_string(),
+        Value::Double(v) => v.to_string(),
+        Value::String(v) => v.clone(),
+        Value::Bytes(v) => hex(v),
+        v => format!("{:?}", v),
+    }
+}
+
+fn display(cell: &Option<String>) -> &str {
+    cell.as_deref().unwrap_or("NULL")
+}
+
+fn width(s: &str) -> usize {
+    s.chars().count()
+}
+
+fn pad(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(self::width(s))))
+}
+
+fn row_count(rows: usize) -> String {
+    match rows {
+        1 => "(1 row)".to_string(),
+        n => format!("({} rows)", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::table::Table;
+
+    fn table() -> Table {
+        Table {
+            columns: vec!["id".to_string(), "name".to_string()],
+            rows: vec![
+                vec![Some("1".to_string()), Some("Ada".to_string())],
+                vec![Some("10".to_string()), None],
+            ],
+        }
+    }
+
+    #[test]
+    fn rows_should_be_aligned_under_the_header() {
+        let mut out = Vec::new();
+        table().write_aligned(&mut out).unwrap();
+        let expected = " id | name\n----+------\n 1  | Ada\n 10 | NULL\n(2 rows)\n";
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn expanded_rows_should_be_written_as_records() {
+        let mut out = Vec::new();
+        table().write_expanded(&mut out).unwrap();
+        let expected = "-[ RECORD 1 ]\nid   | 1\nname | Ada\n-[ RECORD 2 ]\nid   | 10\nname | NULL\n";
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+    }
+}
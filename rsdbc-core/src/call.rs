@@ -0,0 +1,226 @@
+//! Stored procedure calls with OUT and INOUT parameters.
+//!
+//! Parameters are registered with [Statement::bind_out] and [Statement::bind_inout] by the
+//! 0-based index of their placeholder. The values passed to [Statement::execute_call] are bound to
+//! the remaining IN and the INOUT placeholders, in order, and the values of the OUT and INOUT
+//! parameters are returned as a [Segment::OutParameters] alongside any rows.
+//!
+//! ```ignore
+//! // CREATE PROCEDURE add_tax(IN amount DECIMAL(10, 2), INOUT rate DECIMAL(4, 2), OUT total DECIMAL(10, 2))
+//! let mut stmt = connection.create_statement("CALL add_tax(?, ?, ?)")?;
+//! stmt.bind_inout(1, RsdbcType::Decimal)?;
+//! stmt.bind_out(2, RsdbcType::Decimal)?;
+//! for segment in stmt.execute_call(&[Value::Int32(100), Value::Double(0.2)])? {
+//!     if let Segment::OutParameters(out) = segment {
+//!         let total = out.get_value_by_name("TOTAL")?;
+//!     }
+//! }
+//! ```
+//!
+//! [Statement::bind_out]: crate::connection::Statement::bind_out
+//! [Statement::bind_inout]: crate::connection::Statement::bind_inout
+//! [Statement::execute_call]: crate::connection::Statement::execute_call
+
+use std::collections::BTreeMap;
+use crate::error::RsdbcErrors;
+use crate::{find_by_name, Column, OutParameterMetadata, OutParameters, OutParametersMetadata, Result, ResultSet, RsdbcType, Value};
+
+/// A part of the result of executing a statement
+pub enum Segment<'a> {
+    /// Rows returned by the statement
+    Rows(Box<dyn ResultSet + 'a>),
+    /// The number of rows updated by the statement
+    UpdateCount(u64),
+    /// The values of the OUT and INOUT parameters of a stored procedure call
+    OutParameters(Box<dyn OutParameters>),
+}
+
+/// The direction of a parameter
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParameterMode {
+    In,
+    Out,
+    InOut,
+}
+
+impl ParameterMode {
+    /// Whether the value of the parameter is bound
+    pub fn is_in(&self) -> bool {
+        matches!(self, ParameterMode::In | ParameterMode::InOut)
+    }
+
+    /// Whether the value of the parameter is returned
+    pub fn is_out(&self) -> bool {
+        matches!(self, ParameterMode::Out | ParameterMode::InOut)
+    }
+}
+
+/// The OUT and INOUT parameters registered on a statement, by placeholder index.
+///
+/// Placeholders which aren't registered are IN parameters.
+#[derive(Debug, Clone, Default)]
+pub struct CallParameters {
+    registered: BTreeMap<u32, (ParameterMode, RsdbcType)>,
+}
+
+impl CallParameters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the placeholder at `index`, replacing any previous registration
+    pub fn register(&mut self, index: u32, mode: ParameterMode, r#type: RsdbcType) {
+        match mode {
+            ParameterMode::In => self.registered.remove(&index),
+            _ => self.registered.insert(index, (mode, r#type)),
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.registered.is_empty()
+    }
+
+    pub fn mode(&self, index: u32) -> ParameterMode {
+        self.registered.get(&index).map_or(ParameterMode::In, |(mode, _)| *mode)
+    }
+
+    /// The registered OUT and INOUT parameters ordered by index
+    pub fn outputs(&self) -> impl Iterator<Item = (u32, ParameterMode, RsdbcType)> + '_ {
+        self.registered.iter().map(|(index, (mode, r#type))| (*index, *mode, *r#type))
+    }
+
+    /// Match `values` to the IN and INOUT parameters of a statement with `placeholders`
+    /// placeholders. OUT parameters have no value.
+    ///
+    /// returns [RsdbcErrors::IllegalArgument] if a parameter is registered beyond the last
+    /// placeholder or if the number of values doesn't match the number of IN and INOUT parameters
+    pub fn arrange<'v>(&self, placeholders: u32, values: &'v [Value]) -> Result<Vec<Option<&'v Value>>> {
+        if let Some(index) = self.registered.keys().find(|i| **i >= placeholders) {
+            return Err(RsdbcErrors::IllegalArgument(
+                format!("parameter {} is registered but the statement has {} parameters", index, placeholders)
+            ));
+        }
+
+        let expected = (0..placeholders).filter(|i| self.mode(*i).is_in()).count();
+        if values.len() != expected {
+            return Err(RsdbcErrors::IllegalArgument(
+                format!("expected {} IN and INOUT parameter values but got {}", expected, values.len())
+            ));
+        }
+
+        let mut values = values.iter();
+        Ok((0..placeholders)
+            .map(|i| match self.mode(i).is_in() {
+                true => values.next(),
+                false => None,
+            })
+            .collect())
+    }
+}
+
+/// The values of OUT and INOUT parameters along with their metadata
+#[derive(Debug, Clone)]
+pub struct OutParameterValues {
+    parameters: Vec<Column>,
+    values: Vec<Option<Value>>,
+}
+
+impl OutParameterValues {
+    /// `parameters` and `values` are in the order the parameters are declared
+    pub fn new(parameters: Vec<Column>, values: Vec<Option<Value>>) -> Self {
+        Self { parameters, values }
+    }
+}
+
+impl OutParameters for OutParameterValues {
+    fn get_metadata(&self) -> Box<dyn OutParametersMetadata> {
+        Box::new(self.parameters.clone())
+    }
+
+    fn get_value(&self, index: u32) -> Result<Option<&Value>> {
+        self.values
+            .get(index as usize)
+            .map(Option::as_ref)
+            .ok_or(RsdbcErrors::IndexOutOfBounds { index: u64::from(index), length: self.values.len() as u64 })
+    }
+
+    fn get_value_by_name(&self, name: &str) -> Result<Option<&Value>> {
+        if name.is_empty() {
+            return Err(RsdbcErrors::IllegalArgument("parameter name must not be empty".to_string()));
+        }
+
+        let index = find_by_name(self.parameters.iter().map(|p| p.name.as_str()), name)
+            .ok_or_else(|| RsdbcErrors::NoSuchElement(format!("out parameter {}", name)))?;
+        self.get_value(index as u32)
+    }
+}
+
+impl OutParameterMetadata for Column {}
+
+impl OutParametersMetadata for Vec<Column> {
+    fn get_parameter_metadata_by_index(&self, index: u32) -> Result<Box<dyn OutParameterMetadata>> {
+        self.get(index as usize)
+            .map(|c| Box::new(c.clone()) as Box<dyn OutParameterMetadata>)
+            .ok_or(RsdbcErrors::IndexOutOfBounds { index: u64::from(index), length: self.len() as u64 })
+    }
+
+    fn get_parameter_metadata_by_name(&self, name: &str) -> Result<Box<dyn OutParameterMetadata>> {
+        if name.is_empty() {
+            return Err(RsdbcErrors::IllegalArgument("parameter name must not be empty".to_string()));
+        }
+
+        find_by_name(self.iter().map(|c| c.name.as_str()), name)
+            .map(|i| Box::new(self[i].clone()) as Box<dyn OutParameterMetadata>)
+            .ok_or_else(|| RsdbcErrors::NoSuchElement(format!("out parameter {}", name)))
+    }
+
+    fn get_parameter_metadatas(&self) -> Vec<Box<dyn OutParameterMetadata>> {
+        self.iter().map(|c| Box::new(c.clone()) as Box<dyn OutParameterMetadata>).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::call::{CallParameters, OutParameterValues, ParameterMode};
+    use crate::error::RsdbcErrors;
+    use crate::{Column, OutParameters, RsdbcType, Value};
+
+    #[test]
+    fn values_should_be_matched_to_in_parameters() {
+        let mut parameters = CallParameters::new();
+        parameters.register(1, ParameterMode::InOut, RsdbcType::Integer);
+        parameters.register(2, ParameterMode::Out, RsdbcType::Varchar);
+        assert_eq!(ParameterMode::In, parameters.mode(0));
+
+        let values = [Value::Int32(1), Value::Int32(2)];
+        let arranged = parameters.arrange(3, &values).unwrap();
+        assert!(matches!(arranged[..], [Some(Value::Int32(1)), Some(Value::Int32(2)), None]));
+
+        assert!(matches!(parameters.arrange(3, &values[..1]), Err(RsdbcErrors::IllegalArgument(_))));
+        assert!(matches!(parameters.arrange(2, &values), Err(RsdbcErrors::IllegalArgument(_))));
+
+        let outputs: Vec<_> = parameters.outputs().map(|(i, mode, _)| (i, mode)).collect();
+        assert_eq!(vec![(1, ParameterMode::InOut), (2, ParameterMode::Out)], outputs);
+
+        parameters.register(1, ParameterMode::In, RsdbcType::Integer);
+        assert_eq!(1, parameters.outputs().count());
+    }
+
+    #[test]
+    fn out_parameters_should_be_read_by_index_or_name() {
+        let out = OutParameterValues::new(
+            vec![Column::new("rate", RsdbcType::Double), Column::new("Total", RsdbcType::Varchar)],
+            vec![None, Some(Value::String("120".to_string()))],
+        );
+
+        assert!(out.get_value(0).unwrap().is_none());
+        assert!(matches!(out.get_value_by_name("TOTAL").unwrap(), Some(Value::String(s)) if s == "120"));
+        assert!(matches!(out.get_value(2), Err(RsdbcErrors::IndexOutOfBounds { index: 2, length: 2 })));
+        assert!(matches!(out.get_value_by_name("missing"), Err(RsdbcErrors::NoSuchElement(_))));
+        assert!(matches!(out.get_value_by_name(""), Err(RsdbcErrors::IllegalArgument(_))));
+
+        let metadata = out.get_metadata();
+        assert_eq!("Total", metadata.get_parameter_metadata_by_name("total").unwrap().get_name());
+        assert_eq!(2, metadata.get_parameter_metadatas().len());
+    }
+}
@@ -1,9 +1,15 @@
 use std::collections::HashMap;
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
 use std::str::FromStr;
 use std::time::Duration;
 use futures::future::BoxFuture;
+use futures::FutureExt;
 use url::Url;
-use crate::{OptionValue, RsdbcErrors, Result};
+use crate::{OptionValue, ResultSet, RsdbcErrors, RsdbcType, Result, SQLWarning, Segment, TransactionDefinition, Value};
+use crate::named::{NamedSql, NamedStatement};
+use crate::retry::RetryPolicy;
+use crate::sql::Dialect;
 
 pub trait ConnectionFactory: 'static + Send + Sync {
     // TODO: should have associated type for Error so that we have multiple error types?
@@ -267,11 +273,14 @@ pub trait Connection: Send {
     /// Begins a new transaction.
     fn begin_transaction(&mut self) -> Result<()>;
 
-    // TODO: how to handle object safety for this?
-    // /// Begins a new transaction.
-    // /// Beginning the transaction may fail if the [TransactionDefinition] conflicts with the
-    // /// connection configuration.
-    // fn begin_transaction_with_definition(&mut self, definition: Box<dyn TransactionDefinition>);
+    /// Begins a new transaction.
+    /// Beginning the transaction may fail if the [TransactionDefinition] conflicts with the
+    /// connection configuration.
+    ///
+    /// The default implementation returns an unsupported error.
+    fn begin_transaction_with(&mut self, _definition: &dyn TransactionDefinition) -> Result<()> {
+        Err(RsdbcErrors::Unsupported("transaction definitions are not supported by this driver".to_string()))
+    }
 
 
     // Explicitly close this database connection.
@@ -284,7 +293,7 @@ pub trait Connection: Send {
     fn close(&mut self) -> Result<()>;
 
     /// Commits the current transaction.
-    fn commit_transaction(&mut self);
+    fn commit_transaction(&mut self) -> Result<()>;
 
     /// Creates a new [Batch] instance for building a batched request.
     fn create_batch(&mut self) -> Result<Box<dyn Batch>>;
@@ -294,8 +303,8 @@ pub trait Connection: Send {
     ///
     /// * `name`: name the name of the savepoint to create.
     ///
-    /// UnsupportedOperationException if savepoints are not supported
-    fn create_savepoint(&mut self, name: &str);
+    /// returns [RsdbcErrors::Unsupported] if savepoints are not supported
+    fn create_savepoint(&mut self, name: &str) -> Result<()>;
 
     /// Creates a new statement for building a statement-based request.
     /// Arguments:
@@ -307,6 +316,31 @@ pub trait Connection: Send {
     // to declare that the trait object captures data from argument `self`, you can add an explicit `'_` lifetime bound
     fn create_statement(&mut self, sql: &str) -> Result<Box<dyn Statement<'_> + '_>>;
 
+    /// Executes `sql`, a single statement without parameters, as is. Unlike a [Statement] it isn't
+    /// prepared, which some statements such as a mysql `CREATE PROCEDURE` require.
+    ///
+    /// The default implementation executes a [Statement] created by [Connection::create_statement].
+    fn execute_unprepared(&mut self, sql: &str) -> Result<()> {
+        self.create_statement(sql)?.execute_update(&[]).map(|_| ())
+    }
+
+    /// Executes a postgres `COPY ... FROM STDIN` statement, streaming `data` in the format the
+    /// statement names, and returns the number of rows copied.
+    ///
+    /// The default implementation returns [RsdbcErrors::Unsupported].
+    fn copy_in(&mut self, sql: &str, data: &mut dyn Read) -> Result<u64> {
+        let _ = (sql, data);
+        Err(RsdbcErrors::Unsupported("COPY is not supported by this driver".to_string()))
+    }
+
+    /// Returns the SQL [Dialect] spoken by the database, which determines how named parameters
+    /// are rewritten by [Connection::create_named_statement].
+    ///
+    /// The default implementation returns [Dialect::Generic].
+    fn dialect(&self) -> Dialect {
+        Dialect::Generic
+    }
+
     /// Returns the auto-commit mode for this connection.
     ///
     /// @return true if the connection is in auto-commit mode; false otherwise.
@@ -333,10 +367,10 @@ pub trait Connection: Send {
     /// Arguments:
     ///
     /// * `name`: the name of the savepoint to release
-    fn release_savepoint(&mut self, name: &str);
+    fn release_savepoint(&mut self, name: &str) -> Result<()>;
 
     /// Rolls back the current transaction.
-    fn rollback_transaction(&mut self);
+    fn rollback_transaction(&mut self) -> Result<()>;
 
     // TODO: This makes sense if the connection is dealing with underlying transaction
     // not sure it makes sense here if we return the transaction to the client
@@ -345,8 +379,8 @@ pub trait Connection: Send {
     ///
     /// * `name`: the name of the savepoint to rollback to
     ///
-    /// @throws UnsupportedOperationException if savepoints are not supported
-    fn rollback_transaction_to_savepoint(&mut self, name: String);
+    /// returns [RsdbcErrors::Unsupported] if savepoints are not supported
+    fn rollback_transaction_to_savepoint(&mut self, name: String) -> Result<()>;
 
     /// Configures the auto-commit mode for the current transaction.
     /// If a connection is in auto-commit mode, then all [Statement]s will be executed
@@ -383,6 +417,15 @@ pub trait Connection: Send {
     /// * `depth`: the validation depth
     fn validate(&mut self, depth: ValidationDepth) -> bool;
 
+    /// Returns the warnings reported on this connection since they were last cleared, in the
+    /// order they were reported.
+    fn warnings(&mut self) -> Vec<SQLWarning> {
+        Vec::new()
+    }
+
+    /// Clears the warnings reported on this connection.
+    fn clear_warnings(&mut self) {}
+
 
 
     // /// Makes all changes made since the previous commit/rollback permanent and releases any database locks currently held by this Connection object.
@@ -460,12 +503,124 @@ pub trait Connection: Send {
     // fn rollback(&mut self);
 }
 
-pub trait Transaction {
+impl<'a> dyn Connection + 'a {
+
+    /// Begins a new transaction returning a [TransactionGuard] which rolls the transaction back
+    /// when dropped unless it has been committed.
+    pub fn begin(&mut self) -> Result<TransactionGuard<'_>> {
+        self.begin_transaction()?;
+        Ok(self.transaction_guard())
+    }
+
+    /// Begins a new transaction with the given [TransactionDefinition] returning a
+    /// [TransactionGuard] which rolls the transaction back when dropped unless it has been committed.
+    pub fn begin_with(&mut self, definition: &dyn TransactionDefinition) -> Result<TransactionGuard<'_>> {
+        self.begin_transaction_with(definition)?;
+        Ok(self.transaction_guard())
+    }
+
+    /// Creates a new statement whose `:name`, `$name` or `@name` parameters are bound by name.
+    /// The parameters are rewritten to the native placeholders of the connection's [Dialect],
+    /// see the [named](crate::named) module.
+    pub fn create_named_statement(&mut self, sql: &str) -> Result<NamedStatement<'_>> {
+        let sql = NamedSql::parse(sql, self.dialect())?;
+        let statement = self.create_statement(sql.sql())?;
+        Ok(NamedStatement::new(statement, sql))
+    }
 
-    // exec
-    // prepare
-    // query
-    // statement
+    /// Executes the statements of `script` in order, split according to the connection's
+    /// [Dialect], see the [script](crate::script) module. Returns the number of statements executed.
+    ///
+    /// Execution stops at the first statement to fail which is returned as a
+    /// [RsdbcErrors::Script] along with the line it starts on. Statements executed before it
+    /// aren't undone unless the script is run within a transaction.
+    pub fn execute_script(&mut self, script: &str) -> Result<usize> {
+        let statements = crate::script::split(script, self.dialect());
+        for statement in &statements {
+            self.execute_unprepared(&statement.sql).map_err(|e| RsdbcErrors::Script {
+                line: statement.line,
+                sql: statement.sql.clone(),
+                source: Box::new(e.with_sql(&statement.sql)),
+            })?;
+        }
+        Ok(statements.len())
+    }
+
+    fn transaction_guard(&mut self) -> TransactionGuard<'_> {
+        TransactionGuard {
+            connection: self,
+            savepoint: None,
+            completed: false,
+        }
+    }
+
+    /// Runs `f` within a transaction. The transaction is committed if `f` returns [Ok] and
+    /// rolled back if it returns [Err] or panics.
+    ///
+    /// The connection is handed to `f` which returns a boxed future, e.g.
+    /// `connection.in_transaction(None, |tx| Box::pin(async move { ... })).await`
+    ///
+    /// Arguments:
+    ///
+    /// * `definition`: the [TransactionDefinition] used to begin the transaction. When [None]
+    ///   the transaction is started with the connection defaults.
+    pub async fn in_transaction<T, F>(&mut self, definition: Option<&dyn TransactionDefinition>, f: F) -> Result<T>
+    where
+        F: for<'c> FnMut(&'c mut dyn Connection) -> BoxFuture<'c, Result<T>>,
+    {
+        self.in_transaction_with_retry(definition, &RetryPolicy::none(), f).await
+    }
+
+    /// Same as [Connection::in_transaction] but retries the whole transaction, including `f`,
+    /// when it fails with a retryable error such as a serialization failure or deadlock.
+    pub async fn in_transaction_with_retry<T, F>(
+        &mut self,
+        definition: Option<&dyn TransactionDefinition>,
+        retry: &RetryPolicy,
+        mut f: F
+    ) -> Result<T>
+    where
+        F: for<'c> FnMut(&'c mut dyn Connection) -> BoxFuture<'c, Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match definition {
+                Some(definition) => self.begin_transaction_with(definition)?,
+                None => self.begin_transaction()?,
+            }
+
+            let err = match AssertUnwindSafe(f(&mut *self)).catch_unwind().await {
+                Ok(Ok(value)) => match self.commit_transaction() {
+                    Ok(()) => return Ok(value),
+                    Err(e) => {
+                        // the transaction may already be over depending on the database
+                        let _ = self.rollback_transaction();
+                        e
+                    }
+                },
+                Ok(Err(e)) => {
+                    // surface the original error rather than one from rolling back
+                    let _ = self.rollback_transaction();
+                    e
+                }
+                Err(panic) => {
+                    let _ = self.rollback_transaction();
+                    panic::resume_unwind(panic);
+                }
+            };
+
+            if !retry.should_retry(attempt, &err) {
+                return Err(err);
+            }
+
+            retry.wait(attempt).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// A transaction on a [Connection]. Statements created from the transaction run within it.
+pub trait Transaction {
 
     /// Commits the current transaction.
     fn commit_transaction(&mut self) -> Result<()>;
@@ -473,34 +628,133 @@ pub trait Transaction {
     /// Creates a new [Batch] instance for building a batched request.
     fn create_batch(&mut self) -> Result<Box<dyn Batch>>;
 
-    /// Creates a savepoint in the current transaction.
+    /// Creates a savepoint in the current transaction and returns it as a nested transaction.
+    /// Committing the nested transaction releases the savepoint while rolling it back, or
+    /// dropping it without committing, rolls back to the savepoint.
     /// Arguments:
     ///
     /// * `name`: name the name of the savepoint to create.
     ///
-    /// UnsupportedOperationException if savepoints are not supported
-    fn create_savepoint(&mut self, name: &str) -> Result<Box<Self>>;
+    /// returns [RsdbcErrors::Unsupported] if savepoints are not supported
+    fn create_savepoint(&mut self, name: &str) -> Result<Box<dyn Transaction + '_>>;
 
+    /// Creates a new statement, executed within this transaction, for building a
+    /// statement-based request.
+    /// Arguments:
+    ///
+    /// * `sql`: the SQL of the statement
+    fn create_statement(&mut self, sql: &str) -> Result<Box<dyn Statement<'_> + '_>>;
 
     /// Releases a savepoint in the current transaction.
     /// Calling this for drivers not supporting savepoint release results in a no-op.
     /// Arguments:
     ///
     /// * `name`: the name of the savepoint to release
-    fn release_savepoint(&mut self, name: &str);
+    fn release_savepoint(&mut self, name: &str) -> Result<()>;
 
     /// Rolls back the current transaction.
     fn rollback_transaction(&mut self) -> Result<()>;
+}
+
+/// A [Transaction] guard that borrows its [Connection] mutably for as long as the transaction
+/// is open so the connection can't be used outside of it.
+///
+/// The transaction is rolled back when the guard is dropped without being committed.
+/// Errors from that rollback are ignored, call [Transaction::rollback_transaction] to observe them.
+pub struct TransactionGuard<'c> {
+    connection: &'c mut dyn Connection,
+    // savepoint this guard represents, None for the outermost transaction
+    savepoint: Option<String>,
+    completed: bool,
+}
 
+impl<'c> TransactionGuard<'c> {
 
+    fn check_open(&self) -> Result<()> {
+        if self.completed {
+            return Err(RsdbcErrors::General("transaction has already been completed".to_string()));
+        }
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        match self.savepoint.clone() {
+            Some(name) => {
+                self.connection.rollback_transaction_to_savepoint(name.clone())?;
+                self.connection.release_savepoint(&name)
+            },
+            None => self.connection.rollback_transaction(),
+        }
+    }
+}
+
+impl<'c> Transaction for TransactionGuard<'c> {
+
+    fn commit_transaction(&mut self) -> Result<()> {
+        self.check_open()?;
+        match self.savepoint.clone() {
+            Some(name) => self.connection.release_savepoint(&name)?,
+            None => {
+                // the transaction is over once commit has been attempted, even if it failed
+                self.completed = true;
+                return self.connection.commit_transaction();
+            },
+        }
+        self.completed = true;
+        Ok(())
+    }
+
+    fn create_batch(&mut self) -> Result<Box<dyn Batch>> {
+        self.check_open()?;
+        self.connection.create_batch()
+    }
+
+    fn create_savepoint(&mut self, name: &str) -> Result<Box<dyn Transaction + '_>> {
+        self.check_open()?;
+        self.connection.create_savepoint(name)?;
+        Ok(Box::new(TransactionGuard {
+            connection: &mut *self.connection,
+            savepoint: Some(name.to_string()),
+            completed: false,
+        }))
+    }
+
+    fn create_statement(&mut self, sql: &str) -> Result<Box<dyn Statement<'_> + '_>> {
+        self.check_open()?;
+        self.connection.create_statement(sql)
+    }
+
+    fn release_savepoint(&mut self, name: &str) -> Result<()> {
+        self.check_open()?;
+        self.connection.release_savepoint(name)
+    }
+
+    fn rollback_transaction(&mut self) -> Result<()> {
+        self.check_open()?;
+        self.completed = true;
+        self.rollback()
+    }
 }
 
+impl<'c> Drop for TransactionGuard<'c> {
+    fn drop(&mut self) {
+        if !self.completed {
+            let _ = self.rollback();
+        }
+    }
+}
 
 fn validate(url: &str) -> Result<()> {
 
     // empty string
     // scheme
     // has driver
+    if !url.contains("://") {
+        return Err(RsdbcErrors::IllegalArgument(format!(
+            "URL `{}` does not match driver[:protocol]://[user:password@]host[:port][/path][?option=value]",
+            url
+        )));
+    }
 
     // path aka database? is this required for all drivers?
 
@@ -649,13 +903,43 @@ pub trait Statement<'conn> {
         self
     }
 
+    // TODO: these are object safe unlike bind_index/execute above. revisit once binding is sorted out
+    /// Execute a query that is expected to return a result set, such as a `SELECT` statement
+    fn execute_query(&mut self, params: &[Value]) -> Result<Box<dyn ResultSet + '_>>;
 
+    /// Execute a query that is expected to update some rows.
+    fn execute_update(&mut self, params: &[Value]) -> Result<u64>;
 
-    // /// Execute a query that is expected to return a result set, such as a `SELECT` statement
-    // fn execute_query(&mut self, params: &[Value]) -> Result<Box<dyn ResultSet + '_>>;
-    //
-    // /// Execute a query that is expected to update some rows.
-    // fn execute_update(&mut self, params: &[Value]) -> Result<u64>;
+    /// Registers the placeholder at `index`, starting at 0, as an OUT parameter of a stored
+    /// procedure call whose value of `type` is returned by [Statement::execute_call].
+    ///
+    /// The default implementation returns [RsdbcErrors::Unsupported].
+    fn bind_out(&mut self, index: u32, r#type: RsdbcType) -> Result<()> {
+        let _ = (index, r#type);
+        Err(RsdbcErrors::Unsupported("OUT parameters are not supported by this driver".to_string()))
+    }
+
+    /// Registers the placeholder at `index`, starting at 0, as an INOUT parameter of a stored
+    /// procedure call whose value is bound and whose value of `type` is returned by
+    /// [Statement::execute_call].
+    ///
+    /// The default implementation returns [RsdbcErrors::Unsupported].
+    fn bind_inout(&mut self, index: u32, r#type: RsdbcType) -> Result<()> {
+        let _ = (index, r#type);
+        Err(RsdbcErrors::Unsupported("INOUT parameters are not supported by this driver".to_string()))
+    }
+
+    /// Execute a stored procedure call, see the [call](crate::call) module.
+    ///
+    /// `params` are bound to the IN and INOUT parameters in order. Returns the rows and update
+    /// counts produced by the call followed by a [Segment::OutParameters] when OUT or INOUT
+    /// parameters are registered.
+    ///
+    /// The default implementation returns [RsdbcErrors::Unsupported].
+    fn execute_call(&mut self, params: &[Value]) -> Result<Vec<Segment<'_>>> {
+        let _ = params;
+        Err(RsdbcErrors::Unsupported("stored procedure calls are not supported by this driver".to_string()))
+    }
 }
 
 
@@ -675,9 +959,203 @@ pub enum SslMode {
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
-    use crate::connection::{ConnectionFactoryOptions, ConnectionFactoryOptionsBuilder};
+    use std::panic::AssertUnwindSafe;
+    use std::time::Duration;
+    use futures::executor::block_on;
+    use futures::FutureExt;
+    use crate::connection::{Batch, Connection, ConnectionFactoryOptions, ConnectionFactoryOptionsBuilder, ConnectionMetadata, IsolationLevel, Statement, Transaction, ValidationDepth};
+    use crate::retry::RetryPolicy;
     use crate::RsdbcErrors;
     use crate::Result;
+    use crate::error::{DatabaseError, DatabaseErrorKind};
+
+    /// Records the transaction calls made against it
+    #[derive(Default)]
+    struct RecordingConnection {
+        calls: Vec<&'static str>,
+        // errors returned by successive commits
+        commit_errors: Vec<RsdbcErrors>,
+    }
+
+    impl Connection for RecordingConnection {
+        fn begin_transaction(&mut self) -> Result<()> {
+            self.calls.push("begin");
+            Ok(())
+        }
+
+        fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn commit_transaction(&mut self) -> Result<()> {
+            self.calls.push("commit");
+            if self.commit_errors.is_empty() {
+                Ok(())
+            } else {
+                Err(self.commit_errors.remove(0))
+            }
+        }
+
+        fn create_batch(&mut self) -> Result<Box<dyn Batch>> { unimplemented!() }
+
+        fn create_savepoint(&mut self, _name: &str) -> Result<()> {
+            self.calls.push("savepoint");
+            Ok(())
+        }
+
+        fn create_statement(&mut self, _sql: &str) -> Result<Box<dyn Statement<'_> + '_>> { unimplemented!() }
+        fn is_auto_commit(&mut self) -> bool { unimplemented!() }
+        fn metadata(&mut self) -> Result<Box<dyn ConnectionMetadata>> { unimplemented!() }
+        fn transaction_isolation_level(&mut self) -> IsolationLevel { unimplemented!() }
+
+        fn release_savepoint(&mut self, _name: &str) -> Result<()> {
+            self.calls.push("release savepoint");
+            Ok(())
+        }
+
+        fn rollback_transaction(&mut self) -> Result<()> {
+            self.calls.push("rollback");
+            Ok(())
+        }
+
+        fn rollback_transaction_to_savepoint(&mut self, _name: String) -> Result<()> {
+            self.calls.push("rollback to savepoint");
+            Ok(())
+        }
+
+        fn auto_commit(&mut self, _commit: bool) { unimplemented!() }
+        fn set_transaction_isolation_level(&mut self, _isolation_level: IsolationLevel) { unimplemented!() }
+        fn validate(&mut self, _depth: ValidationDepth) -> bool { unimplemented!() }
+    }
+
+    #[test]
+    fn transaction_guard_should_rollback_on_drop() {
+        let mut connection = RecordingConnection::default();
+        {
+            let _tx = (&mut connection as &mut dyn Connection).begin().unwrap();
+        }
+        assert_eq!(vec!["begin", "rollback"], connection.calls);
+    }
+
+    #[test]
+    fn transaction_guard_should_not_rollback_after_commit() {
+        let mut connection = RecordingConnection::default();
+        {
+            let mut tx = (&mut connection as &mut dyn Connection).begin().unwrap();
+            tx.commit_transaction().unwrap();
+            assert!(tx.commit_transaction().is_err());
+        }
+        assert_eq!(vec!["begin", "commit"], connection.calls);
+    }
+
+    #[test]
+    fn savepoint_should_rollback_to_savepoint_on_drop() {
+        let mut connection = RecordingConnection::default();
+        {
+            let mut tx = (&mut connection as &mut dyn Connection).begin().unwrap();
+            {
+                let _savepoint = tx.create_savepoint("sp1").unwrap();
+            }
+            tx.commit_transaction().unwrap();
+        }
+        assert_eq!(
+            vec!["begin", "savepoint", "rollback to savepoint", "release savepoint", "commit"],
+            connection.calls
+        );
+    }
+
+    #[test]
+    fn savepoint_commit_should_release_savepoint() {
+        let mut connection = RecordingConnection::default();
+        {
+            let mut tx = (&mut connection as &mut dyn Connection).begin().unwrap();
+            let mut savepoint = tx.create_savepoint("sp1").unwrap();
+            savepoint.commit_transaction().unwrap();
+        }
+        assert_eq!(vec!["begin", "savepoint", "release savepoint", "rollback"], connection.calls);
+    }
+
+    #[test]
+    fn in_transaction_should_commit_on_ok() {
+        let mut connection = RecordingConnection::default();
+        let result = block_on((&mut connection as &mut dyn Connection).in_transaction(None, |_tx| Box::pin(async { Ok(1) })));
+        assert_eq!(1, result.unwrap());
+        assert_eq!(vec!["begin", "commit"], connection.calls);
+    }
+
+    #[test]
+    fn in_transaction_should_rollback_on_err() {
+        let mut connection = RecordingConnection::default();
+        let result: Result<()> = block_on((&mut connection as &mut dyn Connection).in_transaction(None, |_tx| {
+            Box::pin(async { Err(RsdbcErrors::General("boom".to_string())) })
+        }));
+        assert!(matches!(result, Err(RsdbcErrors::General(_))));
+        assert_eq!(vec!["begin", "rollback"], connection.calls);
+    }
+
+    #[test]
+    fn in_transaction_should_rollback_on_panic() {
+        let mut connection = RecordingConnection::default();
+        let result: std::thread::Result<Result<()>> = block_on(AssertUnwindSafe((&mut connection as &mut dyn Connection).in_transaction(None, |_tx| {
+            Box::pin(async { panic!("boom") })
+        })).catch_unwind());
+        assert!(result.is_err());
+        assert_eq!(vec!["begin", "rollback"], connection.calls);
+    }
+
+    #[test]
+    fn in_transaction_should_return_commit_error() {
+        let mut connection = RecordingConnection {
+            commit_errors: vec![RsdbcErrors::General("commit failed".to_string())],
+            ..Default::default()
+        };
+        let result = block_on((&mut connection as &mut dyn Connection).in_transaction(None, |_tx| Box::pin(async { Ok(()) })));
+        assert!(matches!(result, Err(RsdbcErrors::General(_))));
+    }
+
+    #[test]
+    fn in_transaction_with_retry_should_retry_serialization_failures() {
+        let mut connection = RecordingConnection {
+            commit_errors: vec![
+                DatabaseError::from_sql_state("40001", "could not serialize access").into(),
+                DatabaseError::from_sql_state("40P01", "deadlock detected").into(),
+            ],
+            ..Default::default()
+        };
+        let retry = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(2));
+        let mut invocations = 0;
+        let result = block_on((&mut connection as &mut dyn Connection).in_transaction_with_retry(None, &retry, |_tx| {
+            invocations += 1;
+            Box::pin(async { Ok(()) })
+        }));
+        assert!(result.is_ok());
+        assert_eq!(3, invocations);
+    }
+
+    #[test]
+    fn in_transaction_with_retry_should_give_up_after_max_attempts() {
+        let mut connection = RecordingConnection::default();
+        let retry = RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(1));
+        let mut invocations = 0;
+        let result: Result<()> = block_on((&mut connection as &mut dyn Connection).in_transaction_with_retry(None, &retry, |_tx| {
+            invocations += 1;
+            Box::pin(async { Err(DatabaseError::from_sql_state("40001", "could not serialize access").into()) })
+        }));
+        assert_eq!(Some(DatabaseErrorKind::SerializationFailure), result.unwrap_err().kind());
+        assert_eq!(2, invocations);
+    }
+
+    #[test]
+    fn in_transaction_with_retry_should_not_retry_other_errors() {
+        let mut connection = RecordingConnection::default();
+        let mut invocations = 0;
+        let result: Result<()> = block_on((&mut connection as &mut dyn Connection).in_transaction_with_retry(None, &RetryPolicy::default(), |_tx| {
+            invocations += 1;
+            Box::pin(async { Err(RsdbcErrors::General("boom".to_string())) })
+        }));
+        assert!(result.is_err());
+        assert_eq!(1, invocations);
+    }
 
     #[test]
     fn programmatic_connection_factory_builder() {
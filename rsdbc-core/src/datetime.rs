@@ -0,0 +1,318 @@
+//! Text representations of date and time values.
+//!
+//! Dates are written as `YYYY-MM-DD`, times as `HH:MM:SS[.fraction]` and timestamps as a date and
+//! time separated by a space, optionally followed by an offset such as `+01:00`. When parsing, the
+//! seconds are optional, the separator may also be `T` and the offset may also be `Z`, `+01` or
+//! `+0100`. These are the formats SQLite's date and time functions understand and the formats
+//! databases use for the text representation of these values.
+
+use crate::error::RsdbcErrors;
+
+/// Components of a timestamp, the offset is in seconds east of UTC.
+struct Timestamp {
+    year: i32,
+    month: u32,
+    day: u32,
+    time: Time,
+    offset: Option<i32>,
+}
+
+#[derive(Default)]
+struct Time {
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanosecond: u32,
+}
+
+fn invalid(kind: &str, s: &str) -> RsdbcErrors {
+    RsdbcErrors::General(format!("invalid {} `{}`", kind, s))
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str, digits: usize) -> Option<T> {
+    if s.len() != digits || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn parse_date_components(s: &str) -> Option<(i32, u32, u32)> {
+    let mut parts = s.splitn(3, '-');
+    let year = parse_number(parts.next()?, 4)?;
+    let month = parse_number(parts.next()?, 2)?;
+    let day = parse_number(parts.next()?, 2)?;
+    Some((year, month, day))
+}
+
+fn parse_time_components(s: &str) -> Option<Time> {
+    let (s, fraction) = match s.split_once('.') {
+        Some((s, fraction)) => (s, Some(fraction)),
+        None => (s, None),
+    };
+
+    let mut parts = s.splitn(3, ':');
+    let hour = parse_number(parts.next()?, 2)?;
+    let minute = parse_number(parts.next()?, 2)?;
+    let second = match parts.next() {
+        Some(second) => parse_number(second, 2)?,
+        None if fraction.is_none() => 0,
+        None => return None,
+    };
+
+    let nanosecond = match fraction {
+        Some(fraction) if !fraction.is_empty() && fraction.len() <= 9 => {
+            let digits: u32 = parse_number(fraction, fraction.len())?;
+            digits * 10u32.pow(9 - fraction.len() as u32)
+        },
+        Some(_) => return None,
+        None => 0,
+    };
+
+    Some(Time { hour, minute, second, nanosecond })
+}
+
+/// Parse `Z`, `+HH`, `+HHMM` or `+HH:MM` into seconds east of UTC.
+fn parse_offset(s: &str) -> Option<i32> {
+    if s.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+
+    let sign = match s.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let s = s[1..].replacen(':', "", 1);
+    let (hours, minutes): (i32, i32) = match s.len() {
+        2 => (parse_number(&s, 2)?, 0),
+        4 => (parse_number(&s[..2], 2)?, parse_number(&s[2..], 2)?),
+        _ => return None,
+    };
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+fn parse_timestamp(s: &str) -> Option<Timestamp> {
+    let s = s.trim();
+    if s.len() < 10 || !s.is_char_boundary(10) {
+        return None;
+    }
+
+    let (year, month, day) = parse_date_components(&s[..10])?;
+    let rest = &s[10..];
+    if rest.is_empty() {
+        return Some(Timestamp { year, month, day, time: Time::default(), offset: None });
+    }
+
+    let rest = rest.strip_prefix(|c| c == ' ' || c == 'T' || c == 't')?;
+    let (time, offset) = match rest.find(['+', '-', 'Z', 'z']) {
+        Some(i) => (rest[..i].trim_end(), Some(parse_offset(&rest[i..])?)),
+        None => (rest, None),
+    };
+
+    Some(Timestamp { year, month, day, time: parse_time_components(time)?, offset })
+}
+
+/// Fraction of a second with as many digits as needed, i.e. 3, 6 or 9, or none.
+fn format_fraction(nanosecond: u32) -> String {
+    if nanosecond == 0 {
+        String::new()
+    } else if nanosecond.is_multiple_of(1_000_000) {
+        format!(".{:03}", nanosecond / 1_000_000)
+    } else if nanosecond.is_multiple_of(1_000) {
+        format!(".{:06}", nanosecond / 1_000)
+    } else {
+        format!(".{:09}", nanosecond)
+    }
+}
+
+fn format_offset(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    format!("{}{:02}:{:02}", sign, seconds / 3600, seconds % 3600 / 60)
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_support {
+    use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike, Utc};
+    use crate::Result;
+    use super::{format_fraction, format_offset, invalid, parse_date_components, parse_time_components, parse_timestamp, Time, Timestamp};
+
+    fn to_naive_time(time: &Time) -> Option<NaiveTime> {
+        NaiveTime::from_hms_nano_opt(time.hour, time.minute, time.second, time.nanosecond)
+    }
+
+    fn to_naive_date_time(timestamp: &Timestamp) -> Option<NaiveDateTime> {
+        let date = NaiveDate::from_ymd_opt(timestamp.year, timestamp.month, timestamp.day)?;
+        Some(NaiveDateTime::new(date, to_naive_time(&timestamp.time)?))
+    }
+
+    fn to_date_time(timestamp: &Timestamp) -> Option<DateTime<FixedOffset>> {
+        let offset = FixedOffset::east_opt(timestamp.offset.unwrap_or(0))?;
+        offset.from_local_datetime(&to_naive_date_time(timestamp)?).single()
+    }
+
+    /// Parse a date such as `2021-12-31`.
+    pub fn parse_naive_date(s: &str) -> Result<NaiveDate> {
+        parse_date_components(s.trim())
+            .and_then(|(year, month, day)| NaiveDate::from_ymd_opt(year, month, day))
+            .ok_or_else(|| invalid("date", s))
+    }
+
+    /// Parse a time of day such as `23:59:59.999`.
+    pub fn parse_naive_time(s: &str) -> Result<NaiveTime> {
+        parse_time_components(s.trim())
+            .as_ref()
+            .and_then(to_naive_time)
+            .ok_or_else(|| invalid("time", s))
+    }
+
+    /// Parse a timestamp such as `2021-12-31 23:59:59.999`. A date without a time is midnight.
+    ///
+    /// Timestamps with an offset are converted to UTC, the same as SQLite's `datetime` function.
+    pub fn parse_naive_date_time(s: &str) -> Result<NaiveDateTime> {
+        let timestamp = parse_timestamp(s).ok_or_else(|| invalid("timestamp", s))?;
+        match timestamp.offset {
+            Some(_) => to_date_time(&timestamp).map(|dt| dt.naive_utc()),
+            None => to_naive_date_time(&timestamp),
+        }.ok_or_else(|| invalid("timestamp", s))
+    }
+
+    /// Parse a timestamp such as `2021-12-31 23:59:59.999+01:00`.
+    /// Timestamps without an offset are taken to be UTC.
+    pub fn parse_date_time(s: &str) -> Result<DateTime<FixedOffset>> {
+        parse_timestamp(s)
+            .as_ref()
+            .and_then(to_date_time)
+            .ok_or_else(|| invalid("timestamp", s))
+    }
+
+    pub fn format_naive_date(date: &NaiveDate) -> String {
+        format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day())
+    }
+
+    pub fn format_naive_time(time: &NaiveTime) -> String {
+        format!("{:02}:{:02}:{:02}{}", time.hour(), time.minute(), time.second(), format_fraction(time.nanosecond()))
+    }
+
+    pub fn format_naive_date_time(date_time: &NaiveDateTime) -> String {
+        format!("{} {}", format_naive_date(&date_time.date()), format_naive_time(&date_time.time()))
+    }
+
+    pub fn format_date_time<Tz: TimeZone>(date_time: &DateTime<Tz>) -> String {
+        let offset = date_time.offset().fix().local_minus_utc();
+        format!("{}{}", format_naive_date_time(&date_time.naive_local()), format_offset(offset))
+    }
+
+    /// Convert a timestamp to UTC.
+    pub fn to_utc(date_time: DateTime<FixedOffset>) -> DateTime<Utc> {
+        date_time.with_timezone(&Utc)
+    }
+}
+
+#[cfg(feature = "chrono")]
+pub use chrono_support::*;
+
+#[cfg(feature = "time")]
+mod time_support {
+    use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+    use crate::Result;
+    use super::{format_fraction, format_offset, invalid, parse_timestamp, Timestamp};
+
+    fn to_offset_date_time(timestamp: &Timestamp) -> Option<OffsetDateTime> {
+        let month = Month::try_from(u8::try_from(timestamp.month).ok()?).ok()?;
+        let date = Date::from_calendar_date(timestamp.year, month, u8::try_from(timestamp.day).ok()?).ok()?;
+        let time = time::Time::from_hms_nano(
+            u8::try_from(timestamp.time.hour).ok()?,
+            u8::try_from(timestamp.time.minute).ok()?,
+            u8::try_from(timestamp.time.second).ok()?,
+            timestamp.time.nanosecond,
+        ).ok()?;
+        let offset = UtcOffset::from_whole_seconds(timestamp.offset.unwrap_or(0)).ok()?;
+        Some(PrimitiveDateTime::new(date, time).assume_offset(offset))
+    }
+
+    /// Parse a timestamp such as `2021-12-31 23:59:59.999+01:00`.
+    /// Timestamps without an offset are taken to be UTC.
+    pub fn parse_offset_date_time(s: &str) -> Result<OffsetDateTime> {
+        parse_timestamp(s)
+            .as_ref()
+            .and_then(to_offset_date_time)
+            .ok_or_else(|| invalid("timestamp", s))
+    }
+
+    pub fn format_offset_date_time(date_time: &OffsetDateTime) -> String {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}{}{}",
+            date_time.year(),
+            u8::from(date_time.month()),
+            date_time.day(),
+            date_time.hour(),
+            date_time.minute(),
+            date_time.second(),
+            format_fraction(date_time.nanosecond()),
+            format_offset(date_time.offset().whole_seconds()),
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+pub use time_support::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::datetime::{parse_offset, parse_timestamp};
+
+    #[test]
+    fn parse_timestamp_components() {
+        let timestamp = parse_timestamp("2021-12-31T23:59:59.5+01:30").unwrap();
+        assert_eq!((2021, 12, 31), (timestamp.year, timestamp.month, timestamp.day));
+        assert_eq!((23, 59, 59, 500_000_000), (timestamp.time.hour, timestamp.time.minute, timestamp.time.second, timestamp.time.nanosecond));
+        assert_eq!(Some(5400), timestamp.offset);
+
+        let timestamp = parse_timestamp("2021-12-31 10:30").unwrap();
+        assert_eq!((10, 30, 0), (timestamp.time.hour, timestamp.time.minute, timestamp.time.second));
+        assert_eq!(None, timestamp.offset);
+
+        assert!(parse_timestamp("2021-12-31").is_some());
+        assert!(parse_timestamp("2021-12-31 25").is_none());
+        assert!(parse_timestamp("31/12/2021").is_none());
+        assert!(parse_timestamp("2021-12-31 10:30:00.").is_none());
+    }
+
+    #[test]
+    fn parse_offsets() {
+        assert_eq!(Some(0), parse_offset("Z"));
+        assert_eq!(Some(-5 * 3600), parse_offset("-05"));
+        assert_eq!(Some(5 * 3600 + 45 * 60), parse_offset("+0545"));
+        assert_eq!(Some(3600), parse_offset("+01:00"));
+        assert_eq!(None, parse_offset("+1"));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_round_trip() -> crate::Result<()> {
+        use crate::datetime::{format_date_time, format_naive_date_time, parse_date_time, parse_naive_date_time, parse_naive_time};
+
+        let date_time = parse_naive_date_time("2021-12-31 23:59:59.123")?;
+        assert_eq!("2021-12-31 23:59:59.123", format_naive_date_time(&date_time));
+        assert_eq!("2021-12-31 22:59:59.123", format_naive_date_time(&parse_naive_date_time("2021-12-31T23:59:59.123+01:00")?));
+
+        let date_time = parse_date_time("2021-12-31 23:59:59-05:00")?;
+        assert_eq!("2021-12-31 23:59:59-05:00", format_date_time(&date_time));
+        assert_eq!("2022-01-01 04:59:59+00:00", format_date_time(&crate::datetime::to_utc(date_time)));
+
+        assert!(parse_naive_time("24:00:00").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_round_trip() -> crate::Result<()> {
+        use crate::datetime::{format_offset_date_time, parse_offset_date_time};
+
+        let date_time = parse_offset_date_time("2021-12-31 23:59:59.000001+05:30")?;
+        assert_eq!("2021-12-31 23:59:59.000001+05:30", format_offset_date_time(&date_time));
+        assert_eq!(1640975399, date_time.unix_timestamp());
+        Ok(())
+    }
+}
@@ -25,6 +25,29 @@ pub enum RsdbcErrors {
 
     #[error("Unknown Database")]
     UnknownDatabase, // UnsupportedScheme
+
+    #[error("No rows returned when exactly one was expected")]
+    NoRows,
+
+    #[error("More than one row returned when at most one was expected")]
+    TooManyRows,
+
+    #[error("Index {index} out of bounds for length {length}")]
+    IndexOutOfBounds { index: u64, length: u64 },
+
+    #[error("Illegal argument: `{0}`")]
+    IllegalArgument(String),
+
+    #[error("No such element: `{0}`")]
+    NoSuchElement(String),
+
+    /// An error reported by the database
+    #[error("Database error: {0}")]
+    Database(Box<DatabaseError>),
+
+    /// A statement of a script failed, see [crate::script]
+    #[error("Statement at line {line} failed: {source}")]
+    Script { line: usize, sql: String, source: Box<RsdbcErrors> },
 }
 
 impl RsdbcErrors {
@@ -40,26 +63,473 @@ impl RsdbcErrors {
     pub fn config(err: String) -> Self {
         RsdbcErrors::Configuration(err)
     }
+
+    /// Returns the [DatabaseError] if this error was reported by the database.
+    pub fn as_database_error(&self) -> Option<&DatabaseError> {
+        match self {
+            RsdbcErrors::Database(e) => Some(e),
+            RsdbcErrors::Script { source, .. } => source.as_database_error(),
+            _ => None,
+        }
+    }
+
+    /// Returns the [DatabaseErrorKind] if this error was reported by the database.
+    pub fn kind(&self) -> Option<DatabaseErrorKind> {
+        self.as_database_error().map(|e| e.kind)
+    }
+
+    /// Classifies this error into the R2DBC exception hierarchy.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            RsdbcErrors::Database(e) => e.kind.category(),
+            RsdbcErrors::Script { source, .. } => source.category(),
+            RsdbcErrors::Configuration(_)
+            | RsdbcErrors::UrlParseError(_)
+            | RsdbcErrors::ParseIntError(_)
+            | RsdbcErrors::UnknownDatabase => ErrorCategory::NonTransientResource,
+            _ => ErrorCategory::General,
+        }
+    }
+
+    /// Returns true if a retry of the failed operation might succeed without any intervention,
+    /// e.g. a timeout or a lost connection.
+    pub fn is_transient(&self) -> bool {
+        self.category().is_transient()
+    }
+
+    /// Returns true if the database aborted the operation because of contention with other
+    /// transactions, so retrying the whole transaction is safe and likely to succeed.
+    ///
+    /// This covers serialization failures and deadlocks (postgres `40001`/`40P01`, mysql `1213`)
+    /// and lock timeouts (postgres `55P03`, mysql `1205`, sqlite `SQLITE_BUSY`/`SQLITE_LOCKED`).
+    /// Unlike [RsdbcErrors::is_transient] this excludes errors such as a lost connection where
+    /// the outcome of the operation is unknown.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind(), Some(
+            DatabaseErrorKind::SerializationFailure
+            | DatabaseErrorKind::Deadlock
+            | DatabaseErrorKind::LockNotAvailable
+        ))
+    }
+
+    /// Returns a [std::fmt::Display] implementation that renders the SQL with a caret under the
+    /// error position when known, see [DatabaseError::display_with_sql].
+    pub fn display_with_sql(&self) -> impl std::fmt::Display + '_ {
+        struct Display<'a>(&'a RsdbcErrors);
+
+        impl std::fmt::Display for Display<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self.0 {
+                    RsdbcErrors::Database(e) => write!(f, "Database error: {}", e.display_with_sql()),
+                    RsdbcErrors::Script { line, source, .. } => {
+                        write!(f, "Statement at line {} failed: {}", line, source.display_with_sql())
+                    },
+                    e => write!(f, "{}", e),
+                }
+            }
+        }
+
+        Display(self)
+    }
+
+    /// Attaches the SQL that caused this error if it was reported by the database and does not
+    /// already have SQL associated with it.
+    pub fn with_sql(mut self, sql: &str) -> Self {
+        if let RsdbcErrors::Database(e) = &mut self {
+            if e.sql.is_none() {
+                e.sql = Some(sql.to_string());
+            }
+        }
+        self
+    }
+}
+
+impl From<DatabaseError> for RsdbcErrors {
+    fn from(err: DatabaseError) -> Self {
+        RsdbcErrors::Database(Box::new(err))
+    }
+}
+
+/// An error reported by the database along with the diagnostic information the driver provides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseError {
+    /// Classification of the error, derived from the SQLSTATE or vendor code
+    pub kind: DatabaseErrorKind,
+    /// Five character SQLSTATE code, e.g. `23505`. SQLite does not provide one.
+    pub sql_state: Option<String>,
+    /// Vendor specific error code e.g. MySQL's `1062` or SQLite's extended result code
+    pub vendor_code: Option<i32>,
+    /// Primary human readable error message
+    pub message: String,
+    /// Optional secondary message with more detail about the problem
+    pub detail: Option<String>,
+    /// Optional suggestion on what to do about the problem
+    pub hint: Option<String>,
+    /// Name of the constraint that was violated, if any
+    pub constraint: Option<String>,
+    /// SQL that caused the error, if known
+    pub sql: Option<String>,
+    /// 1-based character position in `sql` where the error was detected, typically only
+    /// reported for syntax errors
+    pub position: Option<usize>,
+}
+
+impl DatabaseError {
+
+    pub fn new(kind: DatabaseErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            sql_state: None,
+            vendor_code: None,
+            message: message.into(),
+            detail: None,
+            hint: None,
+            constraint: None,
+            sql: None,
+            position: None,
+        }
+    }
+
+    /// Creates an error whose kind is derived from the SQLSTATE
+    pub fn from_sql_state(sql_state: &str, message: impl Into<String>) -> Self {
+        Self {
+            sql_state: Some(sql_state.to_string()),
+            ..Self::new(DatabaseErrorKind::from_sql_state(sql_state), message)
+        }
+    }
+
+    /// Set the position to the first occurrence of `token` in the SQL, for databases that
+    /// report the offending token rather than its position.
+    ///
+    /// This is a heuristic: when the token also appears before the error the earlier occurrence
+    /// is reported, so prefer a position reported by the database where there is one.
+    pub fn locate(&mut self, token: &str) {
+        self.locate_on_line(token, 1);
+    }
+
+    /// Set the position to the first occurrence of `token` on or after the 1-based `line` of the
+    /// SQL, for databases that report the offending token and its line.
+    pub fn locate_on_line(&mut self, token: &str, line: usize) {
+        if token.is_empty() {
+            return;
+        }
+        if let Some(sql) = &self.sql {
+            let start: usize = sql.split_inclusive('\n').take(line.saturating_sub(1)).map(str::len).sum();
+            if let Some(index) = sql[start..].find(token) {
+                self.position = Some(sql[..start + index].chars().count() + 1);
+            }
+        }
+    }
+
+    /// Returns a [std::fmt::Display] implementation that also renders the line of SQL containing
+    /// the error position with a caret under it, similar to psql:
+    ///
+    /// ```text
+    /// syntax error at or near "SELEC" (SQLSTATE 42601)
+    /// LINE 1: SELEC 1
+    ///         ^
+    /// ```
+    ///
+    /// Without SQL or a position this is the same as the regular [std::fmt::Display] output.
+    pub fn display_with_sql(&self) -> DisplayWithSql<'_> {
+        DisplayWithSql(self)
+    }
+}
+
+/// See [DatabaseError::display_with_sql]
+pub struct DisplayWithSql<'a>(&'a DatabaseError);
+
+impl std::fmt::Display for DisplayWithSql<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let err = self.0;
+        write!(f, "{}", err)?;
+
+        let (sql, position) = match (&err.sql, err.position) {
+            (Some(sql), Some(position)) if position > 0 => (sql, position),
+            _ => return Ok(()),
+        };
+
+        // find the line and column of the 1-based character position
+        let mut line_number = 1;
+        let mut line_start = 0;
+        let mut column = None;
+        for (i, (byte_index, c)) in sql.char_indices().enumerate() {
+            if i + 1 == position {
+                column = Some(sql[line_start..byte_index].chars().count());
+                break;
+            }
+            if c == '\n' {
+                line_number += 1;
+                line_start = byte_index + 1;
+            }
+        }
+        // a position one past the end points at the end of the SQL, e.g. an unexpected end of input
+        let column = match column {
+            Some(column) => column,
+            None if position == sql.chars().count() + 1 => sql[line_start..].chars().count(),
+            None => return Ok(()),
+        };
+
+        let line = sql[line_start..].lines().next().unwrap_or("");
+        let prefix = format!("LINE {}: ", line_number);
+        // keep tabs so the caret lines up with the SQL above it
+        let padding: String = line
+            .chars()
+            .take(column)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(f, "\n{}{}\n{}{}^", prefix, line, " ".repeat(prefix.len()), padding)
+    }
 }
 
-#[derive(Debug)]
+impl std::fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        match (&self.sql_state, self.vendor_code) {
+            (Some(state), Some(code)) => write!(f, " (SQLSTATE {}, code {})", state, code)?,
+            (Some(state), None) => write!(f, " (SQLSTATE {})", state)?,
+            (None, Some(code)) => write!(f, " (code {})", code)?,
+            (None, None) => {},
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, "\nDETAIL: {}", detail)?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\nHINT: {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DatabaseError {}
+
+/// Classification of a [DatabaseError] that is common across drivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
-pub enum RsdbcError {
-    BadGrammar,
+pub enum DatabaseErrorKind {
+    /// SQL syntax error or access to an object that does not exist
+    SyntaxError,
+    /// Insufficient privileges or invalid authorization
+    PermissionDenied,
+    /// A unique or primary key constraint was violated
+    UniqueViolation,
+    /// A foreign key constraint was violated
+    ForeignKeyViolation,
+    /// A not null constraint was violated
+    NotNullViolation,
+    /// A check constraint was violated
+    CheckViolation,
+    /// Any other integrity constraint violation
+    IntegrityConstraintViolation,
+    /// Invalid data such as a value out of range or a division by zero
+    DataException,
+    /// The transaction could not be serialized and was rolled back
+    SerializationFailure,
+    /// A deadlock was detected and the transaction was rolled back
+    Deadlock,
+    /// Any other reason the database rolled back the transaction
+    TransactionRollback,
+    /// A lock could not be acquired in time, e.g. SQLite's `SQLITE_BUSY`
+    LockNotAvailable,
+    /// The statement was cancelled, typically because it timed out
+    QueryCanceled,
+    /// The connection to the database failed or was lost
+    ConnectionFailure,
+    /// Any error the driver could not classify
+    Other,
+}
+
+impl DatabaseErrorKind {
+
+    /// Derive the kind from a standard SQLSTATE, falling back to the SQLSTATE class.
+    pub fn from_sql_state(sql_state: &str) -> Self {
+        match sql_state {
+            "23505" => DatabaseErrorKind::UniqueViolation,
+            "23503" => DatabaseErrorKind::ForeignKeyViolation,
+            "23502" => DatabaseErrorKind::NotNullViolation,
+            "23514" => DatabaseErrorKind::CheckViolation,
+            "40001" => DatabaseErrorKind::SerializationFailure,
+            "40P01" => DatabaseErrorKind::Deadlock,
+            "42501" => DatabaseErrorKind::PermissionDenied,
+            "55P03" => DatabaseErrorKind::LockNotAvailable,
+            "57014" => DatabaseErrorKind::QueryCanceled,
+            _ => match sql_state.get(..2) {
+                Some("08") => DatabaseErrorKind::ConnectionFailure,
+                Some("22") => DatabaseErrorKind::DataException,
+                Some("23") => DatabaseErrorKind::IntegrityConstraintViolation,
+                Some("28") => DatabaseErrorKind::PermissionDenied,
+                Some("40") => DatabaseErrorKind::TransactionRollback,
+                Some("42") => DatabaseErrorKind::SyntaxError,
+                _ => DatabaseErrorKind::Other,
+            },
+        }
+    }
+
+    /// The [ErrorCategory] this kind of error belongs to.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            DatabaseErrorKind::SyntaxError => ErrorCategory::BadGrammar,
+            DatabaseErrorKind::PermissionDenied => ErrorCategory::PermissionDenied,
+            DatabaseErrorKind::UniqueViolation
+            | DatabaseErrorKind::ForeignKeyViolation
+            | DatabaseErrorKind::NotNullViolation
+            | DatabaseErrorKind::CheckViolation
+            | DatabaseErrorKind::IntegrityConstraintViolation => ErrorCategory::DataIntegrityViolation,
+            DatabaseErrorKind::DataException => ErrorCategory::NonTransient,
+            DatabaseErrorKind::SerializationFailure
+            | DatabaseErrorKind::Deadlock
+            | DatabaseErrorKind::TransactionRollback => ErrorCategory::Rollback,
+            DatabaseErrorKind::LockNotAvailable
+            | DatabaseErrorKind::QueryCanceled => ErrorCategory::Timeout,
+            DatabaseErrorKind::ConnectionFailure => ErrorCategory::TransientResource,
+            DatabaseErrorKind::Other => ErrorCategory::General,
+        }
+    }
+}
+
+// Based on R2DBC's exception hierarchy
+// https://r2dbc.io/spec/0.9.0.RELEASE/spec/html/#exceptions
+/// Category of an error following the R2DBC transient / non-transient exception hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// Error that could not be classified
     General,
+
+    // non-transient, retrying the same operation will fail again
+    BadGrammar,
+    DataIntegrityViolation,
     NonTransient,
     NonTransientResource,
     PermissionDenied,
+
+    // transient, retrying the operation might succeed
     Rollback,
     Timeout,
     Transient,
-    TransientResource
+    TransientResource,
 }
 
-// index out of bounds
-// IllegalArgumentException
-// NoSuchElementException
+impl ErrorCategory {
+
+    /// Returns true if a retry of the failed operation might succeed without any intervention.
+    pub fn is_transient(&self) -> bool {
+        matches!(self,
+            ErrorCategory::Rollback
+            | ErrorCategory::Timeout
+            | ErrorCategory::Transient
+            | ErrorCategory::TransientResource
+        )
+    }
+
+    /// Returns true if the failed operation will fail again unless its cause is corrected.
+    pub fn is_non_transient(&self) -> bool {
+        matches!(self,
+            ErrorCategory::BadGrammar
+            | ErrorCategory::DataIntegrityViolation
+            | ErrorCategory::NonTransient
+            | ErrorCategory::NonTransientResource
+            | ErrorCategory::PermissionDenied
+        )
+    }
+}
 
 // `Error::InvalidColumnType` if the underlying SQLite column  type is not a valid type as a source for `T`.
 // `Error::InvalidColumnIndex` if `idx` is outside the valid column range for this row.
 // `Error::InvalidColumnName` if `idx` is not a valid column name for this row.
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{DatabaseError, DatabaseErrorKind, ErrorCategory, RsdbcErrors};
+
+    #[test]
+    fn kind_from_sql_state() {
+        assert_eq!(DatabaseErrorKind::UniqueViolation, DatabaseErrorKind::from_sql_state("23505"));
+        assert_eq!(DatabaseErrorKind::IntegrityConstraintViolation, DatabaseErrorKind::from_sql_state("23000"));
+        assert_eq!(DatabaseErrorKind::Deadlock, DatabaseErrorKind::from_sql_state("40P01"));
+        assert_eq!(DatabaseErrorKind::SyntaxError, DatabaseErrorKind::from_sql_state("42601"));
+        assert_eq!(DatabaseErrorKind::ConnectionFailure, DatabaseErrorKind::from_sql_state("08006"));
+        assert_eq!(DatabaseErrorKind::Other, DatabaseErrorKind::from_sql_state("XX000"));
+    }
+
+    #[test]
+    fn database_error_category() {
+        let err = RsdbcErrors::from(DatabaseError::from_sql_state("40001", "could not serialize access"));
+        assert_eq!(Some(DatabaseErrorKind::SerializationFailure), err.kind());
+        assert_eq!(ErrorCategory::Rollback, err.category());
+        assert!(err.is_transient());
+        assert!(err.is_retryable());
+
+        let err = RsdbcErrors::from(DatabaseError::from_sql_state("23505", "duplicate key"));
+        assert!(err.category().is_non_transient());
+        assert!(!err.is_retryable());
+        assert!(matches!(err, RsdbcErrors::Database(e) if e.kind == DatabaseErrorKind::UniqueViolation));
+    }
+
+    #[test]
+    fn connection_failure_should_be_transient_but_not_retryable() {
+        let err = RsdbcErrors::from(DatabaseError::new(DatabaseErrorKind::ConnectionFailure, "connection reset"));
+        assert!(err.is_transient());
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn with_sql_should_only_set_sql_once() {
+        let err = RsdbcErrors::from(DatabaseError::from_sql_state("42601", "syntax error"))
+            .with_sql("SELEC 1")
+            .with_sql("SELECT 2");
+        assert_eq!(Some("SELEC 1"), err.as_database_error().and_then(|e| e.sql.as_deref()));
+    }
+
+    #[test]
+    fn display_includes_sql_state_and_detail() {
+        let mut err = DatabaseError::from_sql_state("23505", "duplicate key value violates unique constraint");
+        err.detail = Some("Key (id)=(1) already exists.".to_string());
+        assert_eq!(
+            "duplicate key value violates unique constraint (SQLSTATE 23505)\nDETAIL: Key (id)=(1) already exists.",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn locate_should_set_character_position() {
+        let mut err = DatabaseError::new(DatabaseErrorKind::SyntaxError, "near \"FORM\": syntax error");
+        err.sql = Some("SELECT 'é' FORM t".to_string());
+        err.locate("FORM");
+        assert_eq!(Some(12), err.position);
+    }
+
+    #[test]
+    fn locate_on_line_should_skip_earlier_lines() {
+        let mut err = DatabaseError::new(DatabaseErrorKind::SyntaxError, "syntax error");
+        err.sql = Some("SELECT t.from\nFROM t from".to_string());
+        err.locate_on_line("from", 2);
+        assert_eq!(Some(22), err.position);
+    }
+
+    #[test]
+    fn display_with_sql_should_render_caret_under_position() {
+        let mut err = DatabaseError::from_sql_state("42601", "syntax error at or near \"FORM\"");
+        err.sql = Some("SELECT *\nFORM t".to_string());
+        err.position = Some(10);
+        assert_eq!(
+            "syntax error at or near \"FORM\" (SQLSTATE 42601)\nLINE 2: FORM t\n        ^",
+            err.display_with_sql().to_string()
+        );
+
+        err.position = Some(15);
+        assert_eq!(
+            "syntax error at or near \"FORM\" (SQLSTATE 42601)\nLINE 2: FORM t\n             ^",
+            err.display_with_sql().to_string()
+        );
+
+        let err = RsdbcErrors::from(err).with_sql("ignored");
+        assert!(err.display_with_sql().to_string().starts_with("Database error: syntax error"));
+    }
+
+    #[test]
+    fn display_with_sql_without_position_should_match_display() {
+        let err = DatabaseError::from_sql_state("42601", "syntax error").display_with_sql().to_string();
+        assert_eq!("syntax error (SQLSTATE 42601)", err);
+    }
+}
@@ -0,0 +1,197 @@
+//! JSON values which are bound and fetched through serde.
+//!
+//! Postgres has `json` and `jsonb` types and MySQL a `JSON` type whereas SQLite stores JSON as
+//! TEXT or BLOB. Values are bound as [Value::Json] and read with [ResultSet::get_json_value].
+//!
+//! Databases without arrays can store a [Value::Array] as a JSON array, see [to_json_array].
+
+use std::ops::{Deref, DerefMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::error::RsdbcErrors;
+use crate::{Result, ResultSet, Value};
+
+/// A value which is serialized to JSON when bound and deserialized from JSON when fetched.
+///
+/// ```ignore
+/// let payload = Json(Payload { id: 1 });
+/// statement.execute_update(&[payload.to_value()?])?;
+///
+/// let payload: Option<Json<Payload>> = rs.get_json(0)?;
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Serialize> Json<T> {
+    /// Serialize into a [Value] which can be bound to a statement
+    pub fn to_value(&self) -> Result<Value> {
+        serde_json::to_value(&self.0).map(Value::Json).map_err(invalid_json)
+    }
+}
+
+impl<T> From<T> for Json<T> {
+    fn from(value: T) -> Self {
+        Json(value)
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+pub(crate) fn invalid_json(e: serde_json::Error) -> RsdbcErrors {
+    RsdbcErrors::General(format!("invalid json: {}", e))
+}
+
+/// Convert the elements of a [Value::Array] into a JSON array.
+///
+/// Exact numerics, dates and times and UUIDs become strings so that no precision is lost.
+/// Bytes can not be represented and a [Value::Clob] is read into a string.
+pub fn to_json_array(values: &[Option<Value>]) -> Result<serde_json::Value> {
+    values.iter()
+        .map(|v| v.as_ref().map_or(Ok(serde_json::Value::Null), to_json))
+        .collect::<Result<Vec<_>>>()
+        .map(serde_json::Value::Array)
+}
+
+fn to_json(value: &Value) -> Result<serde_json::Value> {
+    let json = match value {
+        Value::Bool(v) => serde_json::Value::from(*v),
+        Value::Int32(v) => serde_json::Value::from(*v),
+        Value::UInt32(v) => serde_json::Value::from(*v),
+        Value::Int64(v) => serde_json::Value::from(*v),
+        Value::Double(v) => serde_json::Number::from_f64(*v)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| RsdbcErrors::IllegalArgument(format!("{} can not be represented as JSON", v)))?,
+        Value::String(v) => serde_json::Value::from(v.as_str()),
+        Value::Bytes(_) | Value::Blob(_) => {
+            return Err(RsdbcErrors::Unsupported("bytes can not be stored in a JSON array".to_string()))
+        },
+        Value::Clob(v) => String::from_utf8(v.read_to_vec()?)
+            .map_err(|e| RsdbcErrors::IllegalArgument(format!("invalid UTF-8 in clob: {}", e)))?
+            .into(),
+        #[cfg(feature = "chrono")]
+        Value::NaiveDate(v) => crate::datetime::format_naive_date(v).into(),
+        #[cfg(feature = "chrono")]
+        Value::NaiveTime(v) => crate::datetime::format_naive_time(v).into(),
+        #[cfg(feature = "chrono")]
+        Value::NaiveDateTime(v) => crate::datetime::format_naive_date_time(v).into(),
+        #[cfg(feature = "chrono")]
+        Value::DateTimeUtc(v) => crate::datetime::format_date_time(v).into(),
+        #[cfg(feature = "chrono")]
+        Value::DateTimeFixedOffset(v) => crate::datetime::format_date_time(v).into(),
+        #[cfg(feature = "time")]
+        Value::OffsetDateTime(v) => crate::datetime::format_offset_date_time(v).into(),
+        #[cfg(feature = "rust_decimal")]
+        Value::Decimal(v) => v.to_string().into(),
+        #[cfg(feature = "bigdecimal")]
+        Value::BigDecimal(v) => v.to_string().into(),
+        #[cfg(feature = "uuid")]
+        Value::Uuid(v) => v.hyphenated().to_string().into(),
+        Value::Json(v) => v.clone(),
+        Value::Array(v) => to_json_array(v)?,
+    };
+    Ok(json)
+}
+
+/// Convert a JSON array into the elements of a [Value::Array].
+///
+/// Integers become [Value::Int64], other numbers [Value::Double] and objects [Value::Json].
+pub fn from_json_array(json: serde_json::Value) -> Result<Vec<Option<Value>>> {
+    match json {
+        serde_json::Value::Array(values) => Ok(values.into_iter().map(from_json).collect()),
+        v => Err(RsdbcErrors::General(format!("expected a JSON array but was {}", v))),
+    }
+}
+
+fn from_json(json: serde_json::Value) -> Option<Value> {
+    let value = match json {
+        serde_json::Value::Null => return None,
+        serde_json::Value::Bool(v) => Value::Bool(v),
+        serde_json::Value::Number(v) => match v.as_i64() {
+            Some(v) => Value::Int64(v),
+            None => Value::Double(v.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(v) => Value::String(v),
+        serde_json::Value::Array(values) => Value::Array(values.into_iter().map(from_json).collect()),
+        v @ serde_json::Value::Object(_) => Value::Json(v),
+    };
+    Some(value)
+}
+
+impl<'a> dyn ResultSet + 'a {
+
+    /// Deserialize the JSON value of the column.
+    pub fn get_json<T: DeserializeOwned>(&self, i: u64) -> Result<Option<Json<T>>> {
+        self.get_json_value(i)?
+            .map(|v| serde_json::from_value(v).map(Json).map_err(invalid_json))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::json::{from_json_array, to_json_array, Json};
+    use crate::testing::VecResultSet;
+    use crate::{Result, ResultSet, Value};
+
+    #[test]
+    fn arrays_should_be_converted_to_json_arrays() -> Result<()> {
+        let array = vec![
+            Some(Value::array(vec![Value::Int32(1), Value::Int64(2)])),
+            Some(Value::array(vec![Some(Value::String("a".to_string())), None])),
+            Some(Value::array(vec![Value::Bool(true), Value::Double(1.5)])),
+        ];
+        let json = to_json_array(&array)?;
+        assert_eq!(r#"[[1,2],["a",null],[true,1.5]]"#, json.to_string());
+
+        let values = from_json_array(json)?;
+        assert_eq!(3, values.len());
+        match &values[1] {
+            Some(Value::Array(v)) => {
+                assert!(matches!(&v[0], Some(Value::String(s)) if s == "a"));
+                assert!(v[1].is_none());
+            },
+            v => panic!("unexpected value {:?}", v),
+        }
+        assert!(matches!(&values[0], Some(Value::Array(v)) if matches!(v[1], Some(Value::Int64(2)))));
+
+        assert!(to_json_array(&[Some(Value::Bytes(vec![1]))]).is_err());
+        assert!(from_json_array(serde_json::json!({"a": 1})).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_should_round_trip() -> Result<()> {
+        let value = Json(HashMap::from([("id".to_string(), 1)]));
+        let bound = match value.to_value()? {
+            Value::Json(v) => v,
+            v => panic!("unexpected value {:?}", v),
+        };
+
+        let mut rs: Box<dyn ResultSet> = Box::new(VecResultSet::new(vec![vec![Some(Value::Json(bound))]]));
+        assert!(rs.next());
+        assert_eq!(Some(value), rs.get_json::<HashMap<String, i32>>(0)?);
+        assert!(rs.get_json::<Vec<i32>>(0).is_err());
+
+        Ok(())
+    }
+}
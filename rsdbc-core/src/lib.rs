@@ -1,11 +1,45 @@
 use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::time::Duration;
 use url::Url;
+use crate::connection::IsolationLevel;
 use crate::error::RsdbcErrors;
 
+pub mod call;
 pub mod error;
 pub mod connection;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod datetime;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod lob;
+pub mod mapper;
+pub mod named;
+pub mod retry;
+pub mod script;
+pub mod sql;
+#[cfg(test)]
+mod testing;
+
+#[cfg(feature = "bigdecimal")]
+pub use bigdecimal;
+#[cfg(feature = "chrono")]
+pub use chrono;
+#[cfg(feature = "rust_decimal")]
+pub use rust_decimal;
+#[cfg(feature = "json")]
+pub use serde_json;
+#[cfg(feature = "time")]
+pub use time;
+#[cfg(feature = "uuid")]
+pub use uuid;
+pub use crate::call::{OutParameterValues, ParameterMode, Segment};
+#[cfg(feature = "json")]
+pub use crate::json::Json;
+pub use crate::lob::{Blob, Clob, Lob};
+pub use crate::named::{NamedSql, NamedStatement};
+pub use crate::sql::Dialect;
 
 /// RSDBC Result type
 pub type Result<T> = std::result::Result<T, RsdbcErrors>;
@@ -13,18 +47,152 @@ pub type Result<T> = std::result::Result<T, RsdbcErrors>;
 
 #[derive(Debug, Clone)]
 pub enum Value {
+    Bool(bool),
     Int32(i32),
     UInt32(u32),
+    Int64(i64),
+    Double(f64),
     String(String),
+    Bytes(Vec<u8>),
+    #[cfg(feature = "chrono")]
+    NaiveDate(chrono::NaiveDate),
+    #[cfg(feature = "chrono")]
+    NaiveTime(chrono::NaiveTime),
+    #[cfg(feature = "chrono")]
+    NaiveDateTime(chrono::NaiveDateTime),
+    #[cfg(feature = "chrono")]
+    DateTimeUtc(chrono::DateTime<chrono::Utc>),
+    #[cfg(feature = "chrono")]
+    DateTimeFixedOffset(chrono::DateTime<chrono::FixedOffset>),
+    #[cfg(feature = "time")]
+    OffsetDateTime(time::OffsetDateTime),
+    #[cfg(feature = "rust_decimal")]
+    Decimal(rust_decimal::Decimal),
+    #[cfg(feature = "bigdecimal")]
+    BigDecimal(bigdecimal::BigDecimal),
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+    /// see [Json] to bind any serializable value
+    #[cfg(feature = "json")]
+    Json(serde_json::Value),
+    /// An array whose elements may be NULL. Multi-dimensional arrays are nested arrays of the
+    /// same length, e.g. `{{1,2},{3,4}}`.
+    ///
+    /// Only postgres has arrays. MySQL and SQLite can store them as JSON arrays when enabled.
+    Array(Vec<Option<Value>>),
+    /// A binary large object which is streamed from its source when the statement executes.
+    /// SQLite binds it whole, see the [lob] module.
+    Blob(Lob),
+    /// A character large object of UTF-8 text which is streamed from its source when the
+    /// statement executes. SQLite binds it whole, see the [lob] module.
+    Clob(Lob),
     // TODO: add other types
 }
 
+impl Value {
+    /// Create an [Value::Array] from values or optional values, e.g.
+    /// `Value::array(vec![Some(Value::Int32(1)), None])`
+    pub fn array<I, T>(values: I) -> Value
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Option<Value>>,
+    {
+        Value::Array(values.into_iter().map(Into::into).collect())
+    }
+}
 
 
 
-// TODO: maybe enum?
+
+/// How a UUID is stored by databases without a UUID type such as MySQL and SQLite.
+///
+/// UUIDs are read back regardless of how they were stored.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum UuidStorage {
+    /// The hyphenated text representation, e.g. `CHAR(36)`
+    #[default]
+    Text,
+    /// The 16 bytes, e.g. `BINARY(16)`
+    Binary,
+}
+
+/// A warning reported by the database, e.g. a postgres NOTICE, a MySQL `SHOW WARNINGS` row or
+/// a message from the SQLite error log.
+///
+/// Unlike errors, warnings don't stop the statement from executing so they are collected on the
+/// [Connection](crate::connection::Connection) and on the [ResultSet] they were reported for.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SQLWarning {
+    /// Severity as reported by the database, e.g. `WARNING` or `NOTICE` for postgres and
+    /// `Warning` or `Note` for MySQL
+    pub severity: Option<String>,
+    pub sql_state: Option<String>,
+    pub vendor_code: Option<i32>,
+    pub message: String,
+}
+
+impl SQLWarning {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            severity: None,
+            sql_state: None,
+            vendor_code: None,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SQLWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(severity) = &self.severity {
+            write!(f, "{}: ", severity)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(code) = self.sql_state.as_deref().map(str::to_string).or_else(|| self.vendor_code.map(|c| c.to_string())) {
+            write!(f, " ({})", code)?;
+        }
+        Ok(())
+    }
+}
+
+/// Chain of [SQLWarning]s, in the order they were reported, that can be shared between a
+/// connection and whatever reports warnings on its behalf such as a background task.
+#[derive(Debug, Clone, Default)]
+pub struct SQLWarnings {
+    warnings: Arc<Mutex<Vec<SQLWarning>>>,
+}
+
+impl SQLWarnings {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, warning: SQLWarning) {
+        self.lock().push(warning);
+    }
+
+    pub fn extend(&self, warnings: impl IntoIterator<Item = SQLWarning>) {
+        self.lock().extend(warnings);
+    }
+
+    /// Returns a copy of the warnings reported so far
+    pub fn get(&self) -> Vec<SQLWarning> {
+        self.lock().clone()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
 
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<SQLWarning>> {
+        // a panic while holding the lock can't leave the Vec in an inconsistent state
+        self.warnings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
 }
 
 
@@ -37,28 +205,138 @@ pub struct ConfigurationOption {
 /// This interface is typically implemented by code that calls [beginTransaction(TransactionDefinition)]
 pub trait TransactionDefinition {
 
-    // TODO: This might have to return Option<&V> like HashMap
     /// Retrieve a transaction attribute value by its attribute identifier.
     /// This low-level interface allows querying transaction attributes supported by the {@link Connection} that should be applied
     ///
     /// returns the value of the transaction attribute. Can be None to indicate absence of the attribute
-    fn get_attribute(&self, attribute: &str) -> OptionValue;
+    fn get_attribute(&self, attribute: &str) -> Option<&OptionValue>;
+}
+
+// Typed accessors for the well known [TransactionOptions] so drivers dont each have to match on OptionValue
+impl<'a> dyn TransactionDefinition + 'a {
+
+    pub fn isolation_level(&self) -> Result<Option<IsolationLevel>> {
+        match self.get_attribute(TransactionOptions::ISOLATION_LEVEL) {
+            None => Ok(None),
+            Some(OptionValue::String(v)) => Ok(Some(IsolationLevel::new(v)?)),
+            Some(v) => Err(invalid_attribute(TransactionOptions::ISOLATION_LEVEL, v)),
+        }
+    }
+
+    pub fn read_only(&self) -> Result<Option<bool>> {
+        self.get_bool(TransactionOptions::READ_ONLY)
+    }
+
+    pub fn name(&self) -> Result<Option<&str>> {
+        match self.get_attribute(TransactionOptions::NAME) {
+            None => Ok(None),
+            Some(OptionValue::String(v)) => Ok(Some(v.as_str())),
+            Some(v) => Err(invalid_attribute(TransactionOptions::NAME, v)),
+        }
+    }
+
+    pub fn lock_wait_timeout(&self) -> Result<Option<Duration>> {
+        match self.get_attribute(TransactionOptions::LOCK_WAIT_TIMEOUT) {
+            None => Ok(None),
+            Some(OptionValue::Duration(v)) => Ok(Some(*v)),
+            Some(v) => Err(invalid_attribute(TransactionOptions::LOCK_WAIT_TIMEOUT, v)),
+        }
+    }
+
+    pub fn deferrable(&self) -> Result<Option<bool>> {
+        self.get_bool(TransactionOptions::DEFERRABLE)
+    }
+
+    fn get_bool(&self, attribute: &str) -> Result<Option<bool>> {
+        match self.get_attribute(attribute) {
+            None => Ok(None),
+            Some(OptionValue::Bool(v)) => Ok(Some(*v)),
+            Some(v) => Err(invalid_attribute(attribute, v)),
+        }
+    }
+}
+
+fn invalid_attribute(attribute: &str, value: &OptionValue) -> RsdbcErrors {
+    RsdbcErrors::Configuration(format!("invalid value {:?} for transaction attribute `{}`", value, attribute))
 }
 
 // TODO: where to put constants?
 pub struct TransactionOptions;
 impl TransactionOptions {
     /// Isolation level requested for the transaction.
-    const ISOLATION_LEVEL: &'static str = "isolation_level";
+    pub const ISOLATION_LEVEL: &'static str = "isolation_level";
 
     /// The transaction mutability (i.e. whether the transaction should be started in read-only mode)
-    const READ_ONLY: &'static str = "read_only";
+    pub const READ_ONLY: &'static str = "read_only";
 
     /// Name of the transaction.
-    const NAME: &'static str = "name";
+    pub const NAME: &'static str = "name";
 
-    ///
-    const LOCK_WAIT_TIMEOUT: &'static str = "lock_wait_timeout";
+    /// How long to wait for a lock before giving up.
+    pub const LOCK_WAIT_TIMEOUT: &'static str = "lock_wait_timeout";
+
+    /// Whether a serializable read only transaction may block until it can run without
+    /// risk of a serialization failure. Currently only supported by Postgres.
+    pub const DEFERRABLE: &'static str = "deferrable";
+}
+
+/// Builder for a generic [TransactionDefinition]. Drivers ignore attributes they do not support.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionDefinitionBuilder {
+    pub options: HashMap<String, OptionValue>,
+}
+
+impl TransactionDefinitionBuilder {
+
+    pub fn new() -> Self {
+        Self {
+            options: Default::default()
+        }
+    }
+
+    pub fn build(&self) -> SimpleTransactionDefinition {
+        SimpleTransactionDefinition {
+            options: self.options.to_owned()
+        }
+    }
+
+    pub fn isolation_level(&mut self, isolation_level: IsolationLevel) -> &mut Self {
+        self.add_option(TransactionOptions::ISOLATION_LEVEL, isolation_level.as_sql().into())
+    }
+
+    pub fn read_only(&mut self, read_only: bool) -> &mut Self {
+        self.add_option(TransactionOptions::READ_ONLY, read_only.into())
+    }
+
+    pub fn name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.add_option(TransactionOptions::NAME, name.into().into())
+    }
+
+    pub fn lock_wait_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.add_option(TransactionOptions::LOCK_WAIT_TIMEOUT, timeout.into())
+    }
+
+    pub fn deferrable(&mut self, deferrable: bool) -> &mut Self {
+        self.add_option(TransactionOptions::DEFERRABLE, deferrable.into())
+    }
+
+    /// Add a driver specific attribute
+    pub fn add_option<K: Into<String>>(&mut self, key: K, value: OptionValue) -> &mut Self {
+        self.options.insert(key.into(), value);
+        self
+    }
+}
+
+/// [TransactionDefinition] backed by a map of attributes. See [TransactionDefinitionBuilder]
+#[derive(Debug, Clone, Default)]
+pub struct SimpleTransactionDefinition {
+    options: HashMap<String, OptionValue>,
+}
+
+impl TransactionDefinition for SimpleTransactionDefinition {
+    fn get_attribute(&self, attribute: &str) -> Option<&OptionValue> {
+        self.options.get(attribute)
+    }
 }
 
 
@@ -201,21 +479,207 @@ pub trait ResultSet {
     fn get_f64(&self, i: u64) -> Result<Option<f64>>;
     fn get_string(&self, i: u64) -> Result<Option<String>>;
     fn get_bytes(&self, i: u64) -> Result<Option<Vec<u8>>>;
+
+    // the default implementations of the date and time getters parse the text representation
+    // described in the datetime module
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_date(&self, i: u64) -> Result<Option<chrono::NaiveDate>> {
+        self.get_string(i)?.map(|s| datetime::parse_naive_date(&s)).transpose()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_time(&self, i: u64) -> Result<Option<chrono::NaiveTime>> {
+        self.get_string(i)?.map(|s| datetime::parse_naive_time(&s)).transpose()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_date_time(&self, i: u64) -> Result<Option<chrono::NaiveDateTime>> {
+        self.get_string(i)?.map(|s| datetime::parse_naive_date_time(&s)).transpose()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_date_time_utc(&self, i: u64) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        Ok(self.get_date_time_fixed_offset(i)?.map(datetime::to_utc))
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_date_time_fixed_offset(&self, i: u64) -> Result<Option<chrono::DateTime<chrono::FixedOffset>>> {
+        self.get_string(i)?.map(|s| datetime::parse_date_time(&s)).transpose()
+    }
+
+    #[cfg(feature = "time")]
+    fn get_offset_date_time(&self, i: u64) -> Result<Option<time::OffsetDateTime>> {
+        self.get_string(i)?.map(|s| datetime::parse_offset_date_time(&s)).transpose()
+    }
+
+    // exact numerics are read from their text representation so that no precision is lost, e.g.
+    // postgres' NUMERIC and MySQL's DECIMAL
+
+    #[cfg(feature = "rust_decimal")]
+    fn get_decimal(&self, i: u64) -> Result<Option<rust_decimal::Decimal>> {
+        self.get_string(i)?.map(|s| parse_decimal(&s)).transpose()
+    }
+
+    #[cfg(feature = "bigdecimal")]
+    fn get_big_decimal(&self, i: u64) -> Result<Option<bigdecimal::BigDecimal>> {
+        self.get_string(i)?.map(|s| parse_decimal(&s)).transpose()
+    }
+
+    /// The default implementation reads 16 bytes, e.g. MySQL's `BINARY(16)`, or the text
+    /// representation, e.g. `CHAR(36)`.
+    #[cfg(feature = "uuid")]
+    fn get_uuid(&self, i: u64) -> Result<Option<uuid::Uuid>> {
+        self.get_bytes(i)?.map(|b| parse_uuid(&b)).transpose()
+    }
+
+    /// Returns the elements of an array column, see [Value::Array].
+    ///
+    /// The default implementation reads JSON arrays when the `json` feature is enabled and
+    /// otherwise returns [RsdbcErrors::Unsupported].
+    fn get_array(&self, i: u64) -> Result<Option<Vec<Option<Value>>>> {
+        #[cfg(feature = "json")]
+        {
+            self.get_json_value(i)?.map(json::from_json_array).transpose()
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            let _ = i;
+            Err(RsdbcErrors::Unsupported("arrays are not supported by this driver".to_string()))
+        }
+    }
+
+    /// Returns the JSON value of the column. Use `get_json` to deserialize it into a [Json].
+    #[cfg(feature = "json")]
+    fn get_json_value(&self, i: u64) -> Result<Option<serde_json::Value>> {
+        self.get_bytes(i)?
+            .map(|b| serde_json::from_slice(&b).map_err(json::invalid_json))
+            .transpose()
+    }
+
+    /// Returns a [Blob] which reads the column in chunks.
+    ///
+    /// The default implementation wraps the value returned by `get_bytes`, which the drivers
+    /// fetch whole with the row, see the [lob] module to read larger values.
+    fn get_blob(&self, i: u64) -> Result<Option<Blob<'_>>> {
+        Ok(self.get_bytes(i)?.map(Blob::from_bytes))
+    }
+
+    /// Returns a [Clob] which reads the column in chunks.
+    ///
+    /// The default implementation wraps the value returned by `get_string`, which the drivers
+    /// fetch whole with the row, see the [lob] module to read larger values.
+    fn get_clob(&self, i: u64) -> Result<Option<Clob<'_>>> {
+        Ok(self.get_string(i)?.map(Clob::from_string))
+    }
+
+    /// Returns the [RowMetadata] describing the rows of this result.
+    ///
+    /// The default implementation returns [RsdbcErrors::Unsupported].
+    fn row_metadata(&self) -> Result<Box<dyn RowMetadata>> {
+        Err(RsdbcErrors::Unsupported("row metadata is not supported by this driver".to_string()))
+    }
+
+    /// Returns the warnings reported while producing this result, in the order they were reported.
+    fn warnings(&self) -> Vec<SQLWarning> {
+        Vec::new()
+    }
+
+    /// Clears the warnings reported while producing this result.
+    fn clear_warnings(&mut self) {}
+}
+
+impl<'a> dyn ResultSet + 'a {
+
+    /// Returns the value of the column read as the [Value] suitable for `r#type`, e.g. a
+    /// [Value::Int64] for [RsdbcType::Bigint].
+    ///
+    /// Exact numerics and dates and times are read as the types of the enabled features or as
+    /// their text representation.
+    pub fn get_value(&self, i: u64, r#type: RsdbcType) -> Result<Option<Value>> {
+        let value = match r#type {
+            RsdbcType::Boolean => self.get_bool(i)?.map(Value::Bool),
+            RsdbcType::Tinyint | RsdbcType::Smallint | RsdbcType::Integer => self.get_i32(i)?.map(Value::Int32),
+            RsdbcType::Bigint => self.get_i64(i)?.map(Value::Int64),
+            RsdbcType::Float | RsdbcType::Real | RsdbcType::Double => self.get_f64(i)?.map(Value::Double),
+            RsdbcType::Binary | RsdbcType::Varbinary | RsdbcType::Blob => self.get_bytes(i)?.map(Value::Bytes),
+            RsdbcType::Collection => self.get_array(i)?.map(Value::Array),
+            #[cfg(feature = "bigdecimal")]
+            RsdbcType::Numeric | RsdbcType::Decimal => self.get_big_decimal(i)?.map(Value::BigDecimal),
+            #[cfg(all(feature = "rust_decimal", not(feature = "bigdecimal")))]
+            RsdbcType::Numeric | RsdbcType::Decimal => self.get_decimal(i)?.map(Value::Decimal),
+            #[cfg(feature = "chrono")]
+            RsdbcType::Date => self.get_naive_date(i)?.map(Value::NaiveDate),
+            #[cfg(feature = "chrono")]
+            RsdbcType::Time => self.get_naive_time(i)?.map(Value::NaiveTime),
+            #[cfg(feature = "chrono")]
+            RsdbcType::Timestamp => self.get_naive_date_time(i)?.map(Value::NaiveDateTime),
+            #[cfg(feature = "chrono")]
+            RsdbcType::TimestampWithTimeZone => self.get_date_time_fixed_offset(i)?.map(Value::DateTimeFixedOffset),
+            #[cfg(all(feature = "time", not(feature = "chrono")))]
+            RsdbcType::TimestampWithTimeZone => self.get_offset_date_time(i)?.map(Value::OffsetDateTime),
+            _ => self.get_string(i)?.map(Value::String),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "uuid")]
+fn parse_uuid(bytes: &[u8]) -> Result<uuid::Uuid> {
+    let uuid = match bytes.len() {
+        16 => uuid::Uuid::from_slice(bytes).ok(),
+        _ => std::str::from_utf8(bytes).ok().and_then(|s| uuid::Uuid::parse_str(s.trim()).ok()),
+    };
+    uuid.ok_or_else(|| RsdbcErrors::General(format!("invalid uuid {}", String::from_utf8_lossy(bytes))))
+}
+
+#[cfg(any(feature = "rust_decimal", feature = "bigdecimal"))]
+fn parse_decimal<T>(s: &str) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    s.trim().parse().map_err(|e| RsdbcErrors::General(format!("invalid decimal {}: {}", s, e)))
 }
 
 
 
 /// Meta data for result set
+///
+/// Column indexes are 0-based. Methods panic if the index is out of range.
 pub trait ResultSetMetaData {
     fn num_columns(&self) -> u64;
+
+    /// The name, or alias, of the column in the result
     fn column_name(&self, i: u64) -> String;
-    fn column_type(&self, i: u64) -> DataType;
+
+    fn column_type(&self, i: u64) -> RsdbcType;
+
+    /// The database specific type name, e.g. `VARCHAR(20)` or `int4`
     fn column_type_name(&self, i: u64) -> String;
-    fn precision(&self, i: u64) -> u64;
-    fn schema_name(&self, i: u64) -> String;
-    fn table_name(&self, i: u64) -> String;
-    fn is_nullable(&self, i: u64) -> String;
-    fn is_read_only(&self, i: u64) -> String;
+
+    /// Maximum precision for numeric columns, length in characters for character columns and
+    /// length in bytes for binary columns. [None] if not applicable or not known.
+    fn precision(&self, i: u64) -> Option<u64>;
+
+    /// Number of digits to the right of the decimal point. [None] if not applicable or not known.
+    fn scale(&self, i: u64) -> Option<u64>;
+
+    /// Schema, or database for SQLite and MySQL, of the table the column originates from
+    fn schema_name(&self, i: u64) -> Option<String>;
+
+    /// Table the column originates from. [None] for expressions.
+    fn table_name(&self, i: u64) -> Option<String>;
+
+    /// Name of the table column the result column originates from, which differs from
+    /// [ResultSetMetaData::column_name] when the column is aliased. [None] for expressions.
+    fn base_column_name(&self, i: u64) -> Option<String>;
+
+    fn is_nullable(&self, i: u64) -> Nullability;
+
+    /// Whether the column can't be written to, e.g. because it is an expression rather than a
+    /// table column
+    fn is_read_only(&self, i: u64) -> bool;
 }
 
 pub trait Row<'stmt> {
@@ -234,6 +698,10 @@ pub trait Row<'stmt> {
 /// Columns indexes are 0-based.
 /// Column names do not necessarily reflect the column names how they are in the underlying tables
 /// but rather how columns are represented (e.g. aliased) in the result.
+///
+/// Column name lookups are case-insensitive and the first matching column wins when several
+/// columns have the same name. Wrapping the name in double quotes, e.g. `"Name"`, matches the
+/// name exactly including its case.
 pub trait RowMetadata {
 
     /// Returns the [ColumnMetadata] for one column in this row.
@@ -243,37 +711,50 @@ pub trait RowMetadata {
     /// * `index`: the column index starting at 0
     ///
     /// return the [ColumnMetadata] for one column in this row
-    /// return index out of bounds error if [index] is out of range (negative or equals/exceeds [getColumnMetadatas().len()]
-    fn get_column_metadata(index: i32) -> Result<Box<dyn ColumnMetadata>>;
-
+    /// returns [RsdbcErrors::IndexOutOfBounds] if `index` equals/exceeds the number of columns
+    fn get_column_metadata(&self, index: u64) -> Result<&dyn ColumnMetadata>;
 
     /// Returns the [ColumnMetadata] for one column in this row.
     ///
     /// Arguments:
     ///
     /// * `name`: the name of the column. Column names are case insensitive.
-    /// When a get method contains several columns with same name,
-    /// then the value of the first matching column will be returned.
+    ///   When this row contains several columns with same name,
+    ///   then the metadata of the first matching column will be returned.
     ///
     /// return the [ColumnMetadata] for one column in this row
-    /// returns illegal argument error if [name] empty
-    /// returns no such element if there is no column with the [name]
-    /// NoSuchElementException   if there is no column with the {@code name}
-    fn get_column_metadata_by_name<S: Into<String>>(name: S) -> Result<Box<dyn ColumnMetadata>>;
-
+    /// returns [RsdbcErrors::IllegalArgument] if `name` is empty
+    /// returns [RsdbcErrors::NoSuchElement] if there is no column with the `name`
+    fn get_column_metadata_by_name(&self, name: &str) -> Result<&dyn ColumnMetadata>;
 
     /// Returns the [ColumnMetadata] for all columns in this row.
-    fn get_column_metadatas() -> Vec<Box<dyn ColumnMetadata>>;
+    fn get_column_metadatas(&self) -> Vec<&dyn ColumnMetadata>;
 
-    /// Returns whether this object contains metadata for [column_name].
-    /// Lookups are case-insensitive.
-    /// Implementations may allow escape characters to enforce a particular mode of comparison
-    /// when querying for presence/absence of a column.
+    /// Returns whether this object contains metadata for `column_name`.
+    /// Lookups are case-insensitive unless the name is wrapped in double quotes.
     ///
-    /// return true if this object contains metadata for [column_name]; false otherwise.
-    fn contains<S: Into<String>>(column_name: S) -> bool;
+    /// return true if this object contains metadata for `column_name`; false otherwise.
+    fn contains(&self, column_name: &str) -> bool;
 }
 
+/// Find the index of the first name matching `name`.
+///
+/// Names are compared case-insensitively unless `name` is wrapped in double quotes, e.g. `"Name"`,
+/// in which case the name without quotes must match exactly.
+pub fn find_by_name<'a, I>(names: I, name: &str) -> Option<usize>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let exact = name
+        .strip_prefix('"')
+        .and_then(|n| n.strip_suffix('"'))
+        .filter(|_| name.len() >= 2);
+
+    match exact {
+        Some(exact) => names.into_iter().position(|n| n == exact),
+        None => names.into_iter().position(|n| n.eq_ignore_ascii_case(name)),
+    }
+}
 
 // TODO: do we want this generic trait or do we want something more specific like ColumnMetadata
 // Java R2DBC has this has database / sql types called Type
@@ -283,13 +764,17 @@ pub trait RowMetadata {
 // SQLX - Provides information about a SQL type for the database driver.
 pub trait TypeInfo {
 
-    // might not need this
-    // https://stackoverflow.com/questions/21747136/how-do-i-print-the-type-of-a-variable-in-rust
+    /// Name of the Rust type values of this type are read as by default
     fn rust_type(&self) -> &'static str;
 
     fn name(&self) -> &str;
 }
 
+/// Generic SQL types.
+///
+/// Drivers map their native types, e.g. postgres type OIDs, MySQL column types or SQLite declared
+/// types, to and from these types.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum RsdbcType {
     Char,
     Varchar,
@@ -298,6 +783,7 @@ pub enum RsdbcType {
     Clob,
     Nclob,
     Boolean,
+    Binary,
     Varbinary,
     Blob,
     Integer,
@@ -317,9 +803,65 @@ pub enum RsdbcType {
     Collection,
 }
 
+impl RsdbcType {
+    /// All generic SQL types
+    pub const ALL: &'static [RsdbcType] = &[
+        RsdbcType::Char,
+        RsdbcType::Varchar,
+        RsdbcType::Nchar,
+        RsdbcType::Nvarchar,
+        RsdbcType::Clob,
+        RsdbcType::Nclob,
+        RsdbcType::Boolean,
+        RsdbcType::Binary,
+        RsdbcType::Varbinary,
+        RsdbcType::Blob,
+        RsdbcType::Integer,
+        RsdbcType::Tinyint,
+        RsdbcType::Smallint,
+        RsdbcType::Bigint,
+        RsdbcType::Numeric,
+        RsdbcType::Decimal,
+        RsdbcType::Float,
+        RsdbcType::Real,
+        RsdbcType::Double,
+        RsdbcType::Date,
+        RsdbcType::Time,
+        RsdbcType::TimeWithTimeZone,
+        RsdbcType::Timestamp,
+        RsdbcType::TimestampWithTimeZone,
+        RsdbcType::Collection,
+    ];
+}
+
 impl TypeInfo for RsdbcType {
     fn rust_type(&self) -> &'static str {
-        todo!()
+        match self {
+            RsdbcType::Char
+            | RsdbcType::Varchar
+            | RsdbcType::Nchar
+            | RsdbcType::Nvarchar
+            | RsdbcType::Clob
+            | RsdbcType::Nclob => "String",
+            RsdbcType::Boolean => "bool",
+            RsdbcType::Binary | RsdbcType::Varbinary | RsdbcType::Blob => "Vec<u8>",
+            RsdbcType::Tinyint => "i8",
+            RsdbcType::Smallint => "i16",
+            RsdbcType::Integer => "i32",
+            RsdbcType::Bigint => "i64",
+            // FLOAT without a precision is a double precision number
+            RsdbcType::Float | RsdbcType::Double => "f64",
+            RsdbcType::Real => "f32",
+            // read as their text representation
+            RsdbcType::Numeric
+            | RsdbcType::Decimal
+            | RsdbcType::Date
+            | RsdbcType::Time
+            | RsdbcType::TimeWithTimeZone
+            | RsdbcType::Timestamp
+            | RsdbcType::TimestampWithTimeZone => "String",
+            RsdbcType::Collection => "Vec<T>",
+        }
     }
 
     fn name(&self) -> &str {
@@ -331,6 +873,7 @@ impl TypeInfo for RsdbcType {
             RsdbcType::Clob => "CLOB",
             RsdbcType::Nclob => "NCLOB",
             RsdbcType::Boolean => "BOOLEAN",
+            RsdbcType::Binary => "BINARY",
             RsdbcType::Varbinary => "VARBINARY",
             RsdbcType::Blob => "BLOB",
             RsdbcType::Integer => "INTEGER",
@@ -352,55 +895,107 @@ impl TypeInfo for RsdbcType {
     }
 }
 
-
 // java R2DBC extends ReadableMetadata.
 /// Represents the metadata for a column of the results returned from a query.
-/// The implementation of all methods except [getName()] is optional for drivers.
+/// The implementation of all methods except [ReadableMetadata::get_name] is optional for drivers.
 /// Column metadata is optionally available as by-product of statement execution on a best-effort basis.
 pub trait ColumnMetadata: ReadableMetadata {
 
 }
 
+/// Driver specific description of a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeTypeMetadata {
+    /// Type name as reported by the database, e.g. `int4`, `VARCHAR(20)` or `NEWDECIMAL`
+    pub name: String,
+    /// Type identifier if the database has one, e.g. the postgres type OID or MySQL column type
+    pub id: Option<u32>,
+}
+
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Nullability {
     Nullable,
     NonNull,
     Unknown
 }
 
-
-/// RSDBC Data Types
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum DataType {
-    Bool,
-    Byte,
-    Char,
-    Short,
-    Integer,
-    Float,
-    Double,
-    Decimal,
-    Date,
-    Time,
-    Datetime,
-    Utf8,
-    Binary,
-}
-
 #[derive(Debug, Clone)]
 pub struct Column {
     name: String,
-    data_type: DataType,
-    //precision: u6,
+    data_type: RsdbcType,
+    type_name: String,
+    native_type_id: Option<u32>,
+    precision: Option<u64>,
+    scale: Option<u64>,
+    schema_name: Option<String>,
+    table_name: Option<String>,
+    base_column_name: Option<String>,
+    nullability: Nullability,
+    read_only: bool,
 }
 
 impl Column {
-    pub fn new(name: &str, data_type: DataType) -> Self {
+    pub fn new(name: &str, data_type: RsdbcType) -> Self {
         Column {
             name: name.to_owned(),
             data_type,
+            type_name: String::new(),
+            native_type_id: None,
+            precision: None,
+            scale: None,
+            schema_name: None,
+            table_name: None,
+            base_column_name: None,
+            nullability: Nullability::Unknown,
+            read_only: false,
         }
     }
+
+    pub fn type_name(mut self, type_name: impl Into<String>) -> Self {
+        self.type_name = type_name.into();
+        self
+    }
+
+    pub fn native_type_id(mut self, native_type_id: Option<u32>) -> Self {
+        self.native_type_id = native_type_id;
+        self
+    }
+
+    pub fn precision(mut self, precision: Option<u64>) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn scale(mut self, scale: Option<u64>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn schema_name(mut self, schema_name: Option<String>) -> Self {
+        self.schema_name = schema_name;
+        self
+    }
+
+    pub fn table_name(mut self, table_name: Option<String>) -> Self {
+        self.table_name = table_name;
+        self
+    }
+
+    pub fn base_column_name(mut self, base_column_name: Option<String>) -> Self {
+        self.base_column_name = base_column_name;
+        self
+    }
+
+    pub fn nullability(mut self, nullability: Nullability) -> Self {
+        self.nullability = nullability;
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
 }
 
 impl ResultSetMetaData for Vec<Column> {
@@ -412,38 +1007,101 @@ impl ResultSetMetaData for Vec<Column> {
         self[i as usize].name.clone()
     }
 
-    fn column_type(&self, i: u64) -> DataType {
+    fn column_type(&self, i: u64) -> RsdbcType {
         self[i as usize].data_type
     }
 
-    // fn precision(&self, i: u64) -> u64 {
-    //     match self.column_type {
+    fn column_type_name(&self, i: u64) -> String {
+        self[i as usize].type_name.clone()
+    }
 
-    //     }
-    // }
+    fn precision(&self, i: u64) -> Option<u64> {
+        self[i as usize].precision
+    }
 
-    fn column_type_name(&self, i: u64) -> String {
-        todo!()
+    fn scale(&self, i: u64) -> Option<u64> {
+        self[i as usize].scale
+    }
+
+    fn schema_name(&self, i: u64) -> Option<String> {
+        self[i as usize].schema_name.clone()
+    }
+
+    fn table_name(&self, i: u64) -> Option<String> {
+        self[i as usize].table_name.clone()
+    }
+
+    fn base_column_name(&self, i: u64) -> Option<String> {
+        self[i as usize].base_column_name.clone()
+    }
+
+    fn is_nullable(&self, i: u64) -> Nullability {
+        self[i as usize].nullability
+    }
+
+    fn is_read_only(&self, i: u64) -> bool {
+        self[i as usize].read_only
+    }
+}
+
+impl ReadableMetadata for Column {
+    fn rust_type(&self) -> &'static str {
+        self.data_type.rust_type()
+    }
+
+    fn db_type(&self) -> &dyn TypeInfo {
+        &self.data_type
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_native_type_metadata(&self) -> Option<NativeTypeMetadata> {
+        Some(NativeTypeMetadata {
+            name: self.type_name.clone(),
+            id: self.native_type_id,
+        })
+    }
+
+    fn get_nullability(&self) -> Nullability {
+        self.nullability
+    }
+
+    fn get_precision(&self) -> Option<u64> {
+        self.precision
     }
 
-    fn precision(&self, i: u64) -> u64 {
-        todo!()
+    fn get_scale(&self) -> Option<u64> {
+        self.scale
     }
+}
+
+impl ColumnMetadata for Column {}
 
-    fn schema_name(&self, i: u64) -> String {
-        todo!()
+impl RowMetadata for Vec<Column> {
+    fn get_column_metadata(&self, index: u64) -> Result<&dyn ColumnMetadata> {
+        self.get(index as usize)
+            .map(|c| c as &dyn ColumnMetadata)
+            .ok_or(RsdbcErrors::IndexOutOfBounds { index, length: self.len() as u64 })
     }
 
-    fn table_name(&self, i: u64) -> String {
-        todo!()
+    fn get_column_metadata_by_name(&self, name: &str) -> Result<&dyn ColumnMetadata> {
+        if name.is_empty() {
+            return Err(RsdbcErrors::IllegalArgument("column name must not be empty".to_string()));
+        }
+
+        find_by_name(self.iter().map(|c| c.name.as_str()), name)
+            .map(|i| &self[i] as &dyn ColumnMetadata)
+            .ok_or_else(|| RsdbcErrors::NoSuchElement(format!("column {}", name)))
     }
 
-    fn is_nullable(&self, i: u64) -> String {
-        todo!()
+    fn get_column_metadatas(&self) -> Vec<&dyn ColumnMetadata> {
+        self.iter().map(|c| c as &dyn ColumnMetadata).collect()
     }
 
-    fn is_read_only(&self, i: u64) -> String {
-        todo!()
+    fn contains(&self, column_name: &str) -> bool {
+        find_by_name(self.iter().map(|c| c.name.as_str()), column_name).is_some()
     }
 }
 
@@ -471,8 +1129,20 @@ pub trait DatabaseMetadata {
 /// A parameter is invalidated after consumption.
 ///
 /// The number, type and characteristics of parameters are described through [OutParametersMetadata].
-pub trait OutParameters: Readable {
+pub trait OutParameters {
     fn get_metadata(&self) -> Box<dyn OutParametersMetadata>;
+
+    /// Returns the value of the parameter at `index`, [None] if it is NULL.
+    ///
+    /// returns [RsdbcErrors::IndexOutOfBounds] if `index` equals/exceeds the number of parameters
+    fn get_value(&self, index: u32) -> Result<Option<&Value>>;
+
+    /// Returns the value of the first parameter named `name`, [None] if it is NULL.
+    /// Names are case insensitive, see [find_by_name].
+    ///
+    /// returns [RsdbcErrors::IllegalArgument] if `name` is empty and [RsdbcErrors::NoSuchElement]
+    /// if there is no parameter with the `name`
+    fn get_value_by_name(&self, name: &str) -> Result<Option<&Value>>;
 }
 
 /// Represents the metadata for [OUT] parameters of the results returned from a stored procedure.
@@ -514,12 +1184,11 @@ pub trait OutParameterMetadata: ReadableMetadata {}
 /// The implementation of all methods except [get_name()] is optional for drivers.
 /// Metadata is optionally available as by-product of statement execution on a best-effort basis.
 pub trait ReadableMetadata {
-    // rust type
+    /// Returns the name of the Rust type values are read as by default.
     fn rust_type(&self) -> &'static str;
 
-    // type
     /// returns the database Type [TypeInfo]
-    fn db_type(&self) -> dyn TypeInfo;
+    fn db_type(&self) -> &dyn TypeInfo;
 
     /// Returns the name.
     ///
@@ -532,7 +1201,9 @@ pub trait ReadableMetadata {
     /// object exposing additional information.
     ///
     /// The default implementation returns [None].
-    fn get_native_type_metadata(&self);
+    fn get_native_type_metadata(&self) -> Option<NativeTypeMetadata> {
+        None
+    }
 
     // TODO: is this required?
     /// Returns the nullability of values.
@@ -648,16 +1319,143 @@ pub trait In {}
 pub trait Out {}
 
 
-// Lob / Clob / Blob
-
-
+// Lob / Clob / Blob, see the lob module
 
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+    use crate::connection::IsolationLevel;
+    use crate::{Column, find_by_name, NativeTypeMetadata, Nullability, OptionValue, ReadableMetadata, RowMetadata, RsdbcType, SQLWarning, SQLWarnings, TransactionDefinition, TransactionDefinitionBuilder, TransactionOptions, TypeInfo};
+    use crate::error::RsdbcErrors;
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn transaction_definition_builder_should_set_attributes() {
+        let definition = TransactionDefinitionBuilder::new()
+            .isolation_level(IsolationLevel::Serializable)
+            .read_only(true)
+            .name("batch")
+            .lock_wait_timeout(Duration::from_secs(5))
+            .deferrable(true)
+            .build();
+
+        assert_eq!(Some(&OptionValue::Bool(true)), definition.get_attribute(TransactionOptions::READ_ONLY));
+
+        let definition: &dyn TransactionDefinition = &definition;
+        assert_eq!(Some(IsolationLevel::Serializable), definition.isolation_level().unwrap());
+        assert_eq!(Some(true), definition.read_only().unwrap());
+        assert_eq!(Some("batch"), definition.name().unwrap());
+        assert_eq!(Some(Duration::from_secs(5)), definition.lock_wait_timeout().unwrap());
+        assert_eq!(Some(true), definition.deferrable().unwrap());
+    }
+
+    #[test]
+    fn missing_transaction_attribute_should_return_none() {
+        let definition = TransactionDefinitionBuilder::new().build();
+        assert_eq!(None, definition.get_attribute(TransactionOptions::ISOLATION_LEVEL));
+
+        let definition: &dyn TransactionDefinition = &definition;
+        assert_eq!(None, definition.isolation_level().unwrap());
+        assert_eq!(None, definition.read_only().unwrap());
+    }
+
+    #[test]
+    fn transaction_attribute_with_wrong_type_should_return_err() {
+        let definition = TransactionDefinitionBuilder::new()
+            .add_option(TransactionOptions::READ_ONLY, "yes".into())
+            .build();
+
+        let definition: &dyn TransactionDefinition = &definition;
+        assert!(definition.read_only().is_err());
+    }
+
+    #[test]
+    fn warnings_should_be_shared_between_clones() {
+        let warnings = SQLWarnings::new();
+        let reporter = warnings.clone();
+        reporter.push(SQLWarning::new("first"));
+        reporter.push(SQLWarning::new("second"));
+
+        let messages: Vec<String> = warnings.get().into_iter().map(|w| w.message).collect();
+        assert_eq!(vec!["first", "second"], messages);
+
+        warnings.clear();
+        assert!(reporter.is_empty());
+    }
+
+    #[test]
+    fn warning_display() {
+        let warning = SQLWarning {
+            severity: Some("Warning".to_string()),
+            sql_state: None,
+            vendor_code: Some(1265),
+            message: "Data truncated for column 'name' at row 1".to_string(),
+        };
+        assert_eq!("Warning: Data truncated for column 'name' at row 1 (1265)", warning.to_string());
+    }
+
+    fn columns() -> Vec<Column> {
+        vec![
+            Column::new("id", RsdbcType::Integer).nullability(Nullability::NonNull),
+            Column::new("Name", RsdbcType::Varchar).precision(Some(20)),
+            Column::new("name", RsdbcType::Varchar),
+        ]
+    }
+
+    #[test]
+    fn column_lookup_should_be_case_insensitive_and_first_match_wins() {
+        let columns = columns();
+        assert_eq!(Some(1), find_by_name(vec!["id", "Name", "name"], "NAME"));
+
+        let column = columns.get_column_metadata_by_name("NAME").unwrap();
+        assert_eq!(Some(20), column.get_precision());
+        assert!(columns.contains("ID"));
+        assert!(!columns.contains("missing"));
+    }
+
+    #[test]
+    fn quoted_column_lookup_should_match_exactly() {
+        let columns = columns();
+        assert_eq!(None, columns.get_column_metadata_by_name("\"name\"").unwrap().get_precision());
+        assert!(columns.contains("\"Name\""));
+        assert!(!columns.contains("\"NAME\""));
+    }
+
+    #[test]
+    fn column_lookup_errors() {
+        let columns = columns();
+        assert!(matches!(columns.get_column_metadata(3), Err(RsdbcErrors::IndexOutOfBounds { index: 3, length: 3 })));
+        assert!(matches!(columns.get_column_metadata_by_name(""), Err(RsdbcErrors::IllegalArgument(_))));
+        assert!(matches!(columns.get_column_metadata_by_name("missing"), Err(RsdbcErrors::NoSuchElement(_))));
+    }
+
+    #[test]
+    fn column_metadata() {
+        let column = Column::new("id", RsdbcType::Integer)
+            .type_name("int4")
+            .native_type_id(Some(23))
+            .nullability(Nullability::NonNull);
+        assert_eq!("id", column.get_name());
+        assert_eq!("i32", column.rust_type());
+        assert_eq!("INTEGER", column.db_type().name());
+        assert_eq!(Nullability::NonNull, column.get_nullability());
+        assert_eq!(Some(NativeTypeMetadata { name: "int4".to_string(), id: Some(23) }), column.get_native_type_metadata());
+    }
+
+    #[test]
+    fn every_type_should_have_a_name_and_rust_type() {
+        for t in RsdbcType::ALL {
+            assert!(!t.name().is_empty());
+            assert!(!t.rust_type().is_empty());
+        }
+        assert_eq!("i64", RsdbcType::Bigint.rust_type());
+        assert_eq!("f32", RsdbcType::Real.rust_type());
+        assert_eq!("TIMESTAMP_WITH_TIME_ZONE", RsdbcType::TimestampWithTimeZone.name());
+    }
 }
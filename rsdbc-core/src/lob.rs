@@ -0,0 +1,468 @@
+//! Large objects which are read and written in chunks rather than materialised.
+//!
+//! A [Blob] or [Clob] reads a value as a [Read], an [AsyncRead] or a [Stream] of chunks. Those
+//! returned by [ResultSet::get_blob] and [ResultSet::get_clob] wrap the value already fetched
+//! with its row, so a value too large for memory is read with the driver's own methods instead,
+//! e.g. SQLite's incremental blob I/O or postgres' large objects, which borrow the connection.
+//!
+//! A [Lob] is bound as [Value::Blob] or [Value::Clob] from a reader or a stream. It is consumed
+//! when the statement executes so it can only be bound once. Drivers send it in chunks where the
+//! database can append to a value, while SQLite, which copies every bound value, reads it whole.
+//!
+//! ```ignore
+//! let document = Lob::from_reader(File::open("report.pdf")?);
+//! statement.execute_update(&[Value::Blob(document)])?;
+//!
+//! let mut blob = rs.get_blob(0)?.unwrap();
+//! while let Some(chunk) = blob.next().await {
+//!     out.write_all(&chunk?).await?;
+//! }
+//! ```
+
+use std::fmt;
+use std::io::{self, Read};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use bytes::Bytes;
+use futures::executor::block_on;
+use futures::io::{AsyncRead, AsyncReadExt};
+use futures::{Stream, StreamExt};
+use crate::error::RsdbcErrors;
+use crate::{Result, Value};
+
+/// The size of the chunks a [Blob] or [Clob] is streamed in unless set otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A binary large object which is read in chunks.
+pub struct Blob<'a> {
+    reader: Box<dyn Read + 'a>,
+    len: Option<u64>,
+    chunk_size: usize,
+}
+
+impl<'a> Blob<'a> {
+    /// Create a blob reading from `reader`, the length is used as a size hint when known
+    pub fn new<R: Read + 'a>(reader: R, len: Option<u64>) -> Self {
+        Self {
+            reader: Box::new(reader),
+            len,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Create a blob from a value which has already been fetched
+    pub fn from_bytes(bytes: Vec<u8>) -> Blob<'static> {
+        let len = bytes.len() as u64;
+        Blob::new(io::Cursor::new(bytes), Some(len))
+    }
+
+    /// Sets the size of the chunks the blob is streamed in
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// The length in bytes, if known
+    pub fn size(&self) -> Option<u64> {
+        self.len
+    }
+
+    /// Read the remaining bytes into memory
+    pub fn read_to_vec(mut self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.len.unwrap_or(0) as usize);
+        self.reader.read_to_end(&mut bytes).map_err(lob_error)?;
+        Ok(bytes)
+    }
+
+    fn next_chunk(&mut self) -> io::Result<Option<Bytes>> {
+        let mut chunk = vec![0; self.chunk_size];
+        let n = read_fully(&mut self.reader, &mut chunk)?;
+        if n == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(n);
+        Ok(Some(Bytes::from(chunk)))
+    }
+}
+
+impl fmt::Debug for Blob<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Blob")
+            .field("len", &self.len)
+            .field("chunk_size", &self.chunk_size)
+            .finish()
+    }
+}
+
+impl Read for Blob<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+// readers are synchronous, like the rest of the API, so they are always ready
+impl AsyncRead for Blob<'_> {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.get_mut().reader.read(buf))
+    }
+}
+
+impl Stream for Blob<'_> {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().next_chunk().transpose())
+    }
+}
+
+/// A character large object which is read in chunks of UTF-8 text.
+///
+/// As a [Read] or [AsyncRead] it returns the UTF-8 bytes. As a [Stream] chunks end on character
+/// boundaries.
+pub struct Clob<'a> {
+    blob: Blob<'a>,
+    // the start of a character which was split across chunks
+    partial: Vec<u8>,
+}
+
+impl<'a> Clob<'a> {
+    /// Create a clob reading UTF-8 text from `reader`, the length in bytes is used as a size
+    /// hint when known
+    pub fn new<R: Read + 'a>(reader: R, len: Option<u64>) -> Self {
+        Self {
+            blob: Blob::new(reader, len),
+            partial: Vec::new(),
+        }
+    }
+
+    /// Create a clob from a value which has already been fetched
+    pub fn from_string(s: String) -> Clob<'static> {
+        let len = s.len() as u64;
+        Clob::new(io::Cursor::new(s.into_bytes()), Some(len))
+    }
+
+    /// Sets the size in bytes of the chunks the clob is streamed in
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.blob = self.blob.with_chunk_size(chunk_size);
+        self
+    }
+
+    /// The length in bytes, if known
+    pub fn size(&self) -> Option<u64> {
+        self.blob.size()
+    }
+
+    /// Read the remaining text into memory
+    pub fn read_to_string(self) -> Result<String> {
+        let mut bytes = self.partial;
+        bytes.extend(self.blob.read_to_vec()?);
+        String::from_utf8(bytes).map_err(|e| lob_error(invalid_utf8(e.utf8_error())))
+    }
+
+    fn next_chunk(&mut self) -> io::Result<Option<String>> {
+        let mut bytes = std::mem::take(&mut self.partial);
+        match self.blob.next_chunk()? {
+            Some(chunk) => bytes.extend_from_slice(&chunk),
+            None if bytes.is_empty() => return Ok(None),
+            None => return Err(invalid_data("clob ends with an incomplete UTF-8 character")),
+        }
+
+        let valid = match std::str::from_utf8(&bytes) {
+            Ok(_) => bytes.len(),
+            // an incomplete character at the end is completed by the next chunk
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(invalid_utf8(e)),
+        };
+        self.partial = bytes.split_off(valid);
+        if bytes.is_empty() {
+            return self.next_chunk();
+        }
+        // validated above
+        String::from_utf8(bytes).map(Some).map_err(|e| invalid_utf8(e.utf8_error()))
+    }
+}
+
+impl fmt::Debug for Clob<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Clob")
+            .field("len", &self.blob.len)
+            .field("chunk_size", &self.blob.chunk_size)
+            .finish()
+    }
+}
+
+impl Read for Clob<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.partial.is_empty() {
+            let n = self.partial.len().min(buf.len());
+            buf[..n].copy_from_slice(&self.partial[..n]);
+            self.partial.drain(..n);
+            return Ok(n);
+        }
+        Read::read(&mut self.blob, buf)
+    }
+}
+
+impl AsyncRead for Clob<'_> {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Poll::Ready(Read::read(self.get_mut(), buf))
+    }
+}
+
+impl Stream for Clob<'_> {
+    type Item = io::Result<String>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.get_mut().next_chunk().transpose())
+    }
+}
+
+type LobReader = Box<dyn Read + Send>;
+
+/// The contents of a large object which is bound as [Value::Blob] or [Value::Clob].
+///
+/// Clones share the same source which is consumed by the first statement it is bound to.
+#[derive(Clone)]
+pub struct Lob {
+    reader: Arc<Mutex<Option<LobReader>>>,
+    len: Option<u64>,
+}
+
+impl Lob {
+    /// Bind the bytes read from `reader`
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Self {
+        Self {
+            reader: Arc::new(Mutex::new(Some(Box::new(reader)))),
+            len: None,
+        }
+    }
+
+    /// Bind the bytes read from an [AsyncRead].
+    ///
+    /// Statements are synchronous so the reader is polled to completion as the value is sent.
+    pub fn from_async_read<R: AsyncRead + Send + Unpin + 'static>(reader: R) -> Self {
+        Self::from_reader(AsyncReader(reader))
+    }
+
+    /// Bind the chunks of a [Stream].
+    ///
+    /// Statements are synchronous so the stream is polled to completion as the value is sent.
+    pub fn from_stream<S, B>(stream: S) -> Self
+    where
+        S: Stream<Item = io::Result<B>> + Send + Unpin + 'static,
+        B: AsRef<[u8]> + Send + 'static,
+    {
+        Self::from_reader(StreamReader { stream, chunk: None, offset: 0 })
+    }
+
+    /// Sets the length in bytes which some databases need before the value is sent, e.g. to
+    /// reserve space with SQLite's `zeroblob`
+    pub fn with_size(mut self, len: u64) -> Self {
+        self.len = Some(len);
+        self
+    }
+
+    /// The length in bytes, if known
+    pub fn size(&self) -> Option<u64> {
+        self.len
+    }
+
+    /// Take the reader, returns [RsdbcErrors::IllegalArgument] if it has already been consumed
+    pub fn take(&self) -> Result<Box<dyn Read + Send>> {
+        self.reader
+            .lock()
+            .map_err(|_| RsdbcErrors::General("large object reader was poisoned".to_string()))?
+            .take()
+            .ok_or_else(|| RsdbcErrors::IllegalArgument("large object has already been consumed".to_string()))
+    }
+
+    /// Read the whole value into memory, for databases which need the value in a single message
+    pub fn read_to_vec(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.len.unwrap_or(0) as usize);
+        self.take()?.read_to_end(&mut bytes).map_err(lob_error)?;
+        Ok(bytes)
+    }
+}
+
+impl fmt::Debug for Lob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lob").field("len", &self.len).finish()
+    }
+}
+
+impl From<Vec<u8>> for Lob {
+    fn from(bytes: Vec<u8>) -> Self {
+        let len = bytes.len() as u64;
+        Lob::from_reader(io::Cursor::new(bytes)).with_size(len)
+    }
+}
+
+impl From<String> for Lob {
+    fn from(s: String) -> Self {
+        Lob::from(s.into_bytes())
+    }
+}
+
+struct AsyncReader<R>(R);
+
+impl<R: AsyncRead + Unpin> Read for AsyncReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        block_on(self.0.read(buf))
+    }
+}
+
+struct StreamReader<S, B> {
+    stream: S,
+    chunk: Option<B>,
+    offset: usize,
+}
+
+impl<S, B> Read for StreamReader<S, B>
+where
+    S: Stream<Item = io::Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(chunk) = &self.chunk {
+                let remaining = &chunk.as_ref()[self.offset..];
+                if !remaining.is_empty() {
+                    let n = remaining.len().min(buf.len());
+                    buf[..n].copy_from_slice(&remaining[..n]);
+                    self.offset += n;
+                    return Ok(n);
+                }
+            }
+
+            match block_on(self.stream.next()) {
+                Some(chunk) => {
+                    self.chunk = Some(chunk?);
+                    self.offset = 0;
+                },
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Fill `buf` unless the reader ends first, returns the number of bytes read
+fn read_fully<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(read) => n += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn invalid_utf8(e: std::str::Utf8Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Read `lob` a chunk of at most [DEFAULT_CHUNK_SIZE] bytes at a time and pass each to `write`,
+/// for databases which can append to a value, returns the number of bytes written
+pub fn write_chunks(lob: &Lob, mut write: impl FnMut(&[u8]) -> Result<()>) -> Result<u64> {
+    let mut reader = lob.take()?;
+    let mut chunk = vec![0; DEFAULT_CHUNK_SIZE];
+    let mut written = 0;
+    loop {
+        let n = read_fully(&mut reader, &mut chunk).map_err(lob_error)?;
+        if n == 0 {
+            return Ok(written);
+        }
+        write(&chunk[..n])?;
+        written += n as u64;
+    }
+}
+
+/// Convert an I/O error while reading or writing a large object
+pub fn lob_error(e: io::Error) -> RsdbcErrors {
+    RsdbcErrors::General(format!("large object I/O error: {}", e))
+}
+
+impl Value {
+    /// Bind the bytes of a [Lob] as a BLOB, see [Value::Blob]
+    pub fn blob<L: Into<Lob>>(lob: L) -> Value {
+        Value::Blob(lob.into())
+    }
+
+    /// Bind the UTF-8 text of a [Lob] as a CLOB, see [Value::Clob]
+    pub fn clob<L: Into<Lob>>(lob: L) -> Value {
+        Value::Clob(lob.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use futures::executor::block_on;
+    use futures::io::AsyncReadExt;
+    use futures::{stream, TryStreamExt};
+    use crate::lob::{write_chunks, Blob, Clob, Lob, DEFAULT_CHUNK_SIZE};
+    use crate::Value;
+
+    #[test]
+    fn blobs_should_stream_in_chunks() {
+        let blob = Blob::from_bytes((0..10).collect()).with_chunk_size(4);
+        assert_eq!(Some(10), blob.size());
+        let chunks: Vec<Vec<u8>> = block_on(blob.map_ok(|b| b.to_vec()).try_collect()).unwrap();
+        assert_eq!(vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9]], chunks);
+
+        let mut bytes = Vec::new();
+        block_on(AsyncReadExt::read_to_end(&mut Blob::from_bytes(vec![1, 2, 3]), &mut bytes)).unwrap();
+        assert_eq!(vec![1, 2, 3], bytes);
+    }
+
+    #[test]
+    fn lobs_should_be_written_in_chunks() {
+        let lob = Lob::from((0..DEFAULT_CHUNK_SIZE + 1).map(|i| i as u8).collect::<Vec<u8>>());
+        let mut chunks = Vec::new();
+        assert_eq!(DEFAULT_CHUNK_SIZE as u64 + 1, write_chunks(&lob, |chunk| {
+            chunks.push(chunk.to_vec());
+            Ok(())
+        }).unwrap());
+        assert_eq!(vec![DEFAULT_CHUNK_SIZE, 1], chunks.iter().map(Vec::len).collect::<Vec<_>>());
+        assert_eq!(Some(&(DEFAULT_CHUNK_SIZE as u8)), chunks[1].first());
+        assert!(write_chunks(&lob, |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn clob_chunks_should_end_on_character_boundaries() {
+        // é is two bytes so every chunk of 3 bytes splits one
+        let clob = Clob::from_string("aéééb".to_string()).with_chunk_size(3);
+        let chunks: Vec<String> = block_on(clob.try_collect()).unwrap();
+        assert_eq!("aéééb", chunks.concat());
+        assert!(chunks.iter().all(|c| c.len() <= 4));
+
+        let invalid = Clob::new(&[b'a', 0xC3][..], None).with_chunk_size(1);
+        assert!(block_on(invalid.try_collect::<Vec<String>>()).is_err());
+
+        assert_eq!("aé", Clob::from_string("aé".to_string()).read_to_string().unwrap());
+    }
+
+    #[test]
+    fn lobs_should_be_read_from_streams_once() {
+        let chunks = vec![Ok(b"hello ".to_vec()), Ok(b"world".to_vec())];
+        let lob = Lob::from_stream(stream::iter(chunks));
+        let bound = Value::Blob(lob.clone());
+        assert_eq!(b"hello world".to_vec(), lob.read_to_vec().unwrap());
+        match bound {
+            Value::Blob(lob) => assert!(lob.take().is_err()),
+            v => panic!("unexpected value {:?}", v),
+        }
+
+        let mut s = String::new();
+        Lob::from_async_read(futures::io::Cursor::new(b"abc".to_vec())).take().unwrap().read_to_string(&mut s).unwrap();
+        assert_eq!("abc", s);
+        assert_eq!(Some(3), Lob::from("abc".to_string()).size());
+    }
+}
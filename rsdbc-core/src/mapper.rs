@@ -0,0 +1,172 @@
+use crate::error::RsdbcErrors;
+use crate::{Result, ResultSet};
+
+/// Maps the current row of a [ResultSet] to a value.
+///
+/// Implement this for domain types so they can be passed straight to the query helpers,
+/// e.g. `rs.list(User::from_row)`.
+pub trait FromRow: Sized {
+    /// Build `Self` from the row the result set cursor is currently positioned on.
+    fn from_row(row: &dyn ResultSet) -> Result<Self>;
+}
+
+// Similar to JDBI's ResultIterable. These take a row mapper which is any closure or fn that
+// takes the current row, which includes [FromRow::from_row].
+impl<'a> dyn ResultSet + 'a {
+
+    /// Returns the only row in the result set.
+    ///
+    /// returns [RsdbcErrors::NoRows] if zero rows are returned and [RsdbcErrors::TooManyRows]
+    /// if more than one row is returned
+    pub fn one<T, F>(&mut self, mut mapper: F) -> Result<T>
+    where
+        F: FnMut(&dyn ResultSet) -> Result<T>,
+    {
+        if !self.next() {
+            return Err(RsdbcErrors::NoRows);
+        }
+
+        let value = mapper(self)?;
+        if self.next() {
+            return Err(RsdbcErrors::TooManyRows);
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the only row in the result set or [None] if no rows are returned.
+    ///
+    /// returns [RsdbcErrors::TooManyRows] if more than one row is returned
+    pub fn find_one<T, F>(&mut self, mut mapper: F) -> Result<Option<T>>
+    where
+        F: FnMut(&dyn ResultSet) -> Result<T>,
+    {
+        if !self.next() {
+            return Ok(None);
+        }
+
+        let value = mapper(self)?;
+        if self.next() {
+            return Err(RsdbcErrors::TooManyRows);
+        }
+
+        Ok(Some(value))
+    }
+
+    /// Returns the first row in the result set. Any remaining rows are not read.
+    ///
+    /// returns [RsdbcErrors::NoRows] if zero rows are returned
+    pub fn first<T, F>(&mut self, mapper: F) -> Result<T>
+    where
+        F: FnMut(&dyn ResultSet) -> Result<T>,
+    {
+        self.find_first(mapper)?.ok_or(RsdbcErrors::NoRows)
+    }
+
+    /// Returns the first row in the result set or [None] if no rows are returned.
+    /// Any remaining rows are not read.
+    pub fn find_first<T, F>(&mut self, mut mapper: F) -> Result<Option<T>>
+    where
+        F: FnMut(&dyn ResultSet) -> Result<T>,
+    {
+        if !self.next() {
+            return Ok(None);
+        }
+
+        Ok(Some(mapper(self)?))
+    }
+
+    /// Maps every remaining row in the result set and collects them.
+    pub fn list<T, F>(&mut self, mut mapper: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&dyn ResultSet) -> Result<T>,
+    {
+        let mut values = Vec::new();
+        while self.next() {
+            values.push(mapper(self)?);
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::RsdbcErrors;
+    use crate::mapper::FromRow;
+    use crate::testing::VecResultSet;
+    use crate::{Result, ResultSet};
+
+    #[derive(Debug, PartialEq)]
+    struct Id(i32);
+
+    impl FromRow for Id {
+        fn from_row(row: &dyn ResultSet) -> Result<Self> {
+            Ok(Id(row.get_i32(0)?.unwrap()))
+        }
+    }
+
+    fn get_i32(row: &dyn ResultSet) -> Result<i32> {
+        Ok(row.get_i32(0)?.unwrap())
+    }
+
+    #[test]
+    fn one_should_return_only_row() {
+        let mut rs = VecResultSet::of_i32(vec![1]);
+        assert_eq!(Id(1), (&mut rs as &mut dyn ResultSet).one(Id::from_row).unwrap());
+    }
+
+    #[test]
+    fn one_should_return_no_rows_error_when_empty() {
+        let mut rs = VecResultSet::of_i32(vec![]);
+        let result = (&mut rs as &mut dyn ResultSet).one(get_i32);
+        assert!(matches!(result, Err(RsdbcErrors::NoRows)));
+    }
+
+    #[test]
+    fn one_should_return_too_many_rows_error_when_multiple_rows() {
+        let mut rs = VecResultSet::of_i32(vec![1, 2]);
+        let result = (&mut rs as &mut dyn ResultSet).one(get_i32);
+        assert!(matches!(result, Err(RsdbcErrors::TooManyRows)));
+    }
+
+    #[test]
+    fn find_one_should_return_none_when_empty() {
+        let mut rs = VecResultSet::of_i32(vec![]);
+        assert_eq!(None, (&mut rs as &mut dyn ResultSet).find_one(get_i32).unwrap());
+    }
+
+    #[test]
+    fn find_one_should_return_too_many_rows_error_when_multiple_rows() {
+        let mut rs = VecResultSet::of_i32(vec![1, 2]);
+        let result = (&mut rs as &mut dyn ResultSet).find_one(get_i32);
+        assert!(matches!(result, Err(RsdbcErrors::TooManyRows)));
+    }
+
+    #[test]
+    fn first_should_stop_reading_after_first_row() {
+        let mut rs = VecResultSet::of_i32(vec![1, 2, 3]);
+        assert_eq!(1, (&mut rs as &mut dyn ResultSet).first(get_i32).unwrap());
+        assert_eq!(1, rs.rows_read());
+    }
+
+    #[test]
+    fn first_should_return_no_rows_error_when_empty() {
+        let mut rs = VecResultSet::of_i32(vec![]);
+        let result = (&mut rs as &mut dyn ResultSet).first(get_i32);
+        assert!(matches!(result, Err(RsdbcErrors::NoRows)));
+    }
+
+    #[test]
+    fn find_first_should_return_none_when_empty() {
+        let mut rs = VecResultSet::of_i32(vec![]);
+        assert_eq!(None, (&mut rs as &mut dyn ResultSet).find_first(get_i32).unwrap());
+    }
+
+    #[test]
+    fn list_should_collect_all_rows() {
+        let mut rs = VecResultSet::of_i32(vec![1, 2, 3]);
+        let ids = (&mut rs as &mut dyn ResultSet).list(Id::from_row).unwrap();
+        assert_eq!(vec![Id(1), Id(2), Id(3)], ids);
+    }
+}
@@ -0,0 +1,254 @@
+//! Named parameters which are rewritten to the native placeholders of a [Dialect].
+//!
+//! Parameters are written as `:name`, `$name` or `@name` and may be referenced more than once.
+//! They are recognised anywhere outside of string literals, quoted identifiers, comments and
+//! dollar-quoted bodies, so a statement using MySQL session variables, `@var`, or the postgres
+//! `@` operator directly followed by a name should use positional parameters instead.
+//!
+//! ```ignore
+//! let mut stmt = connection.create_named_statement("SELECT * FROM t WHERE a = :id OR b = :id")?;
+//! let rs = stmt.execute_query(&[("id", Value::Int32(1))])?;
+//! ```
+
+use std::collections::HashSet;
+use crate::connection::Statement;
+use crate::error::RsdbcErrors;
+use crate::sql::{is_identifier_part, is_identifier_start, skip_quoted, Dialect};
+use crate::{Result, ResultSet, Value};
+
+/// SQL whose named parameters have been rewritten to the native placeholders of a [Dialect]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedSql {
+    sql: String,
+    dialect: Dialect,
+    // distinct names in the order they first appear
+    names: Vec<String>,
+    // index into names of each parameter in the order they appear
+    parameters: Vec<usize>,
+}
+
+impl NamedSql {
+    /// Parse the named parameters of `sql` and rewrite them for `dialect`.
+    ///
+    /// returns [RsdbcErrors::IllegalArgument] if `sql` mixes named parameters with native
+    /// placeholders
+    pub fn parse(sql: &str, dialect: Dialect) -> Result<Self> {
+        let bytes = sql.as_bytes();
+        let mut rewritten = String::with_capacity(sql.len());
+        let mut names: Vec<String> = Vec::new();
+        let mut parameters = Vec::new();
+        let mut positional = false;
+        // start of the text not yet copied to rewritten
+        let mut copied = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            if let Some(end) = skip_quoted(dialect, sql, i) {
+                i = end;
+                continue;
+            }
+
+            let name_len = match bytes[i] {
+                b':' | b'$' | b'@' => parameter_name_len(bytes, i),
+                _ => 0,
+            };
+            if name_len == 0 {
+                positional |= is_native_placeholder(dialect, bytes, i);
+                i += 1;
+                continue;
+            }
+
+            let name = &sql[i + 1..i + 1 + name_len];
+            let index = match names.iter().position(|n| n == name) {
+                Some(index) => index,
+                None => {
+                    names.push(name.to_string());
+                    names.len() - 1
+                },
+            };
+            let placeholder = match dialect.has_numbered_placeholders() {
+                true => dialect.placeholder(index),
+                false => dialect.placeholder(parameters.len()),
+            };
+            parameters.push(index);
+
+            rewritten.push_str(&sql[copied..i]);
+            rewritten.push_str(&placeholder);
+            i += 1 + name_len;
+            copied = i;
+        }
+        rewritten.push_str(&sql[copied..]);
+
+        if positional && !names.is_empty() {
+            return Err(RsdbcErrors::IllegalArgument(
+                "named parameters can't be mixed with positional placeholders".to_string()
+            ));
+        }
+
+        Ok(Self {
+            sql: rewritten,
+            dialect,
+            names,
+            parameters,
+        })
+    }
+
+    /// The rewritten SQL
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// The distinct parameter names in the order they first appear
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Arrange the named `values` in the order of the native placeholders. A value referenced
+    /// several times is cloned for each placeholder unless the dialect can reference it by number.
+    ///
+    /// returns [RsdbcErrors::IllegalArgument] if a parameter has no value or more than one value
+    /// and [RsdbcErrors::NoSuchElement] if a value is named after no parameter
+    pub fn bind(&self, values: &[(&str, Value)]) -> Result<Vec<Value>> {
+        let mut bound = HashSet::new();
+        for (name, _) in values {
+            if !self.names.iter().any(|n| n == name) {
+                return Err(RsdbcErrors::NoSuchElement(format!("parameter {}", name)));
+            }
+            if !bound.insert(*name) {
+                return Err(RsdbcErrors::IllegalArgument(format!("parameter {} is bound more than once", name)));
+            }
+        }
+
+        let unbound: Vec<&str> = self.names.iter().map(String::as_str).filter(|n| !bound.contains(n)).collect();
+        if !unbound.is_empty() {
+            return Err(RsdbcErrors::IllegalArgument(format!("parameters {} are not bound", unbound.join(", "))));
+        }
+
+        let value = |name: &str| values.iter().find(|(n, _)| *n == name).map(|(_, v)| v.clone());
+        let values = match self.dialect.has_numbered_placeholders() {
+            true => self.names.iter().filter_map(|n| value(n)).collect(),
+            false => self.parameters.iter().filter_map(|i| value(&self.names[*i])).collect(),
+        };
+        Ok(values)
+    }
+}
+
+/// The length of the name following the prefix at `from`, 0 if there is no parameter
+fn parameter_name_len(bytes: &[u8], from: usize) -> usize {
+    let prefix = bytes[from];
+    // a:b, ::casts, :=, @@system_variables and identifiers with $ aren't parameters
+    if from > 0 && (is_identifier_part(bytes[from - 1]) || bytes[from - 1] == prefix) {
+        return 0;
+    }
+    match bytes.get(from + 1) {
+        Some(b) if is_identifier_start(*b) => {},
+        _ => return 0,
+    }
+    bytes[from + 1..]
+        .iter()
+        .position(|b| !is_identifier_part(*b) || *b == b'$')
+        .unwrap_or(bytes.len() - from - 1)
+}
+
+fn is_native_placeholder(dialect: Dialect, bytes: &[u8], i: usize) -> bool {
+    match dialect {
+        Dialect::Postgres => bytes[i] == b'$' && matches!(bytes.get(i + 1), Some(b) if b.is_ascii_digit()),
+        _ => bytes[i] == b'?',
+    }
+}
+
+/// A [Statement] whose parameters are bound by name, see the [named](crate::named) module.
+///
+/// Every parameter must be given a value on each execution, which is checked before the
+/// statement is executed.
+pub struct NamedStatement<'a> {
+    statement: Box<dyn Statement<'a> + 'a>,
+    sql: NamedSql,
+}
+
+impl<'a> NamedStatement<'a> {
+    /// `statement` must have been created from the rewritten [NamedSql::sql]
+    pub fn new(statement: Box<dyn Statement<'a> + 'a>, sql: NamedSql) -> Self {
+        Self { statement, sql }
+    }
+
+    /// The distinct parameter names in the order they first appear
+    pub fn names(&self) -> &[String] {
+        self.sql.names()
+    }
+
+    /// Execute a query that is expected to return a result set, such as a `SELECT` statement
+    pub fn execute_query(&mut self, params: &[(&str, Value)]) -> Result<Box<dyn ResultSet + '_>> {
+        let values = self.sql.bind(params)?;
+        self.statement.execute_query(&values)
+    }
+
+    /// Execute a query that is expected to update some rows.
+    pub fn execute_update(&mut self, params: &[(&str, Value)]) -> Result<u64> {
+        let values = self.sql.bind(params)?;
+        self.statement.execute_update(&values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::RsdbcErrors;
+    use crate::named::NamedSql;
+    use crate::sql::Dialect;
+    use crate::Value;
+
+    #[test]
+    fn parameters_should_be_rewritten_for_each_dialect() {
+        let sql = "SELECT * FROM t WHERE a = :id AND b = $name OR c = @id";
+        let postgres = NamedSql::parse(sql, Dialect::Postgres).unwrap();
+        assert_eq!("SELECT * FROM t WHERE a = $1 AND b = $2 OR c = $1", postgres.sql());
+        assert_eq!(&["id".to_string(), "name".to_string()], postgres.names());
+
+        let mysql = NamedSql::parse(sql, Dialect::MySql).unwrap();
+        assert_eq!("SELECT * FROM t WHERE a = ? AND b = ? OR c = ?", mysql.sql());
+        assert_eq!(postgres.names(), mysql.names());
+    }
+
+    #[test]
+    fn parameters_should_not_be_found_in_quoted_text() {
+        let sql = "SELECT ':a', \":b\", x::text, $$ :c $$, @@version -- :d\n/* :e */ FROM t WHERE y = :f";
+        let parsed = NamedSql::parse(sql, Dialect::Postgres).unwrap();
+        assert_eq!(&["f".to_string()], parsed.names());
+        assert!(parsed.sql().ends_with("y = $1"));
+
+        let parsed = NamedSql::parse("SELECT `:a`, a:b, x := 1 # :c\nFROM t", Dialect::MySql).unwrap();
+        assert!(parsed.names().is_empty());
+    }
+
+    #[test]
+    fn named_and_positional_parameters_should_not_be_mixed() {
+        assert!(matches!(NamedSql::parse("SELECT :a, ?", Dialect::Sqlite), Err(RsdbcErrors::IllegalArgument(_))));
+        assert!(matches!(NamedSql::parse("SELECT :a, $1", Dialect::Postgres), Err(RsdbcErrors::IllegalArgument(_))));
+        assert_eq!("SELECT ?", NamedSql::parse("SELECT ?", Dialect::MySql).unwrap().sql());
+    }
+
+    #[test]
+    fn values_should_be_arranged_by_name() {
+        let sql = "UPDATE t SET a = :a, b = :b WHERE a = :a";
+        let values = [("b", Value::Int32(2)), ("a", Value::Int32(1))];
+
+        let postgres = NamedSql::parse(sql, Dialect::Postgres).unwrap().bind(&values).unwrap();
+        assert!(matches!(postgres[..], [Value::Int32(1), Value::Int32(2)]));
+
+        let sqlite = NamedSql::parse(sql, Dialect::Sqlite).unwrap().bind(&values).unwrap();
+        assert!(matches!(sqlite[..], [Value::Int32(1), Value::Int32(2), Value::Int32(1)]));
+    }
+
+    #[test]
+    fn unbound_and_unknown_names_should_be_reported() {
+        let parsed = NamedSql::parse("SELECT :a, :b", Dialect::Generic).unwrap();
+        assert!(matches!(parsed.bind(&[("a", Value::Int32(1))]), Err(RsdbcErrors::IllegalArgument(_))));
+        assert!(matches!(
+            parsed.bind(&[("a", Value::Int32(1)), ("b", Value::Int32(2)), ("c", Value::Int32(3))]),
+            Err(RsdbcErrors::NoSuchElement(_))
+        ));
+        assert!(matches!(
+            parsed.bind(&[("a", Value::Int32(1)), ("a", Value::Int32(2))]),
+            Err(RsdbcErrors::IllegalArgument(_))
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use futures_timer::Delay;
+use crate::error::RsdbcErrors;
+
+/// Configures how an operation that failed with a retryable error, see [RsdbcErrors::is_retryable],
+/// is retried.
+///
+/// The delay between attempts starts at `initial_backoff` and doubles after each attempt up to
+/// `max_backoff`. When `jitter` is enabled a random delay between half and all of the computed
+/// backoff is used so that competing clients don't retry in lock step.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of times the operation is attempted, including the first attempt.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+
+    /// Only attempt the operation once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            jitter: false,
+        }
+    }
+
+    pub fn new(max_attempts: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff,
+            max_backoff,
+            jitter: true,
+        }
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns true if the operation should be attempted again after failing on `attempt`,
+    /// starting at 1, with `err`.
+    pub fn should_retry(&self, attempt: u32, err: &RsdbcErrors) -> bool {
+        attempt < self.max_attempts && err.is_retryable()
+    }
+
+    /// The delay to wait after the given failed `attempt`, starting at 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self.initial_backoff
+            .checked_mul(1 << exponent)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+
+        if !self.jitter || backoff.is_zero() {
+            return backoff;
+        }
+
+        let half = backoff / 2;
+        let spread = (backoff - half).as_nanos() as u64;
+        half + Duration::from_nanos(random() % (spread + 1))
+    }
+
+    /// Waits for the backoff of the given failed `attempt`.
+    pub async fn wait(&self, attempt: u32) {
+        Delay::new(self.backoff(attempt)).await
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(50), Duration::from_secs(1))
+    }
+}
+
+// RandomState is seeded randomly per instance which is good enough for jitter
+// and saves pulling in a dependency on rand
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::error::{DatabaseError, RsdbcErrors};
+    use crate::retry::RetryPolicy;
+
+    #[test]
+    fn backoff_should_double_up_to_max() {
+        let policy = RetryPolicy::new(10, Duration::from_millis(10), Duration::from_millis(50)).jitter(false);
+        assert_eq!(Duration::from_millis(10), policy.backoff(1));
+        assert_eq!(Duration::from_millis(20), policy.backoff(2));
+        assert_eq!(Duration::from_millis(40), policy.backoff(3));
+        assert_eq!(Duration::from_millis(50), policy.backoff(4));
+        assert_eq!(Duration::from_millis(50), policy.backoff(100));
+    }
+
+    #[test]
+    fn backoff_with_jitter_should_be_between_half_and_full_backoff() {
+        let policy = RetryPolicy::new(10, Duration::from_millis(100), Duration::from_secs(1));
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff >= Duration::from_millis(100) && backoff <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn should_retry_only_retryable_errors_until_max_attempts() {
+        let policy = RetryPolicy::default();
+        let deadlock = RsdbcErrors::from(DatabaseError::from_sql_state("40P01", "deadlock detected"));
+        assert!(policy.should_retry(1, &deadlock));
+        assert!(policy.should_retry(2, &deadlock));
+        assert!(!policy.should_retry(3, &deadlock));
+
+        let unique = RsdbcErrors::from(DatabaseError::from_sql_state("23505", "duplicate key"));
+        assert!(!policy.should_retry(1, &unique));
+        assert!(!policy.should_retry(1, &RsdbcErrors::General("boom".to_string())));
+    }
+}
@@ -0,0 +1,189 @@
+//! Scripts of several SQL statements, such as migrations and seed data.
+//!
+//! A script is split into statements at each delimiter outside of string literals, quoted
+//! identifiers, comments and dollar-quoted bodies. The delimiter is `;` unless changed by a mysql
+//! `DELIMITER` command and the `BEGIN ... END` body of a sqlite trigger isn't split.
+//!
+//! ```ignore
+//! let executed = connection.execute_script(&fs::read_to_string("seed.sql")?)?;
+//! ```
+
+use crate::sql::{is_identifier_part, is_identifier_start, skip_quoted, Dialect};
+
+/// A statement of a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStatement {
+    /// The SQL of the statement without its delimiter
+    pub sql: String,
+    /// The line of the script the statement starts on, starting at 1
+    pub line: usize,
+}
+
+/// Split `script` into its statements. Statements consisting only of comments are left out.
+pub fn split(script: &str, dialect: Dialect) -> Vec<ScriptStatement> {
+    let (mut statements, unterminated) = scan(script, dialect);
+    statements.extend(unterminated);
+    statements
+}
+
+/// Whether the last statement of `script` is followed by a delimiter, e.g. for an interactive
+/// client to decide whether to read another line. Unterminated quoted text makes the statement
+/// incomplete whereas comments on their own are complete.
+pub fn is_complete(script: &str, dialect: Dialect) -> bool {
+    scan(script, dialect).1.is_none()
+}
+
+/// The terminated statements of `script` and the statement after the last delimiter, if any
+fn scan(script: &str, dialect: Dialect) -> (Vec<ScriptStatement>, Option<ScriptStatement>) {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut delimiter = ";".to_string();
+    // start of the current statement, None until its first token
+    let mut start: Option<usize> = None;
+    let mut words: Vec<String> = Vec::new();
+    // depth of the BEGIN/CASE ... END blocks of a trigger
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if start.is_none() && dialect == Dialect::MySql && at_line_start(bytes, i) {
+            if let Some((new_delimiter, end)) = delimiter_command(script, i) {
+                delimiter = new_delimiter;
+                i = end;
+                continue;
+            }
+        }
+
+        if let Some(end) = skip_quoted(dialect, script, i) {
+            // a comment doesn't start a statement but anything quoted does
+            if !matches!(bytes[i], b'-' | b'/' | b'#') {
+                start.get_or_insert(i);
+            }
+            i = end;
+            continue;
+        }
+
+        if script[i..].starts_with(delimiter.as_str()) && depth == 0 {
+            if let Some(from) = start.take() {
+                statements.push(statement(script, from, i));
+            }
+            words.clear();
+            i += delimiter.len();
+            continue;
+        }
+
+        if bytes[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        start.get_or_insert(i);
+        if is_identifier_start(bytes[i]) && (i == 0 || !is_identifier_part(bytes[i - 1])) {
+            let len = bytes[i..].iter().position(|b| !is_identifier_part(*b)).unwrap_or(bytes.len() - i);
+            let word = script[i..i + len].to_ascii_uppercase();
+            if dialect == Dialect::Sqlite && is_trigger(&words) {
+                match word.as_str() {
+                    "BEGIN" | "CASE" => depth += 1,
+                    "END" if depth > 0 => depth -= 1,
+                    _ => {},
+                }
+            }
+            if words.len() < 3 {
+                words.push(word);
+            }
+            i += len;
+            continue;
+        }
+        i += 1;
+    }
+
+    let unterminated = start.map(|from| statement(script, from, bytes.len()));
+    (statements, unterminated)
+}
+
+fn statement(script: &str, from: usize, to: usize) -> ScriptStatement {
+    ScriptStatement {
+        sql: script[from..to].trim_end().to_string(),
+        line: script[..from].matches('\n').count() + 1,
+    }
+}
+
+fn at_line_start(bytes: &[u8], i: usize) -> bool {
+    bytes[..i].iter().rev().take_while(|b| **b != b'\n').all(u8::is_ascii_whitespace)
+}
+
+/// The delimiter set by a mysql client `DELIMITER` command at `from` and the end of its line
+fn delimiter_command(script: &str, from: usize) -> Option<(String, usize)> {
+    let end = script[from..].find('\n').map_or(script.len(), |end| from + end);
+    let mut parts = script[from..end].split_whitespace();
+    let command = parts.next()?;
+    let delimiter = parts.next()?;
+    match command.eq_ignore_ascii_case("DELIMITER") {
+        true => Some((delimiter.to_string(), end)),
+        false => None,
+    }
+}
+
+/// Whether the statement starting with `words` is `CREATE [TEMP | TEMPORARY] TRIGGER`
+fn is_trigger(words: &[String]) -> bool {
+    match words {
+        [create, trigger, ..] if create == "CREATE" && trigger == "TRIGGER" => true,
+        [create, temp, trigger] => create == "CREATE" && (temp == "TEMP" || temp == "TEMPORARY") && trigger == "TRIGGER",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::script::{is_complete, split, ScriptStatement};
+    use crate::sql::Dialect;
+
+    fn sql(statements: &[ScriptStatement]) -> Vec<&str> {
+        statements.iter().map(|s| s.sql.as_str()).collect()
+    }
+
+    #[test]
+    fn statements_should_be_split_at_semicolons_outside_quoted_text() {
+        let script = "-- seed\nINSERT INTO t VALUES ('a;b');\n\n/* ; */ SELECT \";\" -- ;\n;  ";
+        let statements = split(script, Dialect::Generic);
+        assert_eq!(vec!["INSERT INTO t VALUES ('a;b')", "SELECT \";\" -- ;"], sql(&statements));
+        assert_eq!(vec![2, 4], statements.iter().map(|s| s.line).collect::<Vec<_>>());
+
+        assert_eq!(vec!["SELECT 1", "SELECT 2"], sql(&split("SELECT 1;SELECT 2", Dialect::Generic)));
+        assert!(split("-- nothing ;\n", Dialect::Generic).is_empty());
+    }
+
+    #[test]
+    fn dollar_quoted_bodies_should_not_be_split() {
+        let script = "CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql;\nSELECT f();";
+        let statements = split(script, Dialect::Postgres);
+        assert_eq!(vec!["CREATE FUNCTION f() RETURNS int AS $$ SELECT 1; $$ LANGUAGE sql", "SELECT f()"], sql(&statements));
+        assert_eq!(2, statements[1].line);
+    }
+
+    #[test]
+    fn mysql_delimiter_should_be_changed() {
+        let script = "DELIMITER //\nCREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\nEND//\ndelimiter ;\nCALL p();";
+        let statements = split(script, Dialect::MySql);
+        assert_eq!(vec!["CREATE PROCEDURE p()\nBEGIN\n  SELECT 1;\nEND", "CALL p()"], sql(&statements));
+        assert_eq!(vec![2, 7], statements.iter().map(|s| s.line).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sqlite_trigger_bodies_should_not_be_split() {
+        let script = "CREATE TEMP TRIGGER t AFTER INSERT ON a BEGIN\n  UPDATE b SET n = CASE WHEN n > 0 THEN n END;\n  DELETE FROM c;\nEND;\nBEGIN;\nCOMMIT;";
+        let statements = split(script, Dialect::Sqlite);
+        assert_eq!(3, statements.len());
+        assert!(statements[0].sql.ends_with("DELETE FROM c;\nEND"));
+        assert_eq!(vec!["BEGIN", "COMMIT"], sql(&statements[1..]));
+    }
+
+    #[test]
+    fn statements_should_be_complete_once_delimited() {
+        assert!(is_complete("", Dialect::Generic));
+        assert!(is_complete("SELECT 1;\n-- done", Dialect::Generic));
+        assert!(!is_complete("SELECT 1;\nSELECT", Dialect::Generic));
+        assert!(!is_complete("SELECT ';", Dialect::Generic));
+        assert!(!is_complete("CREATE TRIGGER t AFTER INSERT ON a BEGIN\n  DELETE FROM b;", Dialect::Sqlite));
+        assert!(is_complete("CREATE TRIGGER t AFTER INSERT ON a BEGIN\n  DELETE FROM b;\nEND;", Dialect::Sqlite));
+    }
+}
@@ -0,0 +1,275 @@
+//! Lexical rules of the SQL dialects spoken by the drivers.
+//!
+//! Only as much of the SQL text is understood as is needed to find the parts of a statement,
+//! such as parameters, which can't appear within string literals, quoted identifiers, comments
+//! and dollar-quoted bodies.
+
+use std::ops::Range;
+
+/// The SQL dialect of a database
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// Standard SQL with `?` placeholders
+    Generic,
+    /// PostgreSQL with `$1` placeholders, dollar-quoted bodies and nested block comments
+    Postgres,
+    /// MySQL with `?` placeholders, backslash escapes, backtick identifiers and `#` comments
+    MySql,
+    /// SQLite with `?` placeholders and backtick and bracket identifiers
+    Sqlite,
+}
+
+impl Dialect {
+    /// The native placeholder of the parameter at `index`, starting at 0
+    pub fn placeholder(&self, index: usize) -> String {
+        match self {
+            Dialect::Postgres => format!("${}", index + 1),
+            _ => "?".to_string(),
+        }
+    }
+
+    /// Whether the native placeholder of the parameter at each position is unique, e.g. `$1`,
+    /// so a value bound once can be referenced several times
+    pub fn has_numbered_placeholders(&self) -> bool {
+        matches!(self, Dialect::Postgres)
+    }
+
+    /// `name` as a quoted identifier, with backticks for MySQL and double quotes otherwise, so
+    /// it keeps its case and may be a keyword
+    pub fn quote_identifier(&self, name: &str) -> String {
+        match self {
+            Dialect::MySql => format!("`{}`", name.replace('`', "``")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+}
+
+pub(crate) fn is_identifier_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b >= 0x80
+}
+
+pub(crate) fn is_identifier_part(b: u8) -> bool {
+    is_identifier_start(b) || b.is_ascii_digit() || b == b'$'
+}
+
+fn line_end(bytes: &[u8], from: usize) -> usize {
+    bytes[from..].iter().position(|b| *b == b'\n').map_or(bytes.len(), |end| from + end)
+}
+
+/// The end of the quoted text starting with the `quote` at `from`. A doubled quote stands for
+/// itself and, when `escapes` is true, so does any byte following a backslash.
+fn quoted_end(bytes: &[u8], from: usize, quote: u8, escapes: bool) -> usize {
+    let mut i = from + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if escapes => i += 1,
+            b if b == quote => match bytes.get(i + 1) {
+                Some(next) if *next == quote => i += 1,
+                _ => return i + 1,
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn block_comment_end(bytes: &[u8], from: usize, nested: bool) -> usize {
+    let mut depth = 0;
+    let mut i = from;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"/*") && (nested || depth == 0) {
+            depth += 1;
+            i += 2;
+        } else if bytes[i..].starts_with(b"*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// The tag, including both `$`, of a dollar quote starting at `from`, e.g. `$$` or `$body$`
+pub(crate) fn dollar_quote_tag(bytes: &[u8], from: usize) -> Option<&[u8]> {
+    if from > 0 && is_identifier_part(bytes[from - 1]) {
+        return None;
+    }
+    let rest = &bytes[from + 1..];
+    let len = rest.iter().position(|b| *b == b'$')?;
+    let tag = &rest[..len];
+    match tag.first() {
+        None => Some(&bytes[from..from + 2]),
+        Some(first) if is_identifier_start(*first) && tag.iter().all(|b| *b != b'$' && is_identifier_part(*b)) => {
+            Some(&bytes[from..from + len + 2])
+        },
+        _ => None,
+    }
+}
+
+/// A native placeholder found by [placeholders]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    /// The byte range of the placeholder in the SQL
+    pub range: Range<usize>,
+    /// The index of the parameter it stands for, starting at 0
+    pub index: usize,
+}
+
+/// The native placeholders of `dialect` in `sql`, e.g. `?` or `$1`, outside of string literals,
+/// quoted identifiers, comments and dollar-quoted bodies
+pub fn placeholders(dialect: Dialect, sql: &str) -> Vec<Placeholder> {
+    let bytes = sql.as_bytes();
+    let mut placeholders = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = skip_quoted(dialect, sql, i) {
+            i = end;
+            continue;
+        }
+        match bytes[i] {
+            b'$' if dialect.has_numbered_placeholders() && (i == 0 || !is_identifier_part(bytes[i - 1])) => {
+                let digits = bytes[i + 1..].iter().take_while(|b| b.is_ascii_digit()).count();
+                if let Some(number) = sql[i + 1..i + 1 + digits].parse::<usize>().ok().filter(|n| *n > 0) {
+                    placeholders.push(Placeholder { range: i..i + 1 + digits, index: number - 1 });
+                }
+                i += 1 + digits;
+            },
+            b'?' if !dialect.has_numbered_placeholders() => {
+                placeholders.push(Placeholder { range: i..i + 1, index: placeholders.len() });
+                i += 1;
+            },
+            _ => i += 1,
+        }
+    }
+    placeholders
+}
+
+/// The keywords and unquoted identifiers of `sql` in order, outside of string literals, quoted
+/// identifiers, comments and dollar-quoted bodies
+pub fn words(dialect: Dialect, sql: &str) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut words = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(end) = skip_quoted(dialect, sql, i) {
+            i = end;
+            continue;
+        }
+        match is_identifier_start(bytes[i]) {
+            true => {
+                let len = bytes[i..].iter().take_while(|b| is_identifier_part(**b)).count();
+                words.push(&sql[i..i + len]);
+                i += len;
+            },
+            false => i += 1,
+        }
+    }
+    words
+}
+
+/// If a string literal, quoted identifier, comment or dollar-quoted body starts at `from` then
+/// returns the index just past its end, or the end of `sql` when it isn't terminated.
+pub(crate) fn skip_quoted(dialect: Dialect, sql: &str, from: usize) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let end = match bytes[from] {
+        b'\'' => {
+            // E'...' strings accept backslash escapes in postgres
+            let escape_string = dialect == Dialect::Postgres
+                && from > 0
+                && bytes[from - 1].eq_ignore_ascii_case(&b'e')
+                && (from == 1 || !is_identifier_part(bytes[from - 2]));
+            quoted_end(bytes, from, b'\'', dialect == Dialect::MySql || escape_string)
+        },
+        b'"' => quoted_end(bytes, from, b'"', dialect == Dialect::MySql),
+        b'`' if matches!(dialect, Dialect::MySql | Dialect::Sqlite) => quoted_end(bytes, from, b'`', false),
+        b'[' if dialect == Dialect::Sqlite => bytes[from..].iter().position(|b| *b == b']').map_or(bytes.len(), |end| from + end + 1),
+        b'#' if dialect == Dialect::MySql => line_end(bytes, from),
+        b'-' if bytes[from..].starts_with(b"--") => {
+            // mysql only treats -- as a comment when it's followed by whitespace
+            match (dialect, bytes.get(from + 2)) {
+                (Dialect::MySql, Some(b)) if !b.is_ascii_whitespace() => return None,
+                _ => line_end(bytes, from),
+            }
+        },
+        b'/' if bytes[from..].starts_with(b"/*") => block_comment_end(bytes, from, dialect == Dialect::Postgres),
+        b'$' if dialect == Dialect::Postgres => {
+            let tag = dollar_quote_tag(bytes, from)?;
+            let body = from + tag.len();
+            sql[body..].find(std::str::from_utf8(tag).ok()?).map_or(bytes.len(), |end| body + end + tag.len())
+        },
+        _ => return None,
+    };
+    Some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql::{placeholders, skip_quoted, words, Dialect};
+
+    #[test]
+    fn quoted_text_should_be_skipped() {
+        assert_eq!(Some(7), skip_quoted(Dialect::Generic, "'it''s' x", 0));
+        assert_eq!(Some(6), skip_quoted(Dialect::MySql, "'a\\'b' x", 0));
+        assert_eq!(Some(4), skip_quoted(Dialect::Postgres, "'a\\' 'x", 0));
+        assert_eq!(Some(7), skip_quoted(Dialect::Postgres, "E'a\\'b' x", 1));
+        assert_eq!(Some(5), skip_quoted(Dialect::Sqlite, "[a b] x", 0));
+        assert_eq!(None, skip_quoted(Dialect::Postgres, "[a b] x", 0));
+        assert_eq!(Some(4), skip_quoted(Dialect::Generic, "'abc", 0));
+    }
+
+    #[test]
+    fn placeholders_should_be_found_outside_quoted_text() {
+        let sql = "CALL p('?', \"\\\"?\", `?`, ?) -- ?\n# ?\n/* ? */";
+        let found = placeholders(Dialect::MySql, sql);
+        assert_eq!(1, found.len());
+        assert_eq!(sql.find("?)").unwrap(), found[0].range.start);
+        assert!(placeholders(Dialect::MySql, "SELECT 'it''s ?'").is_empty());
+
+        let indexes: Vec<usize> = placeholders(Dialect::Sqlite, "VALUES (?, ?)").into_iter().map(|p| p.index).collect();
+        assert_eq!(vec![0, 1], indexes);
+
+        let sql = "SELECT $2, a$1, '$1', $$ $1 $$, $10";
+        let found: Vec<(&str, usize)> = placeholders(Dialect::Postgres, sql).into_iter().map(|p| (&sql[p.range], p.index)).collect();
+        assert_eq!(vec![("$2", 1), ("$10", 9)], found);
+    }
+
+    #[test]
+    fn words_should_be_found_outside_quoted_text() {
+        let sql = "/* SELECT */ INSERT INTO \"returning\" VALUES (x'01', 'returning', E'a\\'b') -- returning";
+        assert_eq!(vec!["INSERT", "INTO", "VALUES", "x", "E"], words(Dialect::Postgres, sql));
+        assert_eq!(vec!["a$b", "SELECT"], words(Dialect::Postgres, "a$b $$ returning $$ SELECT"));
+    }
+
+    #[test]
+    fn identifiers_should_be_quoted() {
+        assert_eq!("\"Order \"\"1\"\"\"", Dialect::Postgres.quote_identifier("Order \"1\""));
+        assert_eq!("`a``b`", Dialect::MySql.quote_identifier("a`b"));
+    }
+
+    #[test]
+    fn comments_should_be_skipped() {
+        assert_eq!(Some(4), skip_quoted(Dialect::Generic, "-- a\nx", 0));
+        assert_eq!(None, skip_quoted(Dialect::MySql, "--a\nx", 0));
+        assert_eq!(Some(3), skip_quoted(Dialect::MySql, "# a\nx", 0));
+        assert_eq!(None, skip_quoted(Dialect::Postgres, "# a\nx", 0));
+        assert_eq!(Some(13), skip_quoted(Dialect::Postgres, "/* /* a */ */ x", 0));
+        assert_eq!(Some(10), skip_quoted(Dialect::MySql, "/* /* a */ */ x", 0));
+    }
+
+    #[test]
+    fn dollar_quoted_bodies_should_be_skipped() {
+        let sql = "$$ it's $1 $$ x";
+        assert_eq!(Some(13), skip_quoted(Dialect::Postgres, sql, 0));
+        let sql = "$fn$ $$ $fn$ x";
+        assert_eq!(Some(12), skip_quoted(Dialect::Postgres, sql, 0));
+        assert_eq!(None, skip_quoted(Dialect::Postgres, "$1 + $2", 0));
+        assert_eq!(None, skip_quoted(Dialect::Postgres, "a$b$ x", 1));
+        assert_eq!(None, skip_quoted(Dialect::MySql, "$$ x $$", 0));
+    }
+}
@@ -0,0 +1,99 @@
+//! Fixtures shared by the tests of this crate
+
+use crate::error::RsdbcErrors;
+use crate::{Result, ResultSet, ResultSetMetaData, Value};
+
+/// A [ResultSet] over rows of values held in memory, whose getters return the value of the
+/// column when it is of the requested type and an error otherwise.
+pub(crate) struct VecResultSet {
+    rows: Vec<Vec<Option<Value>>>,
+    position: Option<usize>,
+}
+
+impl VecResultSet {
+    pub(crate) fn new(rows: Vec<Vec<Option<Value>>>) -> Self {
+        Self { rows, position: None }
+    }
+
+    /// A result set with a single column of 32-bit integers
+    pub(crate) fn of_i32(values: Vec<i32>) -> Self {
+        Self::new(values.into_iter().map(|v| vec![Some(Value::Int32(v))]).collect())
+    }
+
+    /// The number of rows read with [ResultSet::next]
+    pub(crate) fn rows_read(&self) -> usize {
+        self.position.map_or(0, |p| p + 1)
+    }
+
+    fn get<T>(&self, i: u64, f: impl FnOnce(&Value) -> Option<T>) -> Result<Option<T>> {
+        let row = self
+            .position
+            .map(|p| &self.rows[p])
+            .ok_or_else(|| RsdbcErrors::General("no current row".to_string()))?;
+        let value = row
+            .get(i as usize)
+            .ok_or(RsdbcErrors::IndexOutOfBounds { index: i, length: row.len() as u64 })?;
+        match value {
+            Some(v) => f(v).map(Some).ok_or_else(|| RsdbcErrors::IllegalArgument(format!("column {} is {:?}", i, v))),
+            None => Ok(None),
+        }
+    }
+}
+
+impl ResultSet for VecResultSet {
+    fn meta_data(&self) -> Result<Box<dyn ResultSetMetaData>> {
+        Err(RsdbcErrors::Unsupported("metadata of rows in memory".to_string()))
+    }
+
+    fn next(&mut self) -> bool {
+        let next = self.position.map_or(0, |p| p + 1);
+        if next < self.rows.len() {
+            self.position = Some(next);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn get_bool(&self, i: u64) -> Result<Option<bool>> {
+        self.get(i, |v| match v { Value::Bool(v) => Some(*v), _ => None })
+    }
+
+    fn get_i8(&self, i: u64) -> Result<Option<i8>> {
+        self.get(i, |v| match v { Value::Int32(v) => i8::try_from(*v).ok(), _ => None })
+    }
+
+    fn get_i16(&self, i: u64) -> Result<Option<i16>> {
+        self.get(i, |v| match v { Value::Int32(v) => i16::try_from(*v).ok(), _ => None })
+    }
+
+    fn get_i32(&self, i: u64) -> Result<Option<i32>> {
+        self.get(i, |v| match v { Value::Int32(v) => Some(*v), _ => None })
+    }
+
+    fn get_i64(&self, i: u64) -> Result<Option<i64>> {
+        self.get(i, |v| match v { Value::Int32(v) => Some(i64::from(*v)), Value::Int64(v) => Some(*v), _ => None })
+    }
+
+    fn get_f32(&self, i: u64) -> Result<Option<f32>> {
+        self.get(i, |v| match v { Value::Double(v) => Some(*v as f32), _ => None })
+    }
+
+    fn get_f64(&self, i: u64) -> Result<Option<f64>> {
+        self.get(i, |v| match v { Value::Double(v) => Some(*v), _ => None })
+    }
+
+    fn get_string(&self, i: u64) -> Result<Option<String>> {
+        self.get(i, |v| match v { Value::String(v) => Some(v.clone()), _ => None })
+    }
+
+    fn get_bytes(&self, i: u64) -> Result<Option<Vec<u8>>> {
+        self.get(i, |v| match v {
+            Value::Bytes(v) => Some(v.clone()),
+            Value::String(v) => Some(v.clone().into_bytes()),
+            #[cfg(feature = "json")]
+            Value::Json(v) => Some(v.to_string().into_bytes()),
+            _ => None,
+        })
+    }
+}
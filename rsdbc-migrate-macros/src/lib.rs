@@ -0,0 +1,57 @@
+//! Macros for [rsdbc-migrate](https://docs.rs/rsdbc-migrate), use them through that crate.
+
+use std::fs;
+use std::path::PathBuf;
+use proc_macro::TokenStream;
+
+/// Embeds the `V{version}__{description}.sql` files of a directory, relative to the crate's
+/// `Cargo.toml`, and expands to a `Result<Migrator, MigrateError>` created from them.
+///
+/// The directory is read when the crate is compiled so adding a file requires a rebuild, which
+/// `cargo` doesn't detect by itself unless a build script watches the directory.
+///
+/// ```ignore
+/// let migrator = rsdbc_migrate::embed_migrations!("migrations")?;
+/// ```
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> TokenStream {
+    match embed(input) {
+        Ok(tokens) => tokens,
+        Err(message) => format!("compile_error!({:?})", message).parse().unwrap(),
+    }
+}
+
+fn embed(input: TokenStream) -> Result<TokenStream, String> {
+    let literal = input.to_string();
+    let dir = literal
+        .trim()
+        .strip_prefix('"')
+        .and_then(|l| l.strip_suffix('"'))
+        .ok_or("expected the migrations directory as a string literal")?;
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?;
+    let dir = PathBuf::from(manifest_dir).join(dir);
+    let entries = fs::read_dir(&dir).map_err(|e| format!("could not read {}: {}", dir.display(), e))?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let is_sql = matches!(path.extension(), Some(extension) if extension == "sql");
+        match path.file_name().and_then(|n| n.to_str()) {
+            // as with Migrator::from_dir other files are ignored
+            Some(name) if path.is_file() && is_sql && name.starts_with('V') => {
+                files.push((name.to_string(), path.display().to_string()));
+            },
+            _ => {},
+        }
+    }
+    files.sort();
+
+    let files: Vec<String> = files
+        .iter()
+        .map(|(name, path)| format!("({:?}, include_str!({:?}))", name, path))
+        .collect();
+    format!("::rsdbc_migrate::Migrator::embedded(&[{}])", files.join(", "))
+        .parse()
+        .map_err(|e| format!("{:?}", e))
+}
@@ -0,0 +1,34 @@
+use std::io;
+use thiserror::Error;
+use rsdbc_core::error::RsdbcErrors;
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum MigrateError {
+    #[error("Invalid migration file name `{0}`, expected V{{version}}__{{description}}.sql")]
+    InvalidFileName(String),
+
+    #[error("Migration version {0} is defined more than once")]
+    DuplicateVersion(u64),
+
+    /// The file of an applied migration was changed after it was applied
+    #[error("Checksum of applied migration {version} changed from {applied} to {local}")]
+    ChecksumMismatch { version: u64, applied: u32, local: u32 },
+
+    /// A migration was applied which isn't among the known migrations
+    #[error("Applied migration {0} is missing")]
+    MissingMigration(u64),
+
+    #[error("Migration lock `{0}` could not be acquired")]
+    LockNotAcquired(String),
+
+    /// A migration failed and was rolled back where the dialect allows
+    #[error("Migration {version} failed: {source}")]
+    Failed { version: u64, source: RsdbcErrors },
+
+    #[error("IO error: `{0}`")]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Rsdbc(#[from] RsdbcErrors),
+}
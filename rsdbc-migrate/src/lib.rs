@@ -0,0 +1,117 @@
+//! Versioned schema migrations.
+//!
+//! Migrations are SQL scripts named `V{version}__{description}.sql`, e.g.
+//! `V1__create_person_table.sql`, which are read from a directory at runtime or embedded at
+//! compile time with [embed_migrations]. They are applied in version order by a [Migrator] which
+//! records each one along with a checksum of its SQL in a history table.
+//!
+//! ```ignore
+//! let migrator = rsdbc_migrate::embed_migrations!("migrations")?;
+//! let applied = migrator.migrate(connection.as_mut())?;
+//! ```
+
+// lets embed_migrations! refer to ::rsdbc_migrate within this crate's own tests
+extern crate self as rsdbc_migrate;
+
+pub mod error;
+mod migration;
+mod migrator;
+
+pub use rsdbc_migrate_macros::embed_migrations;
+pub use crate::error::MigrateError;
+pub use crate::migration::Migration;
+pub use crate::migrator::{AppliedMigration, Migrator, DEFAULT_TABLE};
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use futures::executor::block_on;
+    use rsdbc_core::connection::{Connection, ConnectionFactory};
+    use rsdbc_core::error::RsdbcErrors;
+    use rsdbc_sqlite::options::SqliteConnectOptions;
+    use crate::{AppliedMigration, MigrateError, Migration, Migrator};
+
+    fn connect() -> Box<dyn Connection> {
+        block_on(SqliteConnectOptions::new().connect()).unwrap()
+    }
+
+    fn count(connection: &mut dyn Connection, sql: &str) -> i64 {
+        let mut stmt = connection.create_statement(sql).unwrap();
+        let mut rs = stmt.execute_query(&[]).unwrap();
+        assert!(rs.next());
+        rs.get_i64(0).unwrap().unwrap()
+    }
+
+    #[test]
+    fn embedded_migrations_should_be_applied_once() -> Result<(), MigrateError> {
+        let migrator = crate::embed_migrations!("tests/migrations")?;
+        assert_eq!(2, migrator.migrations().len());
+
+        let mut connection = connect();
+        assert_eq!(vec![1, 2], migrator.migrate(connection.as_mut())?);
+        assert!(migrator.migrate(connection.as_mut())?.is_empty());
+        assert_eq!(1, count(connection.as_mut(), "SELECT COUNT(*) FROM person WHERE email IS NOT NULL"));
+
+        let applied = migrator.applied(connection.as_mut())?;
+        assert_eq!(
+            AppliedMigration { version: 2, description: "add email".to_string(), checksum: migrator.migrations()[1].checksum() },
+            applied[1]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn migrations_should_be_read_from_a_directory() -> Result<(), MigrateError> {
+        let dir = std::env::temp_dir().join(format!("rsdbc-migrate-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("V2__second.sql"), "SELECT 2;")?;
+        fs::write(dir.join("V10__tenth.sql"), "SELECT 10;")?;
+        fs::write(dir.join("notes.txt"), "ignored")?;
+        let migrator = Migrator::from_dir(&dir);
+        fs::remove_dir_all(&dir)?;
+
+        let versions: Vec<u64> = migrator?.migrations().iter().map(Migration::version).collect();
+        assert_eq!(vec![2, 10], versions);
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_versions_should_be_rejected() {
+        let migrations = vec![Migration::new(1, "a", "SELECT 1"), Migration::new(1, "b", "SELECT 2")];
+        assert!(matches!(Migrator::new(migrations), Err(MigrateError::DuplicateVersion(1))));
+    }
+
+    #[test]
+    fn changed_and_missing_migrations_should_be_detected() -> Result<(), MigrateError> {
+        let mut connection = connect();
+        let migrator = Migrator::new(vec![Migration::new(1, "create a", "CREATE TABLE a (id INT);")])?;
+        migrator.migrate(connection.as_mut())?;
+
+        let changed = Migrator::new(vec![Migration::new(1, "create a", "CREATE TABLE a (id BIGINT);")])?;
+        assert!(matches!(changed.validate(connection.as_mut()), Err(MigrateError::ChecksumMismatch { version: 1, .. })));
+        assert!(matches!(changed.migrate(connection.as_mut()), Err(MigrateError::ChecksumMismatch { version: 1, .. })));
+
+        let missing = Migrator::new(Vec::new())?;
+        assert!(matches!(missing.validate(connection.as_mut()), Err(MigrateError::MissingMigration(1))));
+        Ok(())
+    }
+
+    #[test]
+    fn failed_migrations_should_be_rolled_back() -> Result<(), MigrateError> {
+        let mut connection = connect();
+        let create_a = Migration::new(1, "create a", "CREATE TABLE a (id INT);");
+        let failing = Migrator::new(vec![
+            create_a.clone(),
+            Migration::new(2, "create b", "CREATE TABLE b (id INT);\nINSERT INTO missing VALUES (1);"),
+        ])?.with_table("history");
+
+        let err = failing.migrate(connection.as_mut()).unwrap_err();
+        assert!(matches!(err, MigrateError::Failed { version: 2, source: RsdbcErrors::Script { line: 2, .. } }));
+        assert_eq!(1, failing.applied(connection.as_mut())?.len());
+        assert!(connection.create_statement("SELECT * FROM b").is_err());
+
+        let fixed = Migrator::new(vec![create_a, Migration::new(2, "create b", "CREATE TABLE b (id INT);")])?.with_table("history");
+        assert_eq!(vec![2], fixed.migrate(connection.as_mut())?);
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::path::Path;
+use crate::error::MigrateError;
+
+/// A versioned migration script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    version: u64,
+    description: String,
+    sql: String,
+    checksum: u32,
+}
+
+impl Migration {
+    pub fn new(version: u64, description: impl Into<String>, sql: impl Into<String>) -> Self {
+        let sql = sql.into();
+        Self {
+            version,
+            description: description.into(),
+            checksum: checksum(&sql),
+            sql,
+        }
+    }
+
+    /// Create a migration from a file named `V{version}__{description}.sql`. Underscores in the
+    /// description are replaced by spaces.
+    pub fn from_file_name(file_name: &str, sql: impl Into<String>) -> Result<Self, MigrateError> {
+        let invalid = || MigrateError::InvalidFileName(file_name.to_string());
+        let (version, description) = file_name
+            .strip_prefix('V')
+            .and_then(|name| name.strip_suffix(".sql"))
+            .and_then(|name| name.split_once("__"))
+            .ok_or_else(invalid)?;
+        let version = version.parse().map_err(|_| invalid())?;
+        if description.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self::new(version, description.replace('_', " "), sql))
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// CRC32 of the SQL with its line endings normalised so a checkout on another platform
+    /// doesn't change it
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+}
+
+/// Read the `V{version}__{description}.sql` files of `dir`. Other files are ignored.
+pub(crate) fn read_dir(dir: &Path) -> Result<Vec<Migration>, MigrateError> {
+    let mut migrations = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if path.is_file() && name.starts_with('V') && name.ends_with(".sql") => name.to_string(),
+            _ => continue,
+        };
+        migrations.push(Migration::from_file_name(&file_name, fs::read_to_string(&path)?)?);
+    }
+    Ok(migrations)
+}
+
+fn checksum(sql: &str) -> u32 {
+    let mut crc = !0u32;
+    for byte in sql.replace("\r\n", "\n").bytes() {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::MigrateError;
+    use crate::migration::{checksum, Migration};
+
+    #[test]
+    fn migrations_should_be_parsed_from_file_names() {
+        let migration = Migration::from_file_name("V12__create_person_table.sql", "CREATE TABLE person (id INT)").unwrap();
+        assert_eq!(12, migration.version());
+        assert_eq!("create person table", migration.description());
+
+        for name in ["V1_create.sql", "V__create.sql", "Vx__create.sql", "V1__.sql", "V1__create.txt"] {
+            assert!(matches!(Migration::from_file_name(name, ""), Err(MigrateError::InvalidFileName(_))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn checksums_should_ignore_line_endings() {
+        assert_eq!(0xCBF4_3926, checksum("123456789"));
+        assert_eq!(checksum("SELECT 1;\nSELECT 2;"), checksum("SELECT 1;\r\nSELECT 2;"));
+        assert_ne!(checksum("SELECT 1;"), checksum("SELECT 2;"));
+    }
+}
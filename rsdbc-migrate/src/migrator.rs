@@ -0,0 +1,233 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+use rsdbc_core::connection::Connection;
+use rsdbc_core::{Dialect, Value};
+use crate::error::MigrateError;
+use crate::migration::{read_dir, Migration};
+
+/// The default name of the table recording the applied migrations
+pub const DEFAULT_TABLE: &str = "rsdbc_schema_history";
+
+// sqlite applies each migration within a savepoint of the exclusive transaction holding the lock
+const SAVEPOINT: &str = "rsdbc_migration";
+
+/// A migration recorded in the history table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: u64,
+    pub description: String,
+    pub checksum: u32,
+}
+
+/// Applies pending migrations in version order and records them in a history table.
+///
+/// Concurrent runs are serialised with a lock: `pg_advisory_lock` for postgres, `GET_LOCK` for
+/// mysql and an exclusive transaction for sqlite. Each migration is applied within a transaction
+/// and rolled back if it fails, apart from mysql DDL which commits implicitly. Migrations which
+/// have already been applied are checked for changes to their checksum before anything is applied.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    table: String,
+}
+
+impl Migrator {
+    /// returns [MigrateError::DuplicateVersion] if two migrations have the same version
+    pub fn new(mut migrations: Vec<Migration>) -> Result<Self, MigrateError> {
+        migrations.sort_by_key(Migration::version);
+        if let Some(pair) = migrations.windows(2).find(|pair| pair[0].version() == pair[1].version()) {
+            return Err(MigrateError::DuplicateVersion(pair[0].version()));
+        }
+        Ok(Self {
+            migrations,
+            table: DEFAULT_TABLE.to_string(),
+        })
+    }
+
+    /// Read the migrations from the `V{version}__{description}.sql` files of `dir`
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, MigrateError> {
+        Self::new(read_dir(dir.as_ref())?)
+    }
+
+    /// Create the migrations from `(file name, SQL)` pairs, usually produced by
+    /// [embed_migrations](crate::embed_migrations)
+    pub fn embedded(files: &[(&str, &str)]) -> Result<Self, MigrateError> {
+        let migrations = files
+            .iter()
+            .map(|(file_name, sql)| Migration::from_file_name(file_name, *sql))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(migrations)
+    }
+
+    /// Sets the name of the history table, [DEFAULT_TABLE] by default
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.table = table.to_string();
+        self
+    }
+
+    pub fn migrations(&self) -> &[Migration] {
+        &self.migrations
+    }
+
+    /// The migrations recorded in the history table ordered by version. The table is created
+    /// if it doesn't exist.
+    pub fn applied(&self, connection: &mut dyn Connection) -> Result<Vec<AppliedMigration>, MigrateError> {
+        self.create_table(connection)?;
+
+        let sql = format!("SELECT version, description, checksum FROM {} ORDER BY version", self.table);
+        let mut stmt = connection.create_statement(&sql)?;
+        let mut rs = stmt.execute_query(&[])?;
+        let mut applied = Vec::new();
+        while rs.next() {
+            applied.push(AppliedMigration {
+                version: rs.get_i64(0)?.unwrap_or_default() as u64,
+                description: rs.get_string(1)?.unwrap_or_default(),
+                checksum: rs.get_i64(2)?.unwrap_or_default() as u32,
+            });
+        }
+        Ok(applied)
+    }
+
+    /// Checks that every applied migration is known and unchanged
+    ///
+    /// returns [MigrateError::MissingMigration] or [MigrateError::ChecksumMismatch] otherwise
+    pub fn validate(&self, connection: &mut dyn Connection) -> Result<(), MigrateError> {
+        let applied = self.applied(connection)?;
+        self.check(&applied)
+    }
+
+    /// Applies the migrations which haven't been applied yet, returning their versions
+    pub fn migrate(&self, connection: &mut dyn Connection) -> Result<Vec<u64>, MigrateError> {
+        let dialect = connection.dialect();
+        self.lock(connection, dialect)?;
+        let result = self.migrate_locked(connection, dialect);
+        let unlocked = self.unlock(connection, dialect);
+        let applied = result?;
+        unlocked?;
+        Ok(applied)
+    }
+
+    fn migrate_locked(&self, connection: &mut dyn Connection, dialect: Dialect) -> Result<Vec<u64>, MigrateError> {
+        let applied = self.applied(connection)?;
+        self.check(&applied)?;
+
+        let pending = self.migrations.iter().filter(|m| !applied.iter().any(|a| a.version == m.version()));
+        let mut versions = Vec::new();
+        for migration in pending {
+            self.apply(connection, dialect, migration)?;
+            versions.push(migration.version());
+        }
+        Ok(versions)
+    }
+
+    fn check(&self, applied: &[AppliedMigration]) -> Result<(), MigrateError> {
+        for applied in applied {
+            let migration = self.migrations
+                .iter()
+                .find(|m| m.version() == applied.version)
+                .ok_or(MigrateError::MissingMigration(applied.version))?;
+            if migration.checksum() != applied.checksum {
+                return Err(MigrateError::ChecksumMismatch {
+                    version: applied.version,
+                    applied: applied.checksum,
+                    local: migration.checksum(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, connection: &mut dyn Connection, dialect: Dialect, migration: &Migration) -> Result<(), MigrateError> {
+        let savepoint = dialect == Dialect::Sqlite;
+        match savepoint {
+            true => connection.create_savepoint(SAVEPOINT)?,
+            false => connection.begin_transaction()?,
+        }
+
+        let start = Instant::now();
+        let result = connection
+            .execute_script(migration.sql())
+            .and_then(|_| self.record(connection, migration, start.elapsed()));
+        if let Err(e) = result {
+            // the migration's error is more useful than any from the rollback
+            let _ = match savepoint {
+                true => connection
+                    .rollback_transaction_to_savepoint(SAVEPOINT.to_string())
+                    .and_then(|_| connection.release_savepoint(SAVEPOINT)),
+                false => connection.rollback_transaction(),
+            };
+            return Err(MigrateError::Failed { version: migration.version(), source: e });
+        }
+
+        match savepoint {
+            true => connection.release_savepoint(SAVEPOINT)?,
+            false => connection.commit_transaction()?,
+        }
+        Ok(())
+    }
+
+    fn create_table(&self, connection: &mut dyn Connection) -> Result<(), MigrateError> {
+        let sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} (\
+                version BIGINT NOT NULL PRIMARY KEY, \
+                description VARCHAR(200) NOT NULL, \
+                checksum BIGINT NOT NULL, \
+                execution_time BIGINT NOT NULL, \
+                installed_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP)",
+            self.table
+        );
+        connection.execute_unprepared(&sql)?;
+        Ok(())
+    }
+
+    fn record(&self, connection: &mut dyn Connection, migration: &Migration, execution_time: Duration) -> rsdbc_core::Result<()> {
+        let sql = format!(
+            "INSERT INTO {} (version, description, checksum, execution_time) \
+            VALUES (:version, :description, :checksum, :execution_time)",
+            self.table
+        );
+        connection.create_named_statement(&sql)?.execute_update(&[
+            ("version", Value::Int64(migration.version() as i64)),
+            ("description", Value::String(migration.description().to_string())),
+            ("checksum", Value::Int64(i64::from(migration.checksum()))),
+            ("execution_time", Value::Int64(execution_time.as_millis() as i64)),
+        ])?;
+        Ok(())
+    }
+
+    /// The postgres advisory lock key, derived from the table name so migrators of different
+    /// history tables don't block each other
+    fn lock_key(&self) -> i64 {
+        i64::from(Migration::new(0, "", self.table.as_str()).checksum())
+    }
+
+    fn lock(&self, connection: &mut dyn Connection, dialect: Dialect) -> Result<(), MigrateError> {
+        match dialect {
+            Dialect::Postgres => connection.execute_unprepared(&format!("SELECT pg_advisory_lock({})", self.lock_key()))?,
+            Dialect::MySql => {
+                let mut stmt = connection.create_statement("SELECT GET_LOCK(?, -1)")?;
+                let mut rs = stmt.execute_query(&[Value::String(self.table.clone())])?;
+                // NULL or 0 if the lock couldn't be acquired
+                if !(rs.next() && rs.get_i64(0)? == Some(1)) {
+                    return Err(MigrateError::LockNotAcquired(self.table.clone()));
+                }
+            },
+            Dialect::Sqlite => connection.execute_unprepared("BEGIN EXCLUSIVE")?,
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn unlock(&self, connection: &mut dyn Connection, dialect: Dialect) -> Result<(), MigrateError> {
+        match dialect {
+            Dialect::Postgres => connection.execute_unprepared(&format!("SELECT pg_advisory_unlock({})", self.lock_key()))?,
+            Dialect::MySql => {
+                connection.create_statement("SELECT RELEASE_LOCK(?)")?.execute_query(&[Value::String(self.table.clone())])?;
+            },
+            // migrations which failed have already been rolled back to their savepoint
+            Dialect::Sqlite => connection.commit_transaction()?,
+            _ => {},
+        }
+        Ok(())
+    }
+}
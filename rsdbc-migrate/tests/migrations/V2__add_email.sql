@@ -0,0 +1,3 @@
+-- emails are optional for existing people
+ALTER TABLE person ADD COLUMN email VARCHAR(200);
+INSERT INTO person (name, email) VALUES ('Ada', 'ada@example.com');
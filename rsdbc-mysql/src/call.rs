@@ -0,0 +1,173 @@
+// https://dev.mysql.com/doc/refman/8.0/en/call.html
+//! Stored procedure calls with OUT and INOUT parameters.
+//!
+//! A procedure's OUT and INOUT arguments must be variables so their placeholders are replaced by
+//! session variables, `@rsdbc_out_{index}`, which are set to the INOUT values before the `CALL`
+//! and selected after it.
+
+use mysql::prelude::Queryable;
+use mysql::{Conn, Row};
+use rsdbc_core::call::CallParameters;
+use rsdbc_core::sql::placeholders;
+use rsdbc_core::{Column, Dialect, OutParameterValues, ParameterMode, Result, ResultSet, Segment, Value};
+use crate::metadata::to_column;
+use crate::statement::{to_params, MySqlResultSet, ValueEncoding};
+use crate::to_rsdbc_err_with_sql;
+
+fn variable(index: u32) -> String {
+    format!("@rsdbc_out_{}", index)
+}
+
+/// The schema, if given, and name of the procedure called by `sql`
+fn procedure_name(sql: &str) -> Option<(Option<String>, String)> {
+    let trimmed = sql.trim_start();
+    let rest = trimmed.get(..4).filter(|k| k.eq_ignore_ascii_case("CALL")).map(|_| &trimmed[4..])?;
+    let mut parts = name_parts(rest.trim_start().split('(').next()?);
+    let name = parts.pop().filter(|n| !n.is_empty())?;
+    Some((parts.pop(), name))
+}
+
+/// The unquoted parts of a qualified name, e.g. `` `shop`.add_tax ``
+fn name_parts(name: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = name.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut().expect("there is always a part");
+        match c {
+            '`' if quoted && chars.peek() == Some(&'`') => {
+                chars.next();
+                part.push('`');
+            },
+            '`' => quoted = !quoted,
+            '.' if !quoted => parts.push(String::new()),
+            c if quoted || !c.is_whitespace() => part.push(c),
+            _ => {},
+        }
+    }
+    parts
+}
+
+/// Names of the procedure's parameters in the order they are declared, looked up in the
+/// current database unless `schema` is given
+fn parameter_names(conn: &mut Conn, schema: Option<&str>, procedure: &str) -> Result<Vec<String>> {
+    let sql = "SELECT PARAMETER_NAME FROM information_schema.PARAMETERS \
+        WHERE SPECIFIC_SCHEMA = COALESCE(?, DATABASE()) AND SPECIFIC_NAME = ? AND ROUTINE_TYPE = 'PROCEDURE' AND ORDINAL_POSITION > 0 \
+        ORDER BY ORDINAL_POSITION";
+    conn.exec(sql, (schema, procedure)).map_err(|e| to_rsdbc_err_with_sql(e, sql))
+}
+
+pub(crate) fn execute_call<'a>(
+    conn: &mut Conn,
+    sql: &str,
+    call: &CallParameters,
+    encoding: ValueEncoding,
+    params: &[Value],
+) -> Result<Vec<Segment<'a>>> {
+    let positions: Vec<usize> = placeholders(Dialect::MySql, sql).into_iter().map(|p| p.range.start).collect();
+    let values = call.arrange(positions.len() as u32, params)?;
+
+    let mut rewritten = sql.to_string();
+    for (index, _, _) in call.outputs().collect::<Vec<_>>().into_iter().rev() {
+        let position = positions[index as usize];
+        rewritten.replace_range(position..position + 1, &variable(index));
+    }
+
+    for (index, mode, _) in call.outputs() {
+        let set = format!("SET {} = ?", variable(index));
+        let value = match mode {
+            ParameterMode::InOut => values[index as usize].cloned(),
+            _ => None,
+        };
+        let params = match value {
+            Some(value) => to_params(&[value], encoding)?,
+            None => mysql::Params::Positional(vec![mysql::Value::NULL]),
+        };
+        conn.exec_drop(set.as_str(), params).map_err(|e| to_rsdbc_err_with_sql(e, &set))?;
+    }
+
+    let in_values: Vec<Value> = values
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| call.mode(*i as u32) == ParameterMode::In)
+        .filter_map(|(_, v)| v.cloned())
+        .collect();
+    let mut segments = query_segments(conn, &rewritten, to_params(&in_values, encoding)?)?;
+
+    if !call.is_empty() {
+        segments.push(Segment::OutParameters(Box::new(out_parameters(conn, sql, call, positions.len())?)));
+    }
+    Ok(segments)
+}
+
+fn query_segments<'a>(conn: &mut Conn, sql: &str, params: mysql::Params) -> Result<Vec<Segment<'a>>> {
+    let mut segments = Vec::new();
+    {
+        let mut result = conn.exec_iter(sql, params).map_err(|e| to_rsdbc_err_with_sql(e, sql))?;
+        while let Some(set) = result.iter() {
+            let columns: Vec<Column> = set.columns().as_ref().iter().map(to_column).collect();
+            let affected_rows = set.affected_rows();
+            let rows = set.collect::<std::result::Result<Vec<Row>, _>>().map_err(|e| to_rsdbc_err_with_sql(e, sql))?;
+            let segment = match columns.is_empty() {
+                true => Segment::UpdateCount(affected_rows),
+                false => Segment::Rows(Box::new(MySqlResultSet::new(Box::new(rows.into_iter().map(Ok)), columns))),
+            };
+            segments.push(segment);
+        }
+    }
+    Ok(segments)
+}
+
+fn out_parameters(conn: &mut Conn, sql: &str, call: &CallParameters, placeholders: usize) -> Result<OutParameterValues> {
+    let outputs: Vec<_> = call.outputs().collect();
+    // the placeholders are taken to be the procedure's arguments when there are as many of them
+    let names = match procedure_name(sql) {
+        Some((schema, procedure)) => parameter_names(conn, schema.as_deref(), &procedure)?,
+        None => Vec::new(),
+    };
+    let names: Vec<String> = outputs
+        .iter()
+        .map(|(index, _, _)| match names.len() == placeholders {
+            true => names[*index as usize].clone(),
+            false => variable(*index),
+        })
+        .collect();
+
+    let select = format!(
+        "SELECT {}",
+        outputs.iter().map(|(index, _, _)| variable(*index)).collect::<Vec<_>>().join(", ")
+    );
+    let rows: Vec<Row> = conn.query(select.as_str()).map_err(|e| to_rsdbc_err_with_sql(e, &select))?;
+    let parameters: Vec<Column> = outputs
+        .iter()
+        .zip(&names)
+        .map(|((_, _, r#type), name)| Column::new(name, *r#type))
+        .collect();
+
+    let mut rs = MySqlResultSet::new(Box::new(rows.into_iter().map(Ok)), parameters.clone());
+    rs.next();
+    let rs: &dyn ResultSet = &rs;
+    let values = outputs
+        .iter()
+        .enumerate()
+        .map(|(i, (_, _, r#type))| rs.get_value(i as u64, *r#type))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(OutParameterValues::new(parameters, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::call::procedure_name;
+
+    #[test]
+    fn procedure_name_should_be_parsed() {
+        assert_eq!(Some((None, "add_tax".to_string())), procedure_name("  call add_tax(?, ?)"));
+        assert_eq!(
+            Some((Some("shop".to_string()), "add_tax".to_string())),
+            procedure_name("CALL `shop`.`add_tax` (?)")
+        );
+        assert_eq!(Some((Some("my.db".to_string()), "a`b".to_string())), procedure_name("CALL `my.db`.`a``b`()"));
+        assert_eq!(Some((None, "a.b".to_string())), procedure_name("CALL `a.b`"));
+        assert_eq!(None, procedure_name("SELECT 1"));
+    }
+}
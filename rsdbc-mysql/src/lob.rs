@@ -0,0 +1,241 @@
+//! Large values which are read and written in chunks.
+//!
+//! The mysql crate sends a bound value whole so a bound LOB is appended to a session variable a
+//! chunk at a time before the statement executes, and read by the statement from the variable.
+//! MySQL has no incremental reads so a value is read a chunk at a time with `SUBSTRING`.
+
+use std::io::{self, Read};
+use mysql::prelude::Queryable;
+use mysql::Conn;
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::lob::write_chunks;
+use rsdbc_core::sql::placeholders;
+use rsdbc_core::{Blob, Clob, Dialect, Lob, Result, Value};
+use crate::statement::{to_mysql_value, ValueEncoding};
+use crate::to_rsdbc_err_with_sql;
+
+fn quote_identifier(identifier: &str) -> String {
+    format!("`{}`", identifier.replace('`', "``"))
+}
+
+fn variable(index: usize) -> String {
+    format!("@rsdbc_lob_{}", index)
+}
+
+/// The LOBs bound to a statement, written to session variables whose names replace their
+/// placeholders, the way OUT parameters are passed to a procedure
+pub(crate) struct LobParameters {
+    /// The statement with the placeholders of the LOBs replaced
+    pub(crate) sql: String,
+    /// The values bound to the remaining placeholders
+    pub(crate) values: Vec<Value>,
+    variables: Vec<String>,
+}
+
+impl LobParameters {
+    /// Writes the LOBs among `params` to session variables, returns None if there are none
+    pub(crate) fn write(conn: &mut Conn, sql: &str, params: &[Value]) -> Result<Option<Self>> {
+        if !params.iter().any(|v| matches!(v, Value::Blob(_) | Value::Clob(_))) {
+            return Ok(None);
+        }
+        let positions = placeholders(Dialect::MySql, sql);
+        if positions.len() != params.len() {
+            return Err(RsdbcErrors::IllegalArgument(format!(
+                "{} values are bound to {} placeholders", params.len(), positions.len()
+            )));
+        }
+
+        let mut lobs = Self {
+            sql: sql.to_string(),
+            values: Vec::new(),
+            variables: Vec::new(),
+        };
+        let mut replacements = Vec::new();
+        for (index, value) in params.iter().enumerate() {
+            let (lob, replacement) = match value {
+                Value::Blob(lob) => (lob, variable(index)),
+                Value::Clob(lob) => (lob, format!("CONVERT({} USING utf8mb4)", variable(index))),
+                _ => {
+                    lobs.values.push(value.clone());
+                    continue;
+                },
+            };
+            lobs.variables.push(variable(index));
+            if let Err(e) = write_variable(conn, &variable(index), lob) {
+                // the error which stopped the write is more useful than one from clearing
+                let _ = lobs.clear(conn);
+                return Err(e);
+            }
+            replacements.push((positions[index].range.clone(), replacement));
+        }
+        for (range, replacement) in replacements.into_iter().rev() {
+            lobs.sql.replace_range(range, &replacement);
+        }
+        Ok(Some(lobs))
+    }
+
+    /// The session variables the LOBs are written to
+    pub(crate) fn variables(&self) -> &[String] {
+        &self.variables
+    }
+
+    /// Frees the session variables once the statement has executed
+    pub(crate) fn clear(&self, conn: &mut Conn) -> Result<()> {
+        clear_variables(conn, &self.variables)
+    }
+}
+
+pub(crate) fn clear_variables(conn: &mut Conn, variables: &[String]) -> Result<()> {
+    let sql = format!("SET {}", variables.iter().map(|v| format!("{} = NULL", v)).collect::<Vec<_>>().join(", "));
+    conn.query_drop(sql.as_str()).map_err(|e| to_rsdbc_err_with_sql(e, &sql))
+}
+
+fn write_variable(conn: &mut Conn, variable: &str, lob: &Lob) -> Result<()> {
+    // a binary string, so a chunk ending within a character is appended as it is
+    let set = format!("SET {} = X''", variable);
+    conn.query_drop(set.as_str()).map_err(|e| to_rsdbc_err_with_sql(e, &set))?;
+
+    let append = format!("SET {0} = CONCAT({0}, ?)", variable);
+    let statement = conn.prep(append.as_str()).map_err(|e| to_rsdbc_err_with_sql(e, &append))?;
+    let written = write_chunks(lob, |chunk| {
+        conn.exec_drop(&statement, (chunk,)).map_err(|e| to_rsdbc_err_with_sql(e, &append))
+    })?;
+
+    // CONCAT returns NULL rather than a value longer than max_allowed_packet
+    let len_sql = format!("SELECT LENGTH({})", variable);
+    let len = conn
+        .query_first::<Option<u64>, _>(len_sql.as_str())
+        .map_err(|e| to_rsdbc_err_with_sql(e, &len_sql))?
+        .flatten();
+    match len == Some(written) {
+        true => Ok(()),
+        false => Err(RsdbcErrors::IllegalArgument(format!(
+            "a large object of {} bytes is longer than max_allowed_packet", written
+        ))),
+    }
+}
+
+/// Reads `len` bytes of a value from a 1-based position, None if the row is gone
+type Substring<'a> = Box<dyn FnMut(u64, u64) -> Result<Option<Vec<u8>>> + 'a>;
+
+/// Reads the value of a column in a single row with a `SUBSTRING` per call
+pub(crate) struct ChunkReader<'a> {
+    substring: Substring<'a>,
+    // 1-based position of the next byte
+    position: u64,
+    len: u64,
+}
+
+impl<'a> ChunkReader<'a> {
+    /// Returns None if the value is NULL
+    pub(crate) fn open(
+        conn: &'a mut Conn,
+        encoding: ValueEncoding,
+        table: &str,
+        column: &str,
+        key_column: &str,
+        key: &Value,
+    ) -> Result<Option<Self>> {
+        // bytes rather than characters are counted once the value is cast to binary
+        let value = format!("CAST({} AS BINARY)", quote_identifier(column));
+        let filter = format!("FROM {} WHERE {} = ?", quote_identifier(table), quote_identifier(key_column));
+        let key = to_mysql_value(key, encoding)?;
+
+        let len_sql = format!("SELECT LENGTH({}) {}", value, filter);
+        let len: Option<u64> = conn
+            .exec_first::<Option<u64>, _, _>(len_sql.as_str(), (key.clone(),))
+            .map_err(|e| to_rsdbc_err_with_sql(e, &len_sql))?
+            .ok_or(RsdbcErrors::NoRows)?;
+        let len = match len {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+
+        let sql = format!("SELECT SUBSTRING({}, ?, ?) {}", value, filter);
+        let statement = conn.prep(sql.as_str()).map_err(|e| to_rsdbc_err_with_sql(e, &sql))?;
+        let substring = move |position: u64, n: u64| {
+            conn.exec_first::<Option<Vec<u8>>, _, _>(&statement, (position, n, key.clone()))
+                .map(Option::flatten)
+                .map_err(|e| to_rsdbc_err_with_sql(e, &sql))
+        };
+        Ok(Some(Self::new(Box::new(substring), len)))
+    }
+
+    fn new(substring: Substring<'a>, len: u64) -> Self {
+        Self {
+            substring,
+            position: 1,
+            len,
+        }
+    }
+
+    pub(crate) fn into_blob(self) -> Blob<'a> {
+        let len = self.len;
+        Blob::new(self, Some(len))
+    }
+
+    pub(crate) fn into_clob(self) -> Clob<'a> {
+        let len = self.len;
+        Clob::new(self, Some(len))
+    }
+}
+
+impl Read for ChunkReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = (self.len + 1).saturating_sub(self.position);
+        let n = remaining.min(buf.len() as u64);
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let chunk = (self.substring)(self.position, n)
+            .map_err(io::Error::other)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "the row was deleted while it was read"))?;
+        let n = chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use crate::lob::{quote_identifier, ChunkReader};
+
+    #[test]
+    fn identifiers_should_be_quoted() {
+        assert_eq!("`document`", quote_identifier("document"));
+        assert_eq!("`a``b`", quote_identifier("a`b"));
+    }
+
+    #[test]
+    fn chunk_reader_should_read_substrings_up_to_the_length() {
+        let value: Vec<u8> = (1..=10).collect();
+        let mut calls = Vec::new();
+        let substring = |position: u64, n: u64| {
+            calls.push((position, n));
+            let start = position as usize - 1;
+            Ok(Some(value[start..(start + n as usize).min(value.len())].to_vec()))
+        };
+        let mut reader = ChunkReader::new(Box::new(substring), 10);
+        let mut bytes = Vec::new();
+        let mut buf = [0; 4];
+        loop {
+            match reader.read(&mut buf).unwrap() {
+                0 => break,
+                n => bytes.extend_from_slice(&buf[..n]),
+            }
+        }
+        drop(reader);
+
+        assert_eq!(value, bytes);
+        assert_eq!(vec![(1, 4), (5, 4), (9, 2)], calls);
+    }
+
+    #[test]
+    fn chunk_reader_should_fail_if_the_row_is_deleted() {
+        let mut reader = ChunkReader::new(Box::new(|_, _| Ok(None)), 10);
+        assert_eq!(std::io::ErrorKind::UnexpectedEof, reader.read(&mut [0; 4]).unwrap_err().kind());
+    }
+}
@@ -0,0 +1,149 @@
+use mysql::consts::{ColumnFlags, ColumnType};
+use rsdbc_core::{Column, Nullability, RsdbcType};
+use crate::types::to_rsdbc_type;
+
+// https://dev.mysql.com/doc/dev/mysql-server/latest/page_protocol_com_query_response_text_resultset_column_definition.html
+/// Build the metadata for a result column from its column definition.
+pub(crate) fn to_column(column: &mysql::Column) -> Column {
+    let column_type = column.column_type();
+    let flags = column.flags();
+    let (precision, scale) = precision_and_scale(column);
+
+    let org_table = column.org_table_str();
+    let result = Column::new(&column.name_str(), column_rsdbc_type(column))
+        .type_name(type_name(column_type))
+        .native_type_id(Some(column_type as u32))
+        .precision(precision)
+        .scale(scale)
+        .nullability(if flags.contains(ColumnFlags::NOT_NULL_FLAG) { Nullability::NonNull } else { Nullability::Nullable });
+
+    // expressions have no originating table
+    if org_table.is_empty() {
+        return result.read_only(true);
+    }
+
+    result
+        .schema_name(Some(column.schema_str().into_owned()))
+        .table_name(Some(org_table.into_owned()))
+        .base_column_name(Some(column.org_name_str().into_owned()))
+}
+
+/// TINYINT(1), or BOOLEAN, columns hold booleans.
+fn column_rsdbc_type(column: &mysql::Column) -> RsdbcType {
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_TINY if column.column_length() == 1 => RsdbcType::Boolean,
+        column_type => to_rsdbc_type(column_type, column.character_set() == BINARY_COLLATION),
+    }
+}
+
+fn type_name(column_type: ColumnType) -> String {
+    let name = format!("{:?}", column_type);
+    name.strip_prefix("MYSQL_TYPE_").unwrap_or(&name).to_string()
+}
+
+// collation ids returned in the column definition, see SHOW COLLATION
+const BINARY_COLLATION: u16 = 63;
+
+/// Derive the precision and scale from the column length, which is the display width in bytes,
+/// and decimals.
+fn precision_and_scale(column: &mysql::Column) -> (Option<u64>, Option<u64>) {
+    let length = u64::from(column.column_length());
+    let decimals = u64::from(column.decimals());
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => {
+            // the length includes the decimal point and the sign of signed columns
+            let point = if decimals > 0 { 1 } else { 0 };
+            let sign = if column.flags().contains(ColumnFlags::UNSIGNED_FLAG) { 0 } else { 1 };
+            (Some(length.saturating_sub(point + sign)), Some(decimals))
+        },
+        ColumnType::MYSQL_TYPE_TINY
+        | ColumnType::MYSQL_TYPE_SHORT
+        | ColumnType::MYSQL_TYPE_INT24
+        | ColumnType::MYSQL_TYPE_LONG
+        | ColumnType::MYSQL_TYPE_LONGLONG => (Some(length), Some(0)),
+        ColumnType::MYSQL_TYPE_VARCHAR
+        | ColumnType::MYSQL_TYPE_VAR_STRING
+        | ColumnType::MYSQL_TYPE_STRING
+        | ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB => {
+            (max_bytes_per_char(column.character_set()).map(|bytes| length / bytes), None)
+        },
+        _ => (None, None),
+    }
+}
+
+/// Maximum number of bytes per character for the common character sets, used to convert the
+/// column length in bytes into characters.
+fn max_bytes_per_char(collation: u16) -> Option<u64> {
+    match collation {
+        BINARY_COLLATION => Some(1),
+        // latin1
+        5 | 8 | 15 | 31 | 47 | 48 | 49 | 94 => Some(1),
+        // utf8mb3
+        33 | 83 | 192..=215 | 223 => Some(3),
+        // utf8mb4
+        45 | 46 | 224..=247 | 255..=323 => Some(4),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql::consts::{ColumnFlags, ColumnType};
+    use rsdbc_core::{Nullability, ResultSetMetaData, RowMetadata, RsdbcType};
+    use crate::metadata::to_column;
+
+    #[test]
+    fn column_metadata_from_column_definition() {
+        let columns = vec![
+            to_column(&mysql::Column::new(ColumnType::MYSQL_TYPE_NEWDECIMAL)
+                .with_name(b"amount")
+                .with_schema(b"shop")
+                .with_org_table(b"orders")
+                .with_org_name(b"total")
+                .with_flags(ColumnFlags::NOT_NULL_FLAG)
+                .with_column_length(12)
+                .with_decimals(2)),
+            to_column(&mysql::Column::new(ColumnType::MYSQL_TYPE_VAR_STRING)
+                .with_name(b"label")
+                .with_character_set(255)
+                .with_column_length(80)),
+            to_column(&mysql::Column::new(ColumnType::MYSQL_TYPE_TINY)
+                .with_name(b"active")
+                .with_column_length(1)),
+        ];
+
+        assert_eq!("amount", columns.column_name(0));
+        assert_eq!(RsdbcType::Decimal, columns.column_type(0));
+        assert_eq!("NEWDECIMAL", columns.column_type_name(0));
+        assert_eq!(Some(10), columns.precision(0));
+        assert_eq!(Some(2), columns.scale(0));
+        assert_eq!(Some("shop".to_string()), columns.schema_name(0));
+        assert_eq!(Some("orders".to_string()), columns.table_name(0));
+        assert_eq!(Some("total".to_string()), columns.base_column_name(0));
+        assert_eq!(Nullability::NonNull, columns.is_nullable(0));
+        assert!(!columns.is_read_only(0));
+
+        assert_eq!(Some(20), columns.precision(1));
+        assert_eq!(None, columns.table_name(1));
+        assert_eq!(Nullability::Nullable, columns.is_nullable(1));
+        assert!(columns.is_read_only(1));
+
+        assert_eq!(RsdbcType::Boolean, columns.column_type(2));
+    }
+
+    #[test]
+    fn row_metadata_should_find_columns_by_name_ignoring_case() {
+        let columns = vec![
+            to_column(&mysql::Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"Id")),
+            to_column(&mysql::Column::new(ColumnType::MYSQL_TYPE_VAR_STRING).with_name(b"label").with_column_length(80)),
+        ];
+
+        assert_eq!("Id", columns.get_column_metadata_by_name("id").unwrap().get_name());
+        assert_eq!("label", columns.get_column_metadata(1).unwrap().get_name());
+        assert!(columns.contains("LABEL"));
+        assert!(!columns.contains("missing"));
+    }
+}
@@ -0,0 +1,642 @@
+use std::cell::Cell;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use mysql::prelude::Queryable;
+use mysql::{Conn, FromValueError, Params, Row};
+use rsdbc_core::connection::{SQLResult, Statement};
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::call::CallParameters;
+use rsdbc_core::{Column, ParameterMode, Result, ResultSet, ResultSetMetaData, RowMetadata, RsdbcType, SQLWarning, SQLWarnings, Segment, UuidStorage, Value};
+use crate::lob::{clear_variables, LobParameters};
+use crate::metadata::to_column;
+use crate::{show_warnings, to_rsdbc_err, to_rsdbc_err_with_sql};
+
+pub struct MySqlStatement<'conn> {
+    conn: &'conn mut Conn,
+    statement: mysql::Statement,
+    sql: String,
+    // the connection's warnings
+    warnings: SQLWarnings,
+    // the connection's last streamed query
+    streamed: StreamedQuery,
+    encoding: ValueEncoding,
+    call: CallParameters,
+}
+
+impl<'conn> MySqlStatement<'conn> {
+    pub(crate) fn prepare(
+        conn: &'conn mut Conn,
+        warnings: SQLWarnings,
+        streamed: StreamedQuery,
+        encoding: ValueEncoding,
+        sql: &str,
+    ) -> Result<Self> {
+        streamed.finish(conn, &warnings)?;
+        let statement = conn.prep(sql).map_err(|e| to_rsdbc_err_with_sql(e, sql))?;
+        Ok(Self {
+            conn,
+            statement,
+            sql: sql.to_string(),
+            warnings,
+            streamed,
+            encoding,
+            call: CallParameters::new(),
+        })
+    }
+
+    /// Executes the statement with `params` through `exec` and returns its result with the
+    /// warnings it reported. LOBs are written to session variables first, see [LobParameters].
+    fn exec<T>(
+        &mut self,
+        params: &[Value],
+        exec: impl FnOnce(&mut Conn, &mysql::Statement, Params) -> mysql::Result<T>,
+    ) -> Result<(T, Vec<SQLWarning>)> {
+        self.streamed.finish(self.conn, &self.warnings)?;
+        let lobs = match LobParameters::write(self.conn, &self.sql, params).map_err(|e| e.with_sql(&self.sql))? {
+            Some(lobs) => lobs,
+            None => {
+                let result = exec(self.conn, &self.statement, to_params(params, self.encoding)?)
+                    .map_err(|e| to_rsdbc_err(e).with_sql(&self.sql))?;
+                return Ok((result, self.collect_warnings()?));
+            },
+        };
+
+        let result = match self.conn.prep(lobs.sql.as_str()) {
+            Ok(statement) => to_params(&lobs.values, self.encoding)
+                .and_then(|params| exec(self.conn, &statement, params).map_err(to_rsdbc_err)),
+            Err(e) => Err(to_rsdbc_err(e)),
+        };
+        // only the warnings of the last statement are shown so they are collected before clearing
+        let result = result.and_then(|result| Ok((result, self.collect_warnings()?)));
+        let cleared = lobs.clear(self.conn);
+        let result = result.map_err(|e| e.with_sql(&self.sql))?;
+        cleared?;
+        Ok(result)
+    }
+
+    fn collect_warnings(&mut self) -> Result<Vec<SQLWarning>> {
+        let warnings = show_warnings(self.conn)?;
+        self.warnings.extend(warnings.clone());
+        Ok(warnings)
+    }
+}
+
+impl Statement<'_> for MySqlStatement<'_> {
+    // values are bound by passing them to execute_query and execute_update, so bindings made
+    // through the builder methods are ignored and execute reports them as unsupported
+    fn add(&mut self) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_index<T>(&mut self, _index: u32, _value: T) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_name<T>(&mut self, _name: &str, _value: T) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_null_index(&mut self, _index: u32) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_null_name(&mut self, _name: &str) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn execute<T: SQLResult>(&self) -> Result<T> where Self: Sized {
+        Err(RsdbcErrors::Unsupported("execute, use execute_query or execute_update".to_string()))
+    }
+
+    /// Rows are received as they are read. MySQL reports the query's warnings with its last row,
+    /// so they are added to the connection's warnings once the connection is next used, see
+    /// [StreamedQuery].
+    fn execute_query(&mut self, params: &[Value]) -> Result<Box<dyn ResultSet + '_>> {
+        self.streamed.finish(self.conn, &self.warnings)?;
+        let lobs = LobParameters::write(self.conn, &self.sql, params).map_err(|e| e.with_sql(&self.sql))?;
+        self.streamed.start(lobs.as_ref().map_or(Vec::new(), |lobs| lobs.variables().to_vec()));
+
+        let (statement, params) = match &lobs {
+            None => (self.statement.clone(), to_params(params, self.encoding)?),
+            Some(lobs) => (
+                self.conn.prep(lobs.sql.as_str()).map_err(|e| to_rsdbc_err(e).with_sql(&self.sql))?,
+                to_params(&lobs.values, self.encoding)?,
+            ),
+        };
+        let columns = statement.columns().iter().map(to_column).collect();
+        let rows = self.conn.exec_iter(statement, params).map_err(|e| to_rsdbc_err(e).with_sql(&self.sql))?;
+        Ok(Box::new(MySqlResultSet::new(Box::new(rows), columns)))
+    }
+
+    fn execute_update(&mut self, params: &[Value]) -> Result<u64> {
+        let (affected_rows, _) = self.exec(params, |conn, statement, params| {
+            conn.exec_drop(statement, params)?;
+            Ok(conn.affected_rows())
+        })?;
+        Ok(affected_rows)
+    }
+
+    fn bind_out(&mut self, index: u32, r#type: RsdbcType) -> Result<()> {
+        self.call.register(index, ParameterMode::Out, r#type);
+        Ok(())
+    }
+
+    fn bind_inout(&mut self, index: u32, r#type: RsdbcType) -> Result<()> {
+        self.call.register(index, ParameterMode::InOut, r#type);
+        Ok(())
+    }
+
+    /// OUT and INOUT parameters are named after the procedure's parameters when the call's
+    /// arguments are all placeholders, otherwise after the session variables they are passed as.
+    fn execute_call(&mut self, params: &[Value]) -> Result<Vec<Segment<'_>>> {
+        self.streamed.finish(self.conn, &self.warnings)?;
+        let segments = crate::call::execute_call(self.conn, &self.sql, &self.call, self.encoding, params)
+            .map_err(|e| e.with_sql(&self.sql))?;
+        self.collect_warnings()?;
+        Ok(segments)
+    }
+}
+
+/// How bound values which have no MySQL type are sent.
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct ValueEncoding {
+    pub(crate) uuid_storage: UuidStorage,
+    #[cfg(feature = "json")]
+    pub(crate) json_arrays: bool,
+}
+
+pub(crate) fn to_params(values: &[Value], encoding: ValueEncoding) -> Result<Params> {
+    if values.is_empty() {
+        return Ok(Params::Empty);
+    }
+    values.iter()
+        .map(|v| to_mysql_value(v, encoding))
+        .collect::<Result<Vec<_>>>()
+        .map(Params::Positional)
+}
+
+/// MySQL's DATETIME and TIMESTAMP don't store an offset so values with an offset are sent as UTC.
+#[cfg_attr(not(any(feature = "uuid", feature = "json")), allow(unused_variables))]
+pub(crate) fn to_mysql_value(value: &Value, encoding: ValueEncoding) -> Result<mysql::Value> {
+    let value = match value {
+        Value::Bool(v) => mysql::Value::Int(i64::from(*v)),
+        Value::Int32(v) => mysql::Value::Int(i64::from(*v)),
+        Value::UInt32(v) => mysql::Value::UInt(u64::from(*v)),
+        Value::Int64(v) => mysql::Value::Int(*v),
+        Value::Double(v) => mysql::Value::Double(*v),
+        Value::String(v) => mysql::Value::Bytes(v.clone().into_bytes()),
+        Value::Bytes(v) => mysql::Value::Bytes(v.clone()),
+        #[cfg(feature = "chrono")]
+        Value::NaiveDate(v) => datetime::naive_date_to_value(v),
+        #[cfg(feature = "chrono")]
+        Value::NaiveTime(v) => datetime::naive_time_to_value(v),
+        #[cfg(feature = "chrono")]
+        Value::NaiveDateTime(v) => datetime::naive_date_time_to_value(v),
+        #[cfg(feature = "chrono")]
+        Value::DateTimeUtc(v) => datetime::naive_date_time_to_value(&v.naive_utc()),
+        #[cfg(feature = "chrono")]
+        Value::DateTimeFixedOffset(v) => datetime::naive_date_time_to_value(&v.naive_utc()),
+        #[cfg(feature = "time")]
+        Value::OffsetDateTime(v) => datetime::offset_date_time_to_value(v),
+        // DECIMAL is sent as text by MySQL as well
+        #[cfg(feature = "rust_decimal")]
+        Value::Decimal(v) => mysql::Value::Bytes(v.to_string().into_bytes()),
+        #[cfg(feature = "bigdecimal")]
+        Value::BigDecimal(v) => mysql::Value::Bytes(v.to_string().into_bytes()),
+        #[cfg(feature = "uuid")]
+        Value::Uuid(v) => match encoding.uuid_storage {
+            UuidStorage::Text => mysql::Value::Bytes(v.hyphenated().to_string().into_bytes()),
+            UuidStorage::Binary => mysql::Value::Bytes(v.as_bytes().to_vec()),
+        },
+        #[cfg(feature = "json")]
+        Value::Json(v) => mysql::Value::Bytes(v.to_string().into_bytes()),
+        #[cfg(feature = "json")]
+        Value::Array(v) if encoding.json_arrays => {
+            mysql::Value::Bytes(rsdbc_core::json::to_json_array(v)?.to_string().into_bytes())
+        },
+        Value::Array(_) => {
+            return Err(RsdbcErrors::Unsupported(
+                "MySQL has no arrays, enable JSON arrays to bind arrays as JSON".to_string()
+            ))
+        },
+        // read whole, statements write them to session variables in chunks instead
+        Value::Blob(v) | Value::Clob(v) => mysql::Value::Bytes(v.read_to_vec()?),
+    };
+    Ok(value)
+}
+
+/// The work left by the last query whose rows were streamed.
+///
+/// Its result set holds the connection until it is dropped, so the warnings MySQL reports with
+/// the last row are fetched, and the session variables its LOBs were written to are cleared,
+/// before the connection is next used.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StreamedQuery {
+    // the session variables of the query, None once its work is done
+    variables: Arc<Mutex<Option<Vec<String>>>>,
+}
+
+impl StreamedQuery {
+    fn start(&self, variables: Vec<String>) {
+        *self.lock() = Some(variables);
+    }
+
+    /// Does the work left by the last streamed query, adding its warnings to `warnings`
+    pub(crate) fn finish(&self, conn: &mut Conn, warnings: &SQLWarnings) -> Result<()> {
+        let variables = match self.lock().take() {
+            Some(variables) => variables,
+            None => return Ok(()),
+        };
+        // clearing the variables resets the warnings so they are fetched first
+        let shown = show_warnings(conn).map(|shown| warnings.extend(shown));
+        let cleared = match variables.is_empty() {
+            true => Ok(()),
+            false => clear_variables(conn, &variables),
+        };
+        shown.and(cleared)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Vec<String>>> {
+        // a panic while holding the lock can't leave the Option in an inconsistent state
+        self.variables.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Rows either received as they are read or, for the results of a call, already received
+pub(crate) type Rows<'a> = Box<dyn Iterator<Item = mysql::Result<Row>> + 'a>;
+
+pub(crate) struct MySqlResultSet<'a> {
+    rows: Rows<'a>,
+    row: Option<Row>,
+    columns: Vec<Column>,
+    // the error which stopped the last call to next, returned by the next getter as next can only
+    // report whether there is a row
+    error: Cell<Option<RsdbcErrors>>,
+    failed: bool,
+}
+
+impl<'a> MySqlResultSet<'a> {
+    pub(crate) fn new(rows: Rows<'a>, columns: Vec<Column>) -> Self {
+        Self {
+            rows,
+            row: None,
+            columns,
+            error: Cell::new(None),
+            failed: false,
+        }
+    }
+
+    fn value(&self, i: u64) -> Result<&mysql::Value> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let row = self.row
+            .as_ref()
+            .ok_or_else(|| RsdbcErrors::General("result set is not positioned on a row".to_string()))?;
+        row.as_ref(i as usize)
+            .ok_or_else(|| RsdbcErrors::General(format!("column index {} out of range", i)))
+    }
+
+    fn get<T: mysql::prelude::FromValue>(&self, i: u64) -> Result<Option<T>> {
+        mysql::from_value_opt::<Option<T>>(self.value(i)?.clone()).map_err(invalid_value)
+    }
+}
+
+fn invalid_value(e: FromValueError) -> RsdbcErrors {
+    RsdbcErrors::General(format!("invalid value {:?}", e.0))
+}
+
+impl ResultSet for MySqlResultSet<'_> {
+    fn meta_data(&self) -> Result<Box<dyn ResultSetMetaData>> {
+        Ok(Box::new(self.columns.clone()))
+    }
+
+    fn row_metadata(&self) -> Result<Box<dyn RowMetadata>> {
+        Ok(Box::new(self.columns.clone()))
+    }
+
+    /// A failure to receive the next row is reported by the getters, so next returns true once
+    /// more when it fails and false from then on rather than silently ending the result set.
+    fn next(&mut self) -> bool {
+        if self.failed {
+            self.row = None;
+            return false;
+        }
+        self.row = match self.rows.next().transpose() {
+            Ok(row) => row,
+            Err(e) => {
+                self.failed = true;
+                self.error.set(Some(to_rsdbc_err(e)));
+                None
+            },
+        };
+        self.row.is_some() || self.failed
+    }
+
+    fn get_bool(&self, i: u64) -> Result<Option<bool>> {
+        self.get(i)
+    }
+
+    fn get_i8(&self, i: u64) -> Result<Option<i8>> {
+        self.get(i)
+    }
+
+    fn get_i16(&self, i: u64) -> Result<Option<i16>> {
+        self.get(i)
+    }
+
+    fn get_i32(&self, i: u64) -> Result<Option<i32>> {
+        self.get(i)
+    }
+
+    fn get_i64(&self, i: u64) -> Result<Option<i64>> {
+        self.get(i)
+    }
+
+    fn get_f32(&self, i: u64) -> Result<Option<f32>> {
+        self.get(i)
+    }
+
+    fn get_f64(&self, i: u64) -> Result<Option<f64>> {
+        self.get(i)
+    }
+
+    fn get_string(&self, i: u64) -> Result<Option<String>> {
+        match self.value(i)? {
+            mysql::Value::Bytes(bytes) => String::from_utf8(bytes.clone())
+                .map(Some)
+                .map_err(|e| RsdbcErrors::General(e.to_string())),
+            mysql::Value::Int(v) => Ok(Some(v.to_string())),
+            mysql::Value::UInt(v) => Ok(Some(v.to_string())),
+            mysql::Value::Float(v) => Ok(Some(v.to_string())),
+            mysql::Value::Double(v) => Ok(Some(v.to_string())),
+            mysql::Value::NULL => Ok(None),
+            // dates and times are returned as text by the text protocol so use the same format
+            v => Ok(Some(v.as_sql(true).trim_matches('\'').to_string())),
+        }
+    }
+
+    fn get_bytes(&self, i: u64) -> Result<Option<Vec<u8>>> {
+        self.get(i)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_date(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveDate>> {
+        Ok(self.get_naive_date_time(i)?.map(|dt| dt.date()))
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_time(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveTime>> {
+        datetime::to_naive_time(self.value(i)?)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_date_time(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveDateTime>> {
+        datetime::to_naive_date_time(self.value(i)?)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_date_time_fixed_offset(&self, i: u64) -> Result<Option<rsdbc_core::chrono::DateTime<rsdbc_core::chrono::FixedOffset>>> {
+        use rsdbc_core::chrono::{FixedOffset, TimeZone};
+
+        let utc = FixedOffset::east_opt(0).expect("UTC is a valid offset");
+        Ok(self.get_naive_date_time(i)?.map(|dt| utc.from_utc_datetime(&dt)))
+    }
+
+    #[cfg(feature = "time")]
+    fn get_offset_date_time(&self, i: u64) -> Result<Option<rsdbc_core::time::OffsetDateTime>> {
+        datetime::to_offset_date_time(self.value(i)?)
+    }
+}
+
+/// Conversions between date and time values and MySQL's DATE, TIME, DATETIME and TIMESTAMP.
+///
+/// The binary protocol returns [mysql::Value::Date] and [mysql::Value::Time] whereas the text
+/// protocol returns text. Values without an offset are taken to be UTC.
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime {
+    use rsdbc_core::error::RsdbcErrors;
+    use rsdbc_core::Result;
+
+    /// A DATETIME or TIMESTAMP's year, month, day, hour, minute, second and microsecond
+    type Parts = (u16, u8, u8, u8, u8, u8, u32);
+
+    fn invalid(what: &str, value: &mysql::Value) -> RsdbcErrors {
+        RsdbcErrors::General(format!("invalid {} {:?}", what, value))
+    }
+
+    fn text(value: &mysql::Value) -> Result<&str> {
+        match value {
+            mysql::Value::Bytes(bytes) => std::str::from_utf8(bytes).map_err(|_| invalid("text", value)),
+            v => Err(invalid("date", v)),
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    mod chrono_support {
+        use rsdbc_core::chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+        use rsdbc_core::datetime::{parse_naive_date_time, parse_naive_time};
+        use rsdbc_core::Result;
+        use super::{invalid, text, Parts};
+
+        fn from_parts(value: &mysql::Value, (year, month, day, hour, minute, second, micros): Parts) -> Result<NaiveDateTime> {
+            NaiveDate::from_ymd_opt(i32::from(year), u32::from(month), u32::from(day))
+                .and_then(|d| d.and_hms_micro_opt(u32::from(hour), u32::from(minute), u32::from(second), micros))
+                .ok_or_else(|| invalid("timestamp", value))
+        }
+
+        pub(crate) fn to_naive_date_time(value: &mysql::Value) -> Result<Option<NaiveDateTime>> {
+            match *value {
+                mysql::Value::NULL => Ok(None),
+                mysql::Value::Date(y, m, d, h, i, s, us) => from_parts(value, (y, m, d, h, i, s, us)).map(Some),
+                _ => parse_naive_date_time(text(value)?).map(Some),
+            }
+        }
+
+        /// TIME is an interval of up to 838 hours so only times of day can be read
+        pub(crate) fn to_naive_time(value: &mysql::Value) -> Result<Option<NaiveTime>> {
+            match *value {
+                mysql::Value::NULL => Ok(None),
+                mysql::Value::Time(false, 0, h, i, s, us) => {
+                    NaiveTime::from_hms_micro_opt(u32::from(h), u32::from(i), u32::from(s), us)
+                        .map(Some)
+                        .ok_or_else(|| invalid("time", value))
+                },
+                mysql::Value::Time(..) => Err(invalid("time of day", value)),
+                mysql::Value::Date(..) => Ok(to_naive_date_time(value)?.map(|dt| dt.time())),
+                _ => parse_naive_time(text(value)?).map(Some),
+            }
+        }
+
+        pub(crate) fn naive_date_to_value(date: &NaiveDate) -> mysql::Value {
+            mysql::Value::Date(date.year() as u16, date.month() as u8, date.day() as u8, 0, 0, 0, 0)
+        }
+
+        pub(crate) fn naive_time_to_value(time: &NaiveTime) -> mysql::Value {
+            // a leap second is sent as 59.999999
+            let micros = (time.nanosecond() / 1_000).min(999_999);
+            mysql::Value::Time(false, 0, time.hour() as u8, time.minute() as u8, time.second() as u8, micros)
+        }
+
+        pub(crate) fn naive_date_time_to_value(date_time: &NaiveDateTime) -> mysql::Value {
+            let micros = (date_time.nanosecond() / 1_000).min(999_999);
+            mysql::Value::Date(
+                date_time.year() as u16,
+                date_time.month() as u8,
+                date_time.day() as u8,
+                date_time.hour() as u8,
+                date_time.minute() as u8,
+                date_time.second() as u8,
+                micros,
+            )
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    pub(crate) use chrono_support::*;
+
+    #[cfg(feature = "time")]
+    mod time_support {
+        use rsdbc_core::datetime::parse_offset_date_time;
+        use rsdbc_core::time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+        use rsdbc_core::Result;
+        use super::{invalid, text};
+
+        pub(crate) fn to_offset_date_time(value: &mysql::Value) -> Result<Option<OffsetDateTime>> {
+            match *value {
+                mysql::Value::NULL => Ok(None),
+                mysql::Value::Date(y, m, d, h, i, s, us) => {
+                    let date = Month::try_from(m).ok().and_then(|m| Date::from_calendar_date(i32::from(y), m, d).ok());
+                    let time = Time::from_hms_micro(h, i, s, us).ok();
+                    match (date, time) {
+                        (Some(date), Some(time)) => Ok(Some(PrimitiveDateTime::new(date, time).assume_utc())),
+                        _ => Err(invalid("timestamp", value)),
+                    }
+                },
+                _ => parse_offset_date_time(text(value)?).map(Some),
+            }
+        }
+
+        pub(crate) fn offset_date_time_to_value(date_time: &OffsetDateTime) -> mysql::Value {
+            let utc = date_time.to_offset(UtcOffset::UTC);
+            mysql::Value::Date(
+                utc.year() as u16,
+                u8::from(utc.month()),
+                utc.day(),
+                utc.hour(),
+                utc.minute(),
+                utc.second(),
+                utc.microsecond(),
+            )
+        }
+    }
+
+    #[cfg(feature = "time")]
+    pub(crate) use time_support::*;
+}
+
+#[cfg(test)]
+mod tests {
+    use rsdbc_core::{ResultSet, Value};
+    use rsdbc_core::error::RsdbcErrors;
+    use crate::statement::{to_mysql_value, to_params, MySqlResultSet, ValueEncoding};
+
+    #[test]
+    fn values_should_be_converted_to_mysql_values() {
+        assert_eq!(mysql::Value::Int(-1), to_mysql_value(&Value::Int32(-1), ValueEncoding::default()).unwrap());
+        assert_eq!(mysql::Value::UInt(1), to_mysql_value(&Value::UInt32(1), ValueEncoding::default()).unwrap());
+        assert_eq!(mysql::Value::Bytes(b"a".to_vec()), to_mysql_value(&Value::String("a".to_string()), ValueEncoding::default()).unwrap());
+        assert_eq!(mysql::Params::Empty, to_params(&[], ValueEncoding::default()).unwrap());
+    }
+
+    #[test]
+    fn failures_to_receive_rows_should_be_reported_by_the_getters() {
+        let rows = vec![Err(mysql::Error::IoError(std::io::Error::other("connection lost")))];
+        let mut rs = MySqlResultSet::new(Box::new(rows.into_iter()), Vec::new());
+        assert!(rs.next());
+        assert!(rs.get_i64(0).unwrap_err().to_string().contains("connection lost"));
+        assert!(!rs.next());
+    }
+
+    #[test]
+    fn lobs_should_be_read_from_their_source() {
+        let lob = rsdbc_core::Lob::from_reader(std::io::Cursor::new(vec![1, 2]));
+        assert_eq!(mysql::Value::Bytes(vec![1, 2]), to_mysql_value(&Value::Blob(lob.clone()), ValueEncoding::default()).unwrap());
+        assert!(to_mysql_value(&Value::Blob(lob), ValueEncoding::default()).is_err());
+        assert_eq!(mysql::Value::Bytes(b"a".to_vec()), to_mysql_value(&Value::clob("a".to_string()), ValueEncoding::default()).unwrap());
+    }
+
+    #[test]
+    fn arrays_should_be_unsupported_unless_json_arrays_are_enabled() {
+        let array = Value::array(vec![Value::Int32(1)]);
+        assert!(matches!(to_mysql_value(&array, ValueEncoding::default()), Err(RsdbcErrors::Unsupported(_))));
+
+        #[cfg(feature = "json")]
+        {
+            let encoding = ValueEncoding { json_arrays: true, ..Default::default() };
+            let array = Value::array(vec![Some(Value::array(vec![Value::Int32(1), Value::Int32(2)])), None]);
+            assert_eq!(mysql::Value::Bytes(b"[[1,2],null]".to_vec()), to_mysql_value(&array, encoding).unwrap());
+        }
+    }
+
+    #[cfg(all(feature = "uuid", feature = "json"))]
+    #[test]
+    fn uuids_should_be_sent_as_configured() {
+        use rsdbc_core::serde_json::json;
+        use rsdbc_core::uuid::Uuid;
+        use rsdbc_core::UuidStorage;
+
+        let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        let binary = ValueEncoding { uuid_storage: UuidStorage::Binary, ..Default::default() };
+        assert_eq!(mysql::Value::Bytes(b"67e55044-10b1-426f-9247-bb680e5fe0c8".to_vec()), to_mysql_value(&Value::Uuid(id), ValueEncoding::default()).unwrap());
+        assert_eq!(mysql::Value::Bytes(id.as_bytes().to_vec()), to_mysql_value(&Value::Uuid(id), binary).unwrap());
+        assert_eq!(mysql::Value::Bytes(br#"{"amount":10}"#.to_vec()), to_mysql_value(&Value::Json(json!({"amount": 10})), ValueEncoding::default()).unwrap());
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn decimals_should_be_sent_as_text() {
+        use std::str::FromStr;
+        use rsdbc_core::rust_decimal::Decimal;
+
+        let decimal = Decimal::from_str("12345678901234567890.1230").unwrap();
+        assert_eq!(mysql::Value::Bytes(b"12345678901234567890.1230".to_vec()), to_mysql_value(&Value::Decimal(decimal), ValueEncoding::default()).unwrap());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_values_should_round_trip() {
+        use rsdbc_core::chrono::{FixedOffset, NaiveDate, TimeZone};
+        use crate::statement::datetime::{to_naive_date_time, to_naive_time};
+
+        let date_time = NaiveDate::from_ymd_opt(2021, 12, 31).unwrap().and_hms_micro_opt(23, 59, 59, 1).unwrap();
+        let value = to_mysql_value(&Value::NaiveDateTime(date_time), ValueEncoding::default()).unwrap();
+        assert_eq!(mysql::Value::Date(2021, 12, 31, 23, 59, 59, 1), value);
+        assert_eq!(Some(date_time), to_naive_date_time(&value).unwrap());
+        assert_eq!(Some(date_time.time()), to_naive_time(&to_mysql_value(&Value::NaiveTime(date_time.time()), ValueEncoding::default()).unwrap()).unwrap());
+
+        // the text protocol
+        let text = mysql::Value::Bytes(b"2021-12-31 23:59:59.000001".to_vec());
+        assert_eq!(Some(date_time), to_naive_date_time(&text).unwrap());
+        assert_eq!(None, to_naive_date_time(&mysql::Value::NULL).unwrap());
+
+        // offsets are sent as UTC
+        let offset = FixedOffset::east_opt(3600).unwrap().from_local_datetime(&date_time).unwrap();
+        assert_eq!(mysql::Value::Date(2021, 12, 31, 22, 59, 59, 1), to_mysql_value(&Value::DateTimeFixedOffset(offset), ValueEncoding::default()).unwrap());
+
+        // TIME intervals which are not a time of day
+        assert!(to_naive_time(&mysql::Value::Time(false, 1, 0, 0, 0, 0)).is_err());
+        assert!(to_naive_time(&mysql::Value::Time(true, 0, 1, 0, 0, 0)).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_values_should_round_trip() {
+        use rsdbc_core::time::OffsetDateTime;
+        use crate::statement::datetime::to_offset_date_time;
+
+        let date_time = OffsetDateTime::from_unix_timestamp(1_640_995_199).unwrap();
+        let value = to_mysql_value(&Value::OffsetDateTime(date_time), ValueEncoding::default()).unwrap();
+        assert_eq!(mysql::Value::Date(2021, 12, 31, 23, 59, 59, 0), value);
+        assert_eq!(Some(date_time), to_offset_date_time(&value).unwrap());
+    }
+}
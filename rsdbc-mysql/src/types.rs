@@ -0,0 +1,170 @@
+use mysql::consts::ColumnType;
+use rsdbc_core::RsdbcType;
+
+/// Map a MySQL column type to a generic type.
+///
+/// MySQL reports `BINARY`, `VARBINARY` and `BLOB` columns with the same column types as `CHAR`,
+/// `VARCHAR` and `TEXT` columns so `binary` tells whether the column has the binary character set.
+pub fn to_rsdbc_type(column_type: ColumnType, binary: bool) -> RsdbcType {
+    match column_type {
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => RsdbcType::Decimal,
+        ColumnType::MYSQL_TYPE_TINY => RsdbcType::Tinyint,
+        ColumnType::MYSQL_TYPE_SHORT | ColumnType::MYSQL_TYPE_YEAR => RsdbcType::Smallint,
+        ColumnType::MYSQL_TYPE_LONG | ColumnType::MYSQL_TYPE_INT24 => RsdbcType::Integer,
+        ColumnType::MYSQL_TYPE_LONGLONG => RsdbcType::Bigint,
+        ColumnType::MYSQL_TYPE_FLOAT => RsdbcType::Real,
+        ColumnType::MYSQL_TYPE_DOUBLE => RsdbcType::Double,
+        ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => RsdbcType::Date,
+        ColumnType::MYSQL_TYPE_TIME | ColumnType::MYSQL_TYPE_TIME2 => RsdbcType::Time,
+        // TIMESTAMP values are converted to the session time zone and carry no offset
+        ColumnType::MYSQL_TYPE_DATETIME
+        | ColumnType::MYSQL_TYPE_DATETIME2
+        | ColumnType::MYSQL_TYPE_TIMESTAMP
+        | ColumnType::MYSQL_TYPE_TIMESTAMP2 => RsdbcType::Timestamp,
+        ColumnType::MYSQL_TYPE_STRING if binary => RsdbcType::Binary,
+        ColumnType::MYSQL_TYPE_STRING => RsdbcType::Char,
+        ColumnType::MYSQL_TYPE_VARCHAR | ColumnType::MYSQL_TYPE_VAR_STRING if binary => RsdbcType::Varbinary,
+        ColumnType::MYSQL_TYPE_VARCHAR | ColumnType::MYSQL_TYPE_VAR_STRING => RsdbcType::Varchar,
+        ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB if binary => RsdbcType::Blob,
+        ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB => RsdbcType::Clob,
+        ColumnType::MYSQL_TYPE_BIT => RsdbcType::Binary,
+        ColumnType::MYSQL_TYPE_GEOMETRY => RsdbcType::Blob,
+        // ENUM, SET and JSON among others are read as text
+        _ => RsdbcType::Varchar,
+    }
+}
+
+/// Map a generic type to the MySQL column type values of that type are stored as along with
+/// whether the column has the binary character set.
+///
+/// Returns [None] for [RsdbcType::Collection] which has no column type.
+pub fn to_mysql_type(t: RsdbcType) -> Option<(ColumnType, bool)> {
+    let mysql_type = match t {
+        RsdbcType::Char | RsdbcType::Nchar => (ColumnType::MYSQL_TYPE_STRING, false),
+        RsdbcType::Varchar | RsdbcType::Nvarchar => (ColumnType::MYSQL_TYPE_VAR_STRING, false),
+        RsdbcType::Clob | RsdbcType::Nclob => (ColumnType::MYSQL_TYPE_BLOB, false),
+        // BOOLEAN is a synonym for TINYINT(1)
+        RsdbcType::Boolean => (ColumnType::MYSQL_TYPE_TINY, false),
+        RsdbcType::Binary => (ColumnType::MYSQL_TYPE_STRING, true),
+        RsdbcType::Varbinary => (ColumnType::MYSQL_TYPE_VAR_STRING, true),
+        RsdbcType::Blob => (ColumnType::MYSQL_TYPE_BLOB, true),
+        RsdbcType::Integer => (ColumnType::MYSQL_TYPE_LONG, false),
+        RsdbcType::Tinyint => (ColumnType::MYSQL_TYPE_TINY, false),
+        RsdbcType::Smallint => (ColumnType::MYSQL_TYPE_SHORT, false),
+        RsdbcType::Bigint => (ColumnType::MYSQL_TYPE_LONGLONG, false),
+        RsdbcType::Numeric | RsdbcType::Decimal => (ColumnType::MYSQL_TYPE_NEWDECIMAL, false),
+        RsdbcType::Float | RsdbcType::Double => (ColumnType::MYSQL_TYPE_DOUBLE, false),
+        RsdbcType::Real => (ColumnType::MYSQL_TYPE_FLOAT, false),
+        RsdbcType::Date => (ColumnType::MYSQL_TYPE_DATE, false),
+        RsdbcType::Time | RsdbcType::TimeWithTimeZone => (ColumnType::MYSQL_TYPE_TIME, false),
+        RsdbcType::Timestamp => (ColumnType::MYSQL_TYPE_DATETIME, false),
+        // TIMESTAMP values are stored in UTC
+        RsdbcType::TimestampWithTimeZone => (ColumnType::MYSQL_TYPE_TIMESTAMP, false),
+        RsdbcType::Collection => return None,
+    };
+    Some(mysql_type)
+}
+
+/// Map a generic type to the type name columns of that type are declared with.
+///
+/// `CHAR`, `VARCHAR`, `BINARY` and `VARBINARY` need a length, e.g. `VARCHAR(255)`, which is
+/// left to the caller. Character and binary large objects are declared as `LONGTEXT` and
+/// `LONGBLOB` to hold values of any size.
+/// Returns [None] for [RsdbcType::Collection] which has no column type.
+pub fn to_declared_type(t: RsdbcType) -> Option<&'static str> {
+    let decl_type = match to_mysql_type(t)? {
+        (ColumnType::MYSQL_TYPE_STRING, false) => "CHAR",
+        (ColumnType::MYSQL_TYPE_STRING, true) => "BINARY",
+        (ColumnType::MYSQL_TYPE_VAR_STRING, false) => "VARCHAR",
+        (ColumnType::MYSQL_TYPE_VAR_STRING, true) => "VARBINARY",
+        (ColumnType::MYSQL_TYPE_BLOB, false) => "LONGTEXT",
+        (ColumnType::MYSQL_TYPE_BLOB, true) => "LONGBLOB",
+        (ColumnType::MYSQL_TYPE_TINY, _) if t == RsdbcType::Boolean => "BOOLEAN",
+        (ColumnType::MYSQL_TYPE_TINY, _) => "TINYINT",
+        (ColumnType::MYSQL_TYPE_SHORT, _) => "SMALLINT",
+        (ColumnType::MYSQL_TYPE_LONG, _) => "INT",
+        (ColumnType::MYSQL_TYPE_LONGLONG, _) => "BIGINT",
+        (ColumnType::MYSQL_TYPE_NEWDECIMAL, _) => "DECIMAL",
+        (ColumnType::MYSQL_TYPE_DOUBLE, _) => "DOUBLE",
+        (ColumnType::MYSQL_TYPE_FLOAT, _) => "FLOAT",
+        (ColumnType::MYSQL_TYPE_DATE, _) => "DATE",
+        (ColumnType::MYSQL_TYPE_TIME, _) => "TIME",
+        (ColumnType::MYSQL_TYPE_DATETIME, _) => "DATETIME",
+        (ColumnType::MYSQL_TYPE_TIMESTAMP, _) => "TIMESTAMP",
+        _ => return None,
+    };
+    Some(decl_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use mysql::consts::ColumnType;
+    use rsdbc_core::RsdbcType;
+    use crate::types::{to_declared_type, to_mysql_type, to_rsdbc_type};
+
+    #[test]
+    fn every_type_should_map_to_a_mysql_type_and_back() {
+        let expected = [
+            (RsdbcType::Char, Some((ColumnType::MYSQL_TYPE_STRING, false)), RsdbcType::Char),
+            (RsdbcType::Varchar, Some((ColumnType::MYSQL_TYPE_VAR_STRING, false)), RsdbcType::Varchar),
+            (RsdbcType::Nchar, Some((ColumnType::MYSQL_TYPE_STRING, false)), RsdbcType::Char),
+            (RsdbcType::Nvarchar, Some((ColumnType::MYSQL_TYPE_VAR_STRING, false)), RsdbcType::Varchar),
+            (RsdbcType::Clob, Some((ColumnType::MYSQL_TYPE_BLOB, false)), RsdbcType::Clob),
+            (RsdbcType::Nclob, Some((ColumnType::MYSQL_TYPE_BLOB, false)), RsdbcType::Clob),
+            (RsdbcType::Boolean, Some((ColumnType::MYSQL_TYPE_TINY, false)), RsdbcType::Tinyint),
+            (RsdbcType::Binary, Some((ColumnType::MYSQL_TYPE_STRING, true)), RsdbcType::Binary),
+            (RsdbcType::Varbinary, Some((ColumnType::MYSQL_TYPE_VAR_STRING, true)), RsdbcType::Varbinary),
+            (RsdbcType::Blob, Some((ColumnType::MYSQL_TYPE_BLOB, true)), RsdbcType::Blob),
+            (RsdbcType::Integer, Some((ColumnType::MYSQL_TYPE_LONG, false)), RsdbcType::Integer),
+            (RsdbcType::Tinyint, Some((ColumnType::MYSQL_TYPE_TINY, false)), RsdbcType::Tinyint),
+            (RsdbcType::Smallint, Some((ColumnType::MYSQL_TYPE_SHORT, false)), RsdbcType::Smallint),
+            (RsdbcType::Bigint, Some((ColumnType::MYSQL_TYPE_LONGLONG, false)), RsdbcType::Bigint),
+            (RsdbcType::Numeric, Some((ColumnType::MYSQL_TYPE_NEWDECIMAL, false)), RsdbcType::Decimal),
+            (RsdbcType::Decimal, Some((ColumnType::MYSQL_TYPE_NEWDECIMAL, false)), RsdbcType::Decimal),
+            (RsdbcType::Float, Some((ColumnType::MYSQL_TYPE_DOUBLE, false)), RsdbcType::Double),
+            (RsdbcType::Real, Some((ColumnType::MYSQL_TYPE_FLOAT, false)), RsdbcType::Real),
+            (RsdbcType::Double, Some((ColumnType::MYSQL_TYPE_DOUBLE, false)), RsdbcType::Double),
+            (RsdbcType::Date, Some((ColumnType::MYSQL_TYPE_DATE, false)), RsdbcType::Date),
+            (RsdbcType::Time, Some((ColumnType::MYSQL_TYPE_TIME, false)), RsdbcType::Time),
+            (RsdbcType::TimeWithTimeZone, Some((ColumnType::MYSQL_TYPE_TIME, false)), RsdbcType::Time),
+            (RsdbcType::Timestamp, Some((ColumnType::MYSQL_TYPE_DATETIME, false)), RsdbcType::Timestamp),
+            (RsdbcType::TimestampWithTimeZone, Some((ColumnType::MYSQL_TYPE_TIMESTAMP, false)), RsdbcType::Timestamp),
+            (RsdbcType::Collection, None, RsdbcType::Collection),
+        ];
+        assert_eq!(RsdbcType::ALL.len(), expected.len());
+
+        for (t, (expected_type, mysql_type, back)) in RsdbcType::ALL.iter().zip(expected) {
+            assert_eq!(*t, expected_type);
+            assert_eq!(mysql_type, to_mysql_type(*t), "{:?}", t);
+            if let Some((column_type, binary)) = mysql_type {
+                assert_eq!(back, to_rsdbc_type(column_type, binary), "{:?}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn mysql_types_should_map_to_generic_types() {
+        assert_eq!(RsdbcType::Varchar, to_rsdbc_type(ColumnType::MYSQL_TYPE_JSON, false));
+        assert_eq!(RsdbcType::Varchar, to_rsdbc_type(ColumnType::MYSQL_TYPE_ENUM, false));
+        assert_eq!(RsdbcType::Clob, to_rsdbc_type(ColumnType::MYSQL_TYPE_LONG_BLOB, false));
+        assert_eq!(RsdbcType::Blob, to_rsdbc_type(ColumnType::MYSQL_TYPE_TINY_BLOB, true));
+        assert_eq!(RsdbcType::Smallint, to_rsdbc_type(ColumnType::MYSQL_TYPE_YEAR, false));
+    }
+
+    #[test]
+    fn every_type_but_collections_should_have_a_declared_type() {
+        for t in RsdbcType::ALL {
+            assert_eq!(*t != RsdbcType::Collection, to_declared_type(*t).is_some(), "{:?}", t);
+        }
+        assert_eq!(Some("BOOLEAN"), to_declared_type(RsdbcType::Boolean));
+        assert_eq!(Some("TINYINT"), to_declared_type(RsdbcType::Tinyint));
+        assert_eq!(Some("LONGBLOB"), to_declared_type(RsdbcType::Blob));
+        assert_eq!(Some("VARBINARY"), to_declared_type(RsdbcType::Varbinary));
+    }
+}
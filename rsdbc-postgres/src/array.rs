@@ -0,0 +1,233 @@
+// https://www.postgresql.org/docs/current/arrays.html
+//! Arrays in the binary format.
+//!
+//! Postgres sends every array, whatever its number of dimensions, as its dimensions followed by
+//! the elements in row-major order whereas a [Value::Array] nests an array per dimension.
+
+use std::error::Error;
+use bytes::BytesMut;
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types::{array_from_sql, array_to_sql as protocol_array_to_sql, ArrayDimension};
+use postgres_protocol::IsNull as ProtocolIsNull;
+use tokio_postgres::types::{FromSql, IsNull, Kind, ToSql, Type};
+use rsdbc_core::Value;
+use crate::numeric::PgNumeric;
+use crate::statement::PgValue;
+
+/// The elements of an array column
+#[derive(Debug)]
+pub(crate) struct PgArray(pub(crate) Vec<Option<Value>>);
+
+impl<'a> FromSql<'a> for PgArray {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        let member = match ty.kind() {
+            Kind::Array(member) => member,
+            _ => return Err(format!("{} is not an array", ty).into()),
+        };
+
+        let array = array_from_sql(raw)?;
+        let dimensions: Vec<usize> = array.dimensions()
+            .map(|d| Ok(usize::try_from(d.len)?))
+            .collect()?;
+        let elements: Vec<Option<Value>> = array.values()
+            .map(|v| v.map(|raw| element_from_sql(member, raw)).transpose())
+            .collect()?;
+
+        Ok(PgArray(nest(elements, &dimensions)))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(ty.kind(), Kind::Array(_))
+    }
+}
+
+/// Nest the row-major elements of an array with more than one dimension.
+fn nest(elements: Vec<Option<Value>>, dimensions: &[usize]) -> Vec<Option<Value>> {
+    if dimensions.len() <= 1 {
+        return elements;
+    }
+
+    let inner: usize = dimensions[1..].iter().product();
+    let mut elements = elements.into_iter();
+    (0..dimensions[0])
+        .map(|_| Some(Value::Array(nest(elements.by_ref().take(inner).collect(), &dimensions[1..]))))
+        .collect()
+}
+
+fn element_from_sql(member: &Type, raw: &[u8]) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    let value = match *member {
+        Type::BOOL => Value::Bool(bool::from_sql(member, raw)?),
+        Type::INT2 => Value::Int32(i32::from(i16::from_sql(member, raw)?)),
+        Type::INT4 => Value::Int32(i32::from_sql(member, raw)?),
+        Type::INT8 => Value::Int64(i64::from_sql(member, raw)?),
+        Type::OID => Value::UInt32(u32::from_sql(member, raw)?),
+        Type::FLOAT4 => Value::Double(f64::from(f32::from_sql(member, raw)?)),
+        Type::FLOAT8 => Value::Double(f64::from_sql(member, raw)?),
+        Type::BYTEA => Value::Bytes(Vec::<u8>::from_sql(member, raw)?),
+        Type::NUMERIC => numeric_from_sql(PgNumeric::from_sql(member, raw)?)?,
+        #[cfg(feature = "uuid")]
+        Type::UUID => Value::Uuid(FromSql::from_sql(member, raw)?),
+        #[cfg(feature = "json")]
+        Type::JSON | Type::JSONB => Value::Json(FromSql::from_sql(member, raw)?),
+        #[cfg(feature = "chrono")]
+        Type::DATE => Value::NaiveDate(FromSql::from_sql(member, raw)?),
+        #[cfg(feature = "chrono")]
+        Type::TIME => Value::NaiveTime(FromSql::from_sql(member, raw)?),
+        #[cfg(feature = "chrono")]
+        Type::TIMESTAMP => Value::NaiveDateTime(FromSql::from_sql(member, raw)?),
+        #[cfg(feature = "chrono")]
+        Type::TIMESTAMPTZ => Value::DateTimeUtc(FromSql::from_sql(member, raw)?),
+        #[cfg(all(feature = "time", not(feature = "chrono")))]
+        Type::TIMESTAMPTZ => Value::OffsetDateTime(FromSql::from_sql(member, raw)?),
+        _ if <String as FromSql>::accepts(member) => Value::String(String::from_sql(member, raw)?),
+        _ => return Err(format!("arrays of {} are not supported", member).into()),
+    };
+    Ok(value)
+}
+
+/// NUMERIC elements are read as the most precise decimal type enabled.
+#[cfg(feature = "bigdecimal")]
+fn numeric_from_sql(numeric: PgNumeric) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    Ok(Value::BigDecimal(numeric.0.parse()?))
+}
+
+#[cfg(all(feature = "rust_decimal", not(feature = "bigdecimal")))]
+fn numeric_from_sql(numeric: PgNumeric) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    Ok(Value::Decimal(numeric.0.parse()?))
+}
+
+#[cfg(not(any(feature = "rust_decimal", feature = "bigdecimal")))]
+fn numeric_from_sql(numeric: PgNumeric) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    Ok(Value::String(numeric.0))
+}
+
+/// Encode the elements of a [Value::Array] as an array of the parameter's element type.
+pub(crate) fn array_to_sql(values: &[Option<Value>], ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+    let member = match ty.kind() {
+        Kind::Array(member) => member,
+        _ => return Err(format!("an array can not be bound to a parameter of type {}", ty).into()),
+    };
+
+    let mut dimensions = Vec::new();
+    dimensions_of(values, &mut dimensions);
+    let mut elements = Vec::new();
+    flatten(values, &dimensions, &mut elements)?;
+
+    let dimensions = dimensions
+        .iter()
+        .map(|len| Ok(ArrayDimension { len: i32::try_from(*len)?, lower_bound: 1 }))
+        .collect::<Result<Vec<_>, Box<dyn Error + Sync + Send>>>()?;
+    protocol_array_to_sql(
+        dimensions,
+        member.oid(),
+        elements,
+        |element, buf| match element {
+            None => Ok(ProtocolIsNull::Yes),
+            Some(v) => match PgValue(v).to_sql_checked(member, buf)? {
+                IsNull::Yes => Ok(ProtocolIsNull::Yes),
+                IsNull::No => Ok(ProtocolIsNull::No),
+            },
+        },
+        out,
+    )?;
+    Ok(IsNull::No)
+}
+
+/// The length of each dimension, taken from the first element of each nested array.
+/// An empty array has no dimensions.
+fn dimensions_of(values: &[Option<Value>], dimensions: &mut Vec<usize>) {
+    if values.is_empty() {
+        return;
+    }
+
+    dimensions.push(values.len());
+    if let Some(Value::Array(inner)) = &values[0] {
+        dimensions_of(inner, dimensions);
+    }
+}
+
+fn flatten<'a>(values: &'a [Option<Value>], dimensions: &[usize], elements: &mut Vec<Option<&'a Value>>) -> Result<(), Box<dyn Error + Sync + Send>> {
+    if values.len() != dimensions.first().copied().unwrap_or(0) {
+        return Err("multi-dimensional arrays must have sub-arrays of the same length".into());
+    }
+
+    for value in values {
+        match (value, dimensions.len() > 1) {
+            (Some(Value::Array(inner)), true) => flatten(inner, &dimensions[1..], elements)?,
+            (_, true) => return Err("multi-dimensional arrays can not have NULL or scalar sub-arrays".into()),
+            (Some(Value::Array(_)), false) => {
+                return Err("multi-dimensional arrays must have sub-arrays of the same length".into())
+            },
+            (value, false) => elements.push(value.as_ref()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use tokio_postgres::types::{FromSql, Type};
+    use rsdbc_core::Value;
+    use crate::array::{array_to_sql, PgArray};
+
+    fn round_trip(array: &Value, ty: &Type) -> Vec<Option<Value>> {
+        let values = match array {
+            Value::Array(values) => values,
+            v => panic!("not an array {:?}", v),
+        };
+        let mut out = BytesMut::new();
+        array_to_sql(values, ty, &mut out).unwrap();
+        PgArray::from_sql(ty, &out).unwrap().0
+    }
+
+    fn ints(values: &[Option<Value>]) -> Vec<Option<i64>> {
+        values.iter()
+            .map(|v| match v {
+                Some(Value::Int64(v)) => Some(*v),
+                Some(Value::Int32(v)) => Some(i64::from(*v)),
+                None => None,
+                v => panic!("unexpected value {:?}", v),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arrays_should_round_trip() {
+        let array = Value::array(vec![Some(Value::Int32(1)), None, Some(Value::Int64(3))]);
+        assert_eq!(vec![Some(1), None, Some(3)], ints(&round_trip(&array, &Type::INT8_ARRAY)));
+
+        let array = Value::array(vec![Value::String("a".to_string()), Value::String("b".to_string())]);
+        let values = round_trip(&array, &Type::TEXT_ARRAY);
+        assert!(matches!(&values[1], Some(Value::String(s)) if s == "b"));
+
+        assert!(round_trip(&Value::array(Vec::<Value>::new()), &Type::INT4_ARRAY).is_empty());
+    }
+
+    #[test]
+    fn multi_dimensional_arrays_should_round_trip() {
+        // {{1,2,3},{4,5,6}}
+        let array = Value::array(vec![
+            Value::array(vec![Value::Int32(1), Value::Int32(2), Value::Int32(3)]),
+            Value::array(vec![Value::Int32(4), Value::Int32(5), Value::Int32(6)]),
+        ]);
+        let values = round_trip(&array, &Type::INT4_ARRAY);
+        assert_eq!(2, values.len());
+        match &values[1] {
+            Some(Value::Array(row)) => assert_eq!(vec![Some(4), Some(5), Some(6)], ints(row)),
+            v => panic!("unexpected value {:?}", v),
+        }
+    }
+
+    #[test]
+    fn invalid_arrays() {
+        let mut out = BytesMut::new();
+        let jagged = vec![
+            Some(Value::array(vec![Value::Int32(1)])),
+            Some(Value::array(vec![Value::Int32(1), Value::Int32(2)])),
+        ];
+        assert!(array_to_sql(&jagged, &Type::INT4_ARRAY, &mut out).is_err());
+        assert!(array_to_sql(&[Some(Value::Int32(1))], &Type::INT4, &mut out).is_err());
+        assert!(array_to_sql(&[Some(Value::String("a".to_string()))], &Type::INT4_ARRAY, &mut out).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+// https://www.postgresql.org/docs/current/lo-funcs.html
+//! Large objects which are stored apart from the rows referencing them by OID.
+//!
+//! Unlike a BYTEA, which is sent whole, a large object is read and written in chunks through the
+//! server side `lo_*` functions. The descriptors returned by `lo_open` are closed at the end of
+//! the transaction so large objects must be read within one, while writing needs no descriptor.
+//!
+//! Statements bind LOBs by writing them to large objects which are deleted once they have
+//! executed, see [LobParameters].
+
+use std::io::{self, Read};
+use futures::executor::block_on;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Client, Row};
+use rsdbc_core::lob::write_chunks;
+use rsdbc_core::sql::placeholders;
+use rsdbc_core::{Blob, Dialect, Lob, Result, Value};
+use crate::to_rsdbc_err;
+
+const INV_READ: i32 = 0x40000;
+
+const SEEK_SET: i32 = 0;
+const SEEK_END: i32 = 2;
+
+fn query_one(client: &Client, sql: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Row> {
+    block_on(client.query_one(sql, params)).map_err(|e| to_rsdbc_err(e).with_sql(sql))
+}
+
+fn open(client: &Client, oid: u32, mode: i32) -> Result<i32> {
+    Ok(query_one(client, "SELECT lo_open($1, $2)", &[&oid, &mode])?.get(0))
+}
+
+fn close(client: &Client, fd: i32) -> Result<()> {
+    query_one(client, "SELECT lo_close($1)", &[&fd]).map(|_| ())
+}
+
+/// Create a large object from the contents of `lob` and return its OID.
+///
+/// The chunks are written with `lo_put`, which needs no descriptor, so unlike reading this works
+/// outside of a transaction.
+pub(crate) fn create(client: &Client, lob: &Lob) -> Result<u32> {
+    let oid: u32 = query_one(client, "SELECT lo_create(0)", &[])?.get(0);
+    let mut offset: i64 = 0;
+    let written = write_chunks(lob, |chunk| {
+        query_one(client, "SELECT lo_put($1, $2, $3)", &[&oid, &offset, &chunk])?;
+        offset += chunk.len() as i64;
+        Ok(())
+    });
+    if let Err(e) = written {
+        // the error which stopped the write is more useful than one from unlinking
+        let _ = unlink(client, oid);
+        return Err(e);
+    }
+    Ok(oid)
+}
+
+/// Open the large object `oid` to read it in chunks
+pub(crate) fn open_blob(client: &Client, oid: u32) -> Result<Blob<'_>> {
+    let fd = open(client, oid, INV_READ)?;
+    let len: i64 = query_one(client, "SELECT lo_lseek64($1, 0, $2)", &[&fd, &SEEK_END])?.get(0);
+    query_one(client, "SELECT lo_lseek64($1, 0, $2)", &[&fd, &SEEK_SET])?;
+    Ok(Blob::new(LargeObjectReader::new(ClientDescriptor { client, fd }), u64::try_from(len).ok()))
+}
+
+pub(crate) fn unlink(client: &Client, oid: u32) -> Result<()> {
+    query_one(client, "SELECT lo_unlink($1)", &[&oid]).map(|_| ())
+}
+
+/// The LOBs bound to a statement, written to large objects which the statement reads with
+/// `lo_get` in place of their placeholders, rather than sent whole with the statement
+pub(crate) struct LobParameters {
+    /// The statement reading the LOBs from their large objects
+    pub(crate) sql: String,
+    /// The values to bind, with the OIDs of the large objects in place of the LOBs
+    pub(crate) values: Vec<Value>,
+    oids: Vec<u32>,
+}
+
+impl LobParameters {
+    /// Writes the LOBs among `params` to large objects, returns None if there are none
+    pub(crate) fn create(client: &Client, sql: &str, params: &[Value]) -> Result<Option<Self>> {
+        if !params.iter().any(|v| matches!(v, Value::Blob(_) | Value::Clob(_))) {
+            return Ok(None);
+        }
+
+        let mut lobs = Self {
+            sql: sql.to_string(),
+            values: Vec::with_capacity(params.len()),
+            oids: Vec::new(),
+        };
+        for value in params {
+            let lob = match value {
+                Value::Blob(lob) | Value::Clob(lob) => lob,
+                _ => {
+                    lobs.values.push(value.clone());
+                    continue;
+                },
+            };
+            match create(client, lob) {
+                Ok(oid) => {
+                    lobs.oids.push(oid);
+                    lobs.values.push(Value::UInt32(oid));
+                },
+                Err(e) => {
+                    // the error which stopped the write is more useful than one from unlinking
+                    let _ = lobs.unlink(client);
+                    return Err(e);
+                },
+            }
+        }
+        lobs.sql = read_lobs(sql, params);
+        Ok(Some(lobs))
+    }
+
+    /// Deletes the large objects once the statement has executed
+    pub(crate) fn unlink(&self, client: &Client) -> Result<()> {
+        self.oids.iter().try_for_each(|oid| unlink(client, *oid))
+    }
+}
+
+/// `sql` with the placeholders of the LOBs among `params` replaced by reading the large object
+/// whose OID is bound in their place
+fn read_lobs(sql: &str, params: &[Value]) -> String {
+    let mut rewritten = sql.to_string();
+    for placeholder in placeholders(Dialect::Postgres, sql).into_iter().rev() {
+        let oid = &sql[placeholder.range.clone()];
+        let read = match params.get(placeholder.index) {
+            Some(Value::Blob(_)) => format!("lo_get({}::oid)", oid),
+            Some(Value::Clob(_)) => format!("convert_from(lo_get({}::oid), 'UTF8')", oid),
+            _ => continue,
+        };
+        rewritten.replace_range(placeholder.range, &read);
+    }
+    rewritten
+}
+
+/// An open large object read by a [LargeObjectReader]
+trait Descriptor {
+    /// Reads at most `len` bytes, none once the end has been reached
+    fn read(&mut self, len: i32) -> Result<Vec<u8>>;
+
+    fn close(&mut self) -> Result<()>;
+}
+
+struct ClientDescriptor<'a> {
+    client: &'a Client,
+    fd: i32,
+}
+
+impl Descriptor for ClientDescriptor<'_> {
+    fn read(&mut self, len: i32) -> Result<Vec<u8>> {
+        Ok(query_one(self.client, "SELECT loread($1, $2)", &[&self.fd, &len])?.get(0))
+    }
+
+    fn close(&mut self) -> Result<()> {
+        close(self.client, self.fd)
+    }
+}
+
+/// Reads a large object with a `loread` per call
+struct LargeObjectReader<D: Descriptor> {
+    descriptor: D,
+    // false once the end has been reached and the descriptor closed
+    open: bool,
+}
+
+impl<D: Descriptor> LargeObjectReader<D> {
+    fn new(descriptor: D) -> Self {
+        Self {
+            descriptor,
+            open: true,
+        }
+    }
+}
+
+impl<D: Descriptor> Read for LargeObjectReader<D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.open {
+            return Ok(0);
+        }
+
+        let len = i32::try_from(buf.len()).unwrap_or(i32::MAX);
+        let chunk = self.descriptor.read(len).map_err(io::Error::other)?;
+        if chunk.is_empty() {
+            self.open = false;
+            self.descriptor.close().map_err(io::Error::other)?;
+            return Ok(0);
+        }
+
+        buf[..chunk.len()].copy_from_slice(&chunk);
+        Ok(chunk.len())
+    }
+}
+
+impl<D: Descriptor> Drop for LargeObjectReader<D> {
+    fn drop(&mut self) {
+        // the descriptor is closed at the end of the transaction regardless
+        if self.open {
+            self.open = false;
+            let _ = self.descriptor.close();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io::Read;
+    use std::rc::Rc;
+    use rsdbc_core::{Lob, Result, Value};
+    use crate::large_object::{read_lobs, Descriptor, LargeObjectReader};
+
+    struct FakeDescriptor {
+        value: Vec<u8>,
+        position: usize,
+        closes: Rc<Cell<u32>>,
+    }
+
+    impl Descriptor for FakeDescriptor {
+        fn read(&mut self, len: i32) -> Result<Vec<u8>> {
+            let end = (self.position + len as usize).min(self.value.len());
+            let chunk = self.value[self.position..end].to_vec();
+            self.position = end;
+            Ok(chunk)
+        }
+
+        fn close(&mut self) -> Result<()> {
+            self.closes.set(self.closes.get() + 1);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn large_objects_should_be_read_until_an_empty_chunk_and_closed_once() {
+        let closes = Rc::new(Cell::new(0));
+        let mut reader = LargeObjectReader::new(FakeDescriptor { value: (0..10).collect(), position: 0, closes: closes.clone() });
+        let mut buf = [0; 4];
+        assert_eq!(4, reader.read(&mut buf).unwrap());
+        assert_eq!([0, 1, 2, 3], buf);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(vec![4, 5, 6, 7, 8, 9], rest);
+        assert_eq!(1, closes.get());
+        assert_eq!(0, reader.read(&mut buf).unwrap());
+
+        drop(reader);
+        assert_eq!(1, closes.get());
+        let reader = LargeObjectReader::new(FakeDescriptor { value: Vec::new(), position: 0, closes: closes.clone() });
+        drop(reader);
+        assert_eq!(2, closes.get());
+    }
+
+    #[test]
+    fn lob_placeholders_should_read_their_large_objects() {
+        let params = [Value::Int32(1), Value::blob(Lob::from(vec![1])), Value::clob("a".to_string())];
+        assert_eq!(
+            "INSERT INTO t VALUES ($1, lo_get($2::oid), convert_from(lo_get($3::oid), 'UTF8'), '$2') -- $3",
+            read_lobs("INSERT INTO t VALUES ($1, $2, $3, '$2') -- $3", &params)
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use tokio_postgres::Client;
+use tokio_postgres::types::Type;
+use rsdbc_core::{Column, Nullability, Result};
+use crate::to_rsdbc_err;
+use crate::types::to_rsdbc_type;
+
+const TABLE_COLUMNS_SQL: &str = "SELECT a.attrelid, a.attnum, n.nspname, c.relname, a.attname, a.attnotnull \
+    FROM pg_catalog.pg_attribute a \
+    JOIN pg_catalog.pg_class c ON c.oid = a.attrelid \
+    JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+    WHERE a.attrelid = ANY($1) AND a.attnum > 0";
+
+/// The table column a result column originates from.
+struct ColumnOrigin {
+    schema: String,
+    table: String,
+    column: String,
+    not_null: bool,
+}
+
+/// Build the metadata for the columns of a result.
+///
+/// The `RowDescription` only carries the table OID and attribute number of columns that
+/// originate from a table so the schema, table, column name and nullability are looked up
+/// from the catalog.
+pub(crate) async fn result_set_metadata(client: &Client, columns: &[tokio_postgres::Column]) -> Result<Vec<Column>> {
+    let mut table_oids: Vec<u32> = columns.iter().filter_map(|c| c.table_oid()).collect();
+    table_oids.sort_unstable();
+    table_oids.dedup();
+
+    let mut origins = HashMap::new();
+    if !table_oids.is_empty() {
+        for row in client.query(TABLE_COLUMNS_SQL, &[&table_oids]).await.map_err(to_rsdbc_err)? {
+            let key: (u32, i16) = (row.get(0), row.get(1));
+            origins.insert(key, ColumnOrigin {
+                schema: row.get(2),
+                table: row.get(3),
+                column: row.get(4),
+                not_null: row.get(5),
+            });
+        }
+    }
+
+    Ok(columns
+        .iter()
+        .map(|c| {
+            let origin = c.table_oid().zip(c.column_id()).and_then(|key| origins.get(&key));
+            to_column(c, origin)
+        })
+        .collect())
+}
+
+fn to_column(column: &tokio_postgres::Column, origin: Option<&ColumnOrigin>) -> Column {
+    let (precision, scale) = precision_and_scale(column.type_(), column.type_modifier());
+    let result = Column::new(column.name(), to_rsdbc_type(column.type_()))
+        .type_name(column.type_().name())
+        .native_type_id(Some(column.type_().oid()))
+        .precision(precision)
+        .scale(scale);
+
+    match origin {
+        Some(origin) => result
+            .schema_name(Some(origin.schema.clone()))
+            .table_name(Some(origin.table.clone()))
+            .base_column_name(Some(origin.column.clone()))
+            .nullability(if origin.not_null { Nullability::NonNull } else { Nullability::Nullable }),
+        None => result.read_only(true),
+    }
+}
+
+/// Derive the precision and scale from the type modifier, e.g. `numeric(10, 2)` or `varchar(20)`.
+/// A modifier of -1 means none was given.
+fn precision_and_scale(t: &Type, type_modifier: i32) -> (Option<u64>, Option<u64>) {
+    match *t {
+        Type::INT2 => (Some(5), Some(0)),
+        Type::INT4 | Type::OID => (Some(10), Some(0)),
+        Type::INT8 => (Some(19), Some(0)),
+        Type::NUMERIC if type_modifier >= 4 => {
+            let modifier = (type_modifier - 4) as u32;
+            (Some(u64::from(modifier >> 16)), Some(u64::from(modifier & 0xffff)))
+        },
+        Type::VARCHAR | Type::BPCHAR if type_modifier >= 4 => (Some((type_modifier - 4) as u64), None),
+        Type::BIT | Type::VARBIT if type_modifier >= 0 => (Some(type_modifier as u64), None),
+        _ => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_postgres::types::Type;
+    use crate::metadata::precision_and_scale;
+
+    #[test]
+    fn precision_and_scale_from_type_modifier() {
+        // numeric(10, 2)
+        assert_eq!((Some(10), Some(2)), precision_and_scale(&Type::NUMERIC, (10 << 16) + 2 + 4));
+        assert_eq!((None, None), precision_and_scale(&Type::NUMERIC, -1));
+        // varchar(20)
+        assert_eq!((Some(20), None), precision_and_scale(&Type::VARCHAR, 24));
+        assert_eq!((None, None), precision_and_scale(&Type::TEXT, -1));
+        assert_eq!((Some(10), Some(0)), precision_and_scale(&Type::INT4, -1));
+    }
+}
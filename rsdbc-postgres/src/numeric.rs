@@ -0,0 +1,195 @@
+// https://github.com/postgres/postgres/blob/master/src/backend/utils/adt/numeric.c
+//! NUMERIC in the binary format which, unlike a float, is exact.
+//!
+//! A NUMERIC is sent as base 10000 digits along with the weight of the first digit, the sign and
+//! the display scale, i.e. the number of decimal digits after the decimal point. Values are
+//! converted to and from their text representation so that any decimal type can be used.
+
+use std::error::Error;
+use bytes::{BufMut, BytesMut};
+use tokio_postgres::types::{FromSql, IsNull, ToSql, Type};
+
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+const DEC_DIGITS: usize = 4;
+
+/// The text representation of a NUMERIC, e.g. `-123.4500`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PgNumeric(pub(crate) String);
+
+impl<'a> FromSql<'a> for PgNumeric {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        decode(raw).map(PgNumeric)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+impl ToSql for PgNumeric {
+    fn to_sql(&self, _ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        encode(&self.0, out)?;
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+
+    tokio_postgres::types::to_sql_checked!();
+}
+
+fn read_u16(raw: &[u8], offset: usize) -> Result<u16, Box<dyn Error + Sync + Send>> {
+    raw.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| "invalid NUMERIC, not enough bytes".into())
+}
+
+fn decode(raw: &[u8]) -> Result<String, Box<dyn Error + Sync + Send>> {
+    let ndigits = read_u16(raw, 0)? as usize;
+    let weight = read_u16(raw, 2)? as i16 as i32;
+    let sign = read_u16(raw, 4)?;
+    let dscale = read_u16(raw, 6)? as usize;
+    let digits = (0..ndigits)
+        .map(|i| read_u16(raw, 8 + i * 2))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match sign {
+        NUMERIC_POS | NUMERIC_NEG => {},
+        NUMERIC_NAN => return Err("NaN can not be represented as a decimal".into()),
+        NUMERIC_PINF | NUMERIC_NINF => return Err("infinity can not be represented as a decimal".into()),
+        _ => return Err(format!("invalid NUMERIC sign {:#x}", sign).into()),
+    }
+
+    // the digit at index i has the weight `weight - i`
+    let digit = |weight_of_digit: i32| -> u16 {
+        usize::try_from(weight - weight_of_digit)
+            .ok()
+            .and_then(|i| digits.get(i).copied())
+            .unwrap_or(0)
+    };
+
+    let mut s = String::new();
+    if sign == NUMERIC_NEG && ndigits > 0 {
+        s.push('-');
+    }
+
+    if weight < 0 {
+        s.push('0');
+    } else {
+        s.push_str(&digit(weight).to_string());
+        for w in (0..weight).rev() {
+            s.push_str(&format!("{:04}", digit(w)));
+        }
+    }
+
+    if dscale > 0 {
+        let mut fraction = String::new();
+        let mut w = -1;
+        while fraction.len() < dscale {
+            fraction.push_str(&format!("{:04}", digit(w)));
+            w -= 1;
+        }
+        fraction.truncate(dscale);
+        s.push('.');
+        s.push_str(&fraction);
+    }
+
+    Ok(s)
+}
+
+fn encode(s: &str, out: &mut BytesMut) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let invalid = || -> Box<dyn Error + Sync + Send> { format!("invalid decimal {}", s).into() };
+
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if integer.is_empty() && fraction.is_empty() || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let dscale = u16::try_from(fraction.len()).map_err(|_| invalid())?;
+
+    // align both parts on base 10000 digits
+    let integer = integer.trim_start_matches('0');
+    let integer_pad = (DEC_DIGITS - integer.len() % DEC_DIGITS) % DEC_DIGITS;
+    let fraction_pad = (DEC_DIGITS - fraction.len() % DEC_DIGITS) % DEC_DIGITS;
+    let aligned: Vec<u8> = std::iter::repeat_n(b'0', integer_pad)
+        .chain(integer.bytes())
+        .chain(fraction.bytes())
+        .chain(std::iter::repeat_n(b'0', fraction_pad))
+        .collect();
+    let mut digits: Vec<u16> = aligned
+        .chunks(DEC_DIGITS)
+        .map(|chunk| chunk.iter().fold(0, |acc, b| acc * 10 + u16::from(b - b'0')))
+        .collect();
+    let mut weight = ((integer_pad + integer.len()) / DEC_DIGITS) as i32 - 1;
+
+    // leading and trailing zero digits are not sent
+    let leading = digits.iter().take_while(|d| **d == 0).count();
+    digits.drain(..leading);
+    weight -= leading as i32;
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+
+    let (weight, sign) = match digits.is_empty() {
+        true => (0, NUMERIC_POS),
+        false if negative => (weight, NUMERIC_NEG),
+        false => (weight, NUMERIC_POS),
+    };
+    let ndigits = u16::try_from(digits.len()).map_err(|_| invalid())?;
+    let weight = i16::try_from(weight).map_err(|_| invalid())?;
+
+    out.put_u16(ndigits);
+    out.put_i16(weight);
+    out.put_u16(sign);
+    out.put_u16(dscale);
+    for digit in digits {
+        out.put_u16(digit);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use crate::numeric::{decode, encode};
+
+    fn round_trip(s: &str) -> String {
+        let mut out = BytesMut::new();
+        encode(s, &mut out).unwrap();
+        decode(&out).unwrap()
+    }
+
+    #[test]
+    fn numeric_should_round_trip() {
+        for s in ["0", "1", "-1", "10000", "12345678901234567890.1230", "0.0001", "-0.00012", "100.5", "99999999.99999999"] {
+            assert_eq!(s, round_trip(s));
+        }
+        assert_eq!("0.00", round_trip("-0.00"));
+        assert_eq!("1.50", round_trip("+001.50"));
+    }
+
+    #[test]
+    fn numeric_should_be_encoded_in_base_10000() {
+        // SELECT numeric_send(12345.678)
+        let mut out = BytesMut::new();
+        encode("12345.678", &mut out).unwrap();
+        assert_eq!(&[0, 3, 0, 1, 0, 0, 0, 3, 0, 1, 0x09, 0x29, 0x1a, 0x7c][..], &out[..]);
+    }
+
+    #[test]
+    fn invalid_numerics() {
+        assert!(encode("1e10", &mut BytesMut::new()).is_err());
+        assert!(encode("", &mut BytesMut::new()).is_err());
+        assert!(encode(".", &mut BytesMut::new()).is_err());
+        // NaN
+        assert!(decode(&[0, 0, 0, 0, 0xC0, 0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,600 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::io;
+use std::pin::Pin;
+use bytes::{BufMut, BytesMut};
+use futures::executor::block_on;
+use futures::StreamExt;
+use tokio_postgres::{Client, Row, RowStream};
+use tokio_postgres::types::{FromSql, IsNull, Kind, ToSql, Type};
+use rsdbc_core::connection::{SQLResult, Statement};
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::call::CallParameters;
+use rsdbc_core::{Column, Lob, OutParameterValues, ParameterMode, Result, ResultSet, ResultSetMetaData, RowMetadata, RsdbcType, Segment, Value};
+use crate::array::{array_to_sql, PgArray};
+use crate::large_object::LobParameters;
+use crate::metadata::result_set_metadata;
+use crate::numeric::PgNumeric;
+use crate::to_rsdbc_err;
+
+// TODO: Statement is synchronous while tokio_postgres is async. revisit once Statement is async
+pub struct PostgresqlStatement<'conn> {
+    client: &'conn Client,
+    statement: tokio_postgres::Statement,
+    sql: String,
+    call: CallParameters,
+}
+
+impl<'conn> PostgresqlStatement<'conn> {
+    pub(crate) fn prepare(client: &'conn Client, sql: &str) -> Result<Self> {
+        let statement = block_on(client.prepare(sql)).map_err(|e| to_rsdbc_err(e).with_sql(sql))?;
+        Ok(Self {
+            client,
+            statement,
+            sql: sql.to_string(),
+            call: CallParameters::new(),
+        })
+    }
+
+    /// Executes the statement with `params` through `exec`. LOBs are written to large objects
+    /// first, see [LobParameters], which are returned for the caller to unlink once the
+    /// statement is done with them.
+    fn exec<T>(
+        &self,
+        params: &[Value],
+        exec: impl FnOnce(&tokio_postgres::Statement, &[&(dyn ToSql + Sync)]) -> Result<T>,
+    ) -> Result<(T, Option<LobParameters>)> {
+        let lobs = LobParameters::create(self.client, &self.sql, params).map_err(|e| e.with_sql(&self.sql))?;
+        let result = match &lobs {
+            None => {
+                let params: Vec<PgValue> = params.iter().map(PgValue).collect();
+                exec(&self.statement, &to_sql_params(&params))
+            },
+            Some(lobs) => {
+                let values: Vec<PgValue> = lobs.values.iter().map(PgValue).collect();
+                block_on(self.client.prepare(&lobs.sql))
+                    .map_err(to_rsdbc_err)
+                    .and_then(|statement| exec(&statement, &to_sql_params(&values)))
+            },
+        };
+        match result {
+            Ok(result) => Ok((result, lobs)),
+            Err(e) => {
+                // when the statement fails in a transaction its rollback deletes the large objects
+                // instead, and the error which stopped the statement is more useful
+                if let Some(lobs) = lobs {
+                    let _ = lobs.unlink(self.client);
+                }
+                Err(e.with_sql(&self.sql))
+            },
+        }
+    }
+}
+
+impl Statement<'_> for PostgresqlStatement<'_> {
+    // values are bound by passing them to execute_query and execute_update, so bindings made
+    // through the builder methods are ignored and execute reports them as unsupported
+    fn add(&mut self) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_index<T>(&mut self, _index: u32, _value: T) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_name<T>(&mut self, _name: &str, _value: T) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_null_index(&mut self, _index: u32) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn bind_null_name(&mut self, _name: &str) -> &mut Self where Self: Sized {
+        self
+    }
+
+    fn execute<T: SQLResult>(&self) -> Result<T> where Self: Sized {
+        Err(RsdbcErrors::Unsupported("execute, use execute_query or execute_update".to_string()))
+    }
+
+    fn execute_query(&mut self, params: &[Value]) -> Result<Box<dyn ResultSet + '_>> {
+        // the client can't run another statement until the rows have been read, so the column
+        // types are named first
+        let columns = block_on(result_set_metadata(self.client, self.statement.columns()))?;
+        let (rows, lobs) = self.exec(params, |statement, params| {
+            block_on(self.client.query_raw(statement, params.iter().copied())).map_err(to_rsdbc_err)
+        })?;
+        Ok(Box::new(PostgresqlResultSet::new(self.client, rows, columns, lobs)))
+    }
+
+    fn execute_update(&mut self, params: &[Value]) -> Result<u64> {
+        let (rows, lobs) = self.exec(params, |statement, params| {
+            block_on(self.client.execute(statement, params)).map_err(to_rsdbc_err)
+        })?;
+        if let Some(lobs) = lobs {
+            lobs.unlink(self.client)?;
+        }
+        Ok(rows)
+    }
+
+    fn bind_out(&mut self, index: u32, r#type: RsdbcType) -> Result<()> {
+        self.call.register(index, ParameterMode::Out, r#type);
+        Ok(())
+    }
+
+    fn bind_inout(&mut self, index: u32, r#type: RsdbcType) -> Result<()> {
+        self.call.register(index, ParameterMode::InOut, r#type);
+        Ok(())
+    }
+
+    /// A procedure's OUT arguments are passed as NULL and the CALL returns the OUT and INOUT
+    /// parameters as a single row, in the order they are declared.
+    fn execute_call(&mut self, params: &[Value]) -> Result<Vec<Segment<'_>>> {
+        let params: Vec<Option<PgValue>> = self.call
+            .arrange(self.statement.params().len() as u32, params)?
+            .into_iter()
+            .map(|v| v.map(PgValue))
+            .collect();
+        let sql_params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|p| p as &(dyn ToSql + Sync)).collect();
+        let columns = block_on(result_set_metadata(self.client, self.statement.columns()))?;
+        if columns.is_empty() {
+            block_on(self.client.execute(&self.statement, &sql_params)).map_err(|e| to_rsdbc_err(e).with_sql(&self.sql))?;
+            return Ok(Vec::new());
+        }
+
+        let rows = block_on(self.client.query_raw(&self.statement, sql_params.iter().copied()))
+            .map_err(|e| to_rsdbc_err(e).with_sql(&self.sql))?;
+        let mut rs = PostgresqlResultSet::new(self.client, rows, columns.clone(), None);
+        if self.call.is_empty() {
+            return Ok(vec![Segment::Rows(Box::new(rs))]);
+        }
+
+        let outputs: Vec<_> = self.call.outputs().collect();
+        if outputs.len() != columns.len() {
+            return Err(RsdbcErrors::IllegalArgument(format!(
+                "{} OUT and INOUT parameters are registered but the call returned {}", outputs.len(), columns.len()
+            )));
+        }
+        if !rs.next() {
+            return Err(RsdbcErrors::General("the call did not return its OUT parameters".to_string()));
+        }
+        let rs: &dyn ResultSet = &rs;
+        let values = outputs
+            .iter()
+            .enumerate()
+            .map(|(i, (_, _, r#type))| rs.get_value(i as u64, *r#type))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(vec![Segment::OutParameters(Box::new(OutParameterValues::new(columns, values)))])
+    }
+}
+
+fn to_sql_params<'a>(params: &'a [PgValue<'a>]) -> Vec<&'a (dyn ToSql + Sync)> {
+    params.iter().map(|p| p as &(dyn ToSql + Sync)).collect()
+}
+
+/// Encodes a bound [Value] as the type postgres inferred for the parameter.
+///
+/// Integers are widened or narrowed to the parameter type, other values must match it.
+#[derive(Debug)]
+pub(crate) struct PgValue<'a>(pub(crate) &'a Value);
+
+impl ToSql for PgValue<'_> {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.to_sql_checked(ty, out)
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        // depends on the value, see to_sql_checked
+        true
+    }
+
+    fn to_sql_checked(&self, ty: &Type, out: &mut BytesMut) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self.0 {
+            Value::Bool(v) => v.to_sql_checked(ty, out),
+            Value::Int32(v) => integer_to_sql(i64::from(*v), ty, out),
+            Value::UInt32(v) if *ty == Type::OID => v.to_sql_checked(ty, out),
+            Value::UInt32(v) => integer_to_sql(i64::from(*v), ty, out),
+            Value::Int64(v) => integer_to_sql(*v, ty, out),
+            Value::Double(v) if *ty == Type::FLOAT4 => (*v as f32).to_sql(ty, out),
+            Value::Double(v) => v.to_sql_checked(ty, out),
+            Value::String(v) => v.to_sql_checked(ty, out),
+            Value::Bytes(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "chrono")]
+            Value::NaiveDate(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "chrono")]
+            Value::NaiveTime(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "chrono")]
+            Value::NaiveDateTime(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "chrono")]
+            Value::DateTimeUtc(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "chrono")]
+            Value::DateTimeFixedOffset(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "time")]
+            Value::OffsetDateTime(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "rust_decimal")]
+            Value::Decimal(v) => PgNumeric(v.to_string()).to_sql_checked(ty, out),
+            #[cfg(feature = "bigdecimal")]
+            Value::BigDecimal(v) => PgNumeric(v.to_string()).to_sql_checked(ty, out),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(v) => v.to_sql_checked(ty, out),
+            #[cfg(feature = "json")]
+            Value::Json(v) => v.to_sql_checked(ty, out),
+            Value::Array(v) => array_to_sql(v, ty, out),
+            Value::Blob(v) => lob_to_sql(v, ty, out),
+            Value::Clob(v) => lob_to_sql(v, ty, out),
+        }
+    }
+}
+
+/// Copy a [Lob] into the message sent to postgres, whole. Statements bind LOBs through large
+/// objects instead, so this is only used to call procedures.
+fn lob_to_sql(lob: &Lob, ty: &Type, out: &mut BytesMut) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+    if *ty != Type::BYTEA && !<String as ToSql>::accepts(ty) {
+        return Err(format!("a large object can not be bound to a parameter of type {}", ty).into());
+    }
+    io::copy(&mut lob.take()?, &mut out.writer())?;
+    Ok(IsNull::No)
+}
+
+fn integer_to_sql(v: i64, ty: &Type, out: &mut BytesMut) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+    match *ty {
+        Type::INT2 => i16::try_from(v)?.to_sql(ty, out),
+        Type::INT4 => i32::try_from(v)?.to_sql(ty, out),
+        Type::NUMERIC => PgNumeric(v.to_string()).to_sql(ty, out),
+        _ => v.to_sql_checked(ty, out),
+    }
+}
+
+/// The rows of a query, received from the server as they are read
+pub(crate) struct PostgresqlResultSet<'conn> {
+    client: &'conn Client,
+    // None once every row has been received
+    rows: Option<Pin<Box<RowStream>>>,
+    row: Option<Row>,
+    columns: Vec<Column>,
+    // the large objects bound to the query, unlinked once its rows have been received
+    lobs: Option<LobParameters>,
+    // the error which stopped the last call to next, returned by the next getter as next can only
+    // report whether there is a row
+    error: Cell<Option<RsdbcErrors>>,
+    failed: bool,
+}
+
+impl<'conn> PostgresqlResultSet<'conn> {
+    fn new(client: &'conn Client, rows: RowStream, columns: Vec<Column>, lobs: Option<LobParameters>) -> Self {
+        Self {
+            client,
+            rows: Some(Box::pin(rows)),
+            row: None,
+            columns,
+            lobs,
+            error: Cell::new(None),
+            failed: false,
+        }
+    }
+
+    fn next_row(&mut self) -> Result<Option<Row>> {
+        let rows = match &mut self.rows {
+            Some(rows) => rows,
+            None => return Ok(None),
+        };
+        match block_on(rows.next()).transpose().map_err(to_rsdbc_err)? {
+            Some(row) => Ok(Some(row)),
+            None => self.unlink_lobs().map(|_| None),
+        }
+    }
+
+    /// Unlinks the large objects the query read. The rows still to be received must be
+    /// discarded first, as the client runs statements in turn.
+    fn unlink_lobs(&mut self) -> Result<()> {
+        self.rows = None;
+        match self.lobs.take() {
+            Some(lobs) => lobs.unlink(self.client),
+            None => Ok(()),
+        }
+    }
+
+    fn row(&self) -> Result<&Row> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.row
+            .as_ref()
+            .ok_or_else(|| RsdbcErrors::General("result set is not positioned on a row".to_string()))
+    }
+
+    fn column_type(&self, i: u64) -> Result<&Type> {
+        self.row()?
+            .columns()
+            .get(i as usize)
+            .map(|c| c.type_())
+            .ok_or_else(|| RsdbcErrors::General(format!("column index {} out of range", i)))
+    }
+
+    fn get<'a, T: FromSql<'a>>(&'a self, i: u64) -> Result<Option<T>> {
+        self.row()?.try_get::<_, Option<T>>(i as usize).map_err(to_rsdbc_err)
+    }
+
+    fn get_integer(&self, i: u64) -> Result<Option<i64>> {
+        match *self.column_type(i)? {
+            Type::INT2 => Ok(self.get::<i16>(i)?.map(i64::from)),
+            Type::INT4 => Ok(self.get::<i32>(i)?.map(i64::from)),
+            Type::OID => Ok(self.get::<u32>(i)?.map(i64::from)),
+            Type::CHAR => Ok(self.get::<i8>(i)?.map(i64::from)),
+            _ => self.get::<i64>(i),
+        }
+    }
+}
+
+fn out_of_range<E: std::fmt::Display>(e: E) -> RsdbcErrors {
+    RsdbcErrors::General(e.to_string())
+}
+
+impl Drop for PostgresqlResultSet<'_> {
+    fn drop(&mut self) {
+        let _ = self.unlink_lobs();
+    }
+}
+
+impl ResultSet for PostgresqlResultSet<'_> {
+    fn meta_data(&self) -> Result<Box<dyn ResultSetMetaData>> {
+        Ok(Box::new(self.columns.clone()))
+    }
+
+    fn row_metadata(&self) -> Result<Box<dyn RowMetadata>> {
+        Ok(Box::new(self.columns.clone()))
+    }
+
+    /// A failure to receive the next row is reported by the getters, so next returns true once
+    /// more when it fails and false from then on rather than silently ending the result set.
+    fn next(&mut self) -> bool {
+        if self.failed {
+            self.row = None;
+            return false;
+        }
+        self.row = match self.next_row() {
+            Ok(row) => row,
+            Err(e) => {
+                self.failed = true;
+                self.error.set(Some(e));
+                None
+            },
+        };
+        self.row.is_some() || self.failed
+    }
+
+    fn get_bool(&self, i: u64) -> Result<Option<bool>> {
+        self.get(i)
+    }
+
+    fn get_i8(&self, i: u64) -> Result<Option<i8>> {
+        self.get_integer(i)?.map(i8::try_from).transpose().map_err(out_of_range)
+    }
+
+    fn get_i16(&self, i: u64) -> Result<Option<i16>> {
+        self.get_integer(i)?.map(i16::try_from).transpose().map_err(out_of_range)
+    }
+
+    fn get_i32(&self, i: u64) -> Result<Option<i32>> {
+        self.get_integer(i)?.map(i32::try_from).transpose().map_err(out_of_range)
+    }
+
+    fn get_i64(&self, i: u64) -> Result<Option<i64>> {
+        self.get_integer(i)
+    }
+
+    fn get_f32(&self, i: u64) -> Result<Option<f32>> {
+        Ok(self.get_f64(i)?.map(|v| v as f32))
+    }
+
+    fn get_f64(&self, i: u64) -> Result<Option<f64>> {
+        match *self.column_type(i)? {
+            Type::FLOAT4 => Ok(self.get::<f32>(i)?.map(f64::from)),
+            Type::FLOAT8 => self.get(i),
+            Type::NUMERIC => self.get::<PgNumeric>(i)?
+                .map(|n| n.0.parse().map_err(out_of_range))
+                .transpose(),
+            _ => Ok(self.get_integer(i)?.map(|v| v as f64)),
+        }
+    }
+
+    fn get_string(&self, i: u64) -> Result<Option<String>> {
+        let ty = self.column_type(i)?;
+        if <String as FromSql>::accepts(ty) {
+            return self.get(i);
+        }
+
+        match *ty {
+            Type::BOOL => Ok(self.get::<bool>(i)?.map(|v| v.to_string())),
+            Type::NUMERIC => Ok(self.get::<PgNumeric>(i)?.map(|n| n.0)),
+            Type::FLOAT4 | Type::FLOAT8 => Ok(self.get_f64(i)?.map(|v| v.to_string())),
+            _ => Ok(self.get_integer(i)?.map(|v| v.to_string())),
+        }
+    }
+
+    fn get_bytes(&self, i: u64) -> Result<Option<Vec<u8>>> {
+        if *self.column_type(i)? == Type::BYTEA {
+            return self.get(i);
+        }
+        Ok(self.get_string(i)?.map(String::into_bytes))
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_date(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveDate>> {
+        self.get(i)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_time(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveTime>> {
+        self.get(i)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_date_time(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveDateTime>> {
+        self.get(i)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_date_time_utc(&self, i: u64) -> Result<Option<rsdbc_core::chrono::DateTime<rsdbc_core::chrono::Utc>>> {
+        use rsdbc_core::chrono::{TimeZone, Utc};
+
+        // timestamps without time zone are taken to be UTC
+        if *self.column_type(i)? == Type::TIMESTAMP {
+            return Ok(self.get_naive_date_time(i)?.map(|dt| Utc.from_utc_datetime(&dt)));
+        }
+        self.get(i)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_date_time_fixed_offset(&self, i: u64) -> Result<Option<rsdbc_core::chrono::DateTime<rsdbc_core::chrono::FixedOffset>>> {
+        use rsdbc_core::chrono::FixedOffset;
+
+        let utc = FixedOffset::east_opt(0).expect("UTC is a valid offset");
+        Ok(self.get_date_time_utc(i)?.map(|dt| dt.with_timezone(&utc)))
+    }
+
+    fn get_array(&self, i: u64) -> Result<Option<Vec<Option<Value>>>> {
+        let ty = self.column_type(i)?;
+        if let Kind::Array(_) = ty.kind() {
+            return Ok(self.get::<PgArray>(i)?.map(|a| a.0));
+        }
+
+        #[cfg(feature = "json")]
+        if let Type::JSON | Type::JSONB = *ty {
+            return self.get_json_value(i)?.map(rsdbc_core::json::from_json_array).transpose();
+        }
+        Err(RsdbcErrors::General(format!("column {} of type {} is not an array", i, ty)))
+    }
+
+    #[cfg(feature = "uuid")]
+    fn get_uuid(&self, i: u64) -> Result<Option<rsdbc_core::uuid::Uuid>> {
+        if *self.column_type(i)? == Type::UUID {
+            return self.get(i);
+        }
+        self.get_string(i)?
+            .map(|s| rsdbc_core::uuid::Uuid::parse_str(&s).map_err(out_of_range))
+            .transpose()
+    }
+
+    #[cfg(feature = "json")]
+    fn get_json_value(&self, i: u64) -> Result<Option<rsdbc_core::serde_json::Value>> {
+        if let Type::JSON | Type::JSONB = *self.column_type(i)? {
+            return self.get(i);
+        }
+        self.get_string(i)?
+            .map(|s| rsdbc_core::serde_json::from_str(&s).map_err(out_of_range))
+            .transpose()
+    }
+
+    #[cfg(feature = "time")]
+    fn get_offset_date_time(&self, i: u64) -> Result<Option<rsdbc_core::time::OffsetDateTime>> {
+        // timestamps without time zone are taken to be UTC
+        if *self.column_type(i)? == Type::TIMESTAMP {
+            return Ok(self.get::<rsdbc_core::time::PrimitiveDateTime>(i)?.map(|dt| dt.assume_utc()));
+        }
+        self.get(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use tokio_postgres::types::{FromSql, ToSql, Type};
+    use rsdbc_core::{Lob, Value};
+    use crate::statement::PgValue;
+
+    fn encode(value: Value, ty: &Type) -> Vec<u8> {
+        let mut out = BytesMut::new();
+        PgValue(&value).to_sql_checked(ty, &mut out).unwrap();
+        out.to_vec()
+    }
+
+    #[test]
+    fn integers_should_be_encoded_as_the_parameter_type() {
+        assert_eq!(7i16, i16::from_sql(&Type::INT2, &encode(Value::Int32(7), &Type::INT2)).unwrap());
+        assert_eq!(7i64, i64::from_sql(&Type::INT8, &encode(Value::Int32(7), &Type::INT8)).unwrap());
+        assert_eq!(7u32, u32::from_sql(&Type::OID, &encode(Value::UInt32(7), &Type::OID)).unwrap());
+
+        let mut out = BytesMut::new();
+        assert!(PgValue(&Value::Int32(70_000)).to_sql_checked(&Type::INT2, &mut out).is_err());
+        assert!(PgValue(&Value::String("a".to_string())).to_sql_checked(&Type::INT4, &mut out).is_err());
+    }
+
+    #[test]
+    fn lobs_should_be_streamed_into_the_parameter() {
+        let chunks = vec![Ok(vec![1, 2]), Ok(vec![3])];
+        let blob = Value::Blob(Lob::from_stream(futures::stream::iter(chunks)));
+        assert_eq!(vec![1, 2, 3], Vec::<u8>::from_sql(&Type::BYTEA, &encode(blob, &Type::BYTEA)).unwrap());
+        assert_eq!("déjà vu", String::from_sql(&Type::TEXT, &encode(Value::clob("déjà vu".to_string()), &Type::TEXT)).unwrap());
+
+        let mut out = BytesMut::new();
+        assert!(PgValue(&Value::blob(vec![1])).to_sql_checked(&Type::INT4, &mut out).is_err());
+
+        // a lob is consumed when it is bound
+        let lob = Lob::from(vec![1]);
+        encode(Value::Blob(lob.clone()), &Type::BYTEA);
+        assert!(PgValue(&Value::Blob(lob)).to_sql_checked(&Type::BYTEA, &mut out).is_err());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_values_should_round_trip() {
+        use rsdbc_core::chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+        let date_time = NaiveDate::from_ymd_opt(2021, 12, 31).unwrap().and_hms_micro_opt(23, 59, 59, 1).unwrap();
+        assert_eq!(date_time.date(), NaiveDate::from_sql(&Type::DATE, &encode(Value::NaiveDate(date_time.date()), &Type::DATE)).unwrap());
+        assert_eq!(date_time, FromSql::from_sql(&Type::TIMESTAMP, &encode(Value::NaiveDateTime(date_time), &Type::TIMESTAMP)).unwrap());
+
+        let offset = FixedOffset::east_opt(3600).unwrap().from_local_datetime(&date_time).unwrap();
+        let utc: rsdbc_core::chrono::DateTime<Utc> = FromSql::from_sql(&Type::TIMESTAMPTZ, &encode(Value::DateTimeFixedOffset(offset), &Type::TIMESTAMPTZ)).unwrap();
+        assert_eq!(offset, utc);
+    }
+
+    #[cfg(all(feature = "rust_decimal", feature = "bigdecimal"))]
+    #[test]
+    fn decimals_should_be_encoded_as_numeric() {
+        use std::str::FromStr;
+        use rsdbc_core::bigdecimal::BigDecimal;
+        use rsdbc_core::rust_decimal::Decimal;
+        use crate::numeric::PgNumeric;
+
+        let decimal = Decimal::from_str("-1234.5600").unwrap();
+        let decoded = PgNumeric::from_sql(&Type::NUMERIC, &encode(Value::Decimal(decimal), &Type::NUMERIC)).unwrap();
+        assert_eq!("-1234.5600", decoded.0);
+
+        let big = BigDecimal::from_str("123456789012345678901234567890.123456789").unwrap();
+        let decoded = PgNumeric::from_sql(&Type::NUMERIC, &encode(Value::BigDecimal(big.clone()), &Type::NUMERIC)).unwrap();
+        assert_eq!(big, BigDecimal::from_str(&decoded.0).unwrap());
+
+        let mut out = BytesMut::new();
+        assert!(PgValue(&Value::Decimal(decimal)).to_sql_checked(&Type::FLOAT8, &mut out).is_err());
+    }
+
+    #[cfg(all(feature = "uuid", feature = "json"))]
+    #[test]
+    fn uuid_and_json_values_should_round_trip() {
+        use rsdbc_core::serde_json;
+        use rsdbc_core::uuid::Uuid;
+
+        let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(id, Uuid::from_sql(&Type::UUID, &encode(Value::Uuid(id), &Type::UUID)).unwrap());
+
+        let payload = serde_json::json!({"amount": 10});
+        for ty in [Type::JSON, Type::JSONB] {
+            let decoded = serde_json::Value::from_sql(&ty, &encode(Value::Json(payload.clone()), &ty)).unwrap();
+            assert_eq!(payload, decoded);
+        }
+
+        let mut out = BytesMut::new();
+        assert!(PgValue(&Value::Uuid(id)).to_sql_checked(&Type::INT4, &mut out).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_values_should_round_trip() {
+        let date_time = rsdbc_core::time::OffsetDateTime::from_unix_timestamp(1_640_995_199).unwrap();
+        let decoded: rsdbc_core::time::OffsetDateTime = FromSql::from_sql(&Type::TIMESTAMPTZ, &encode(Value::OffsetDateTime(date_time), &Type::TIMESTAMPTZ)).unwrap();
+        assert_eq!(date_time, decoded);
+    }
+}
@@ -0,0 +1,122 @@
+use tokio_postgres::types::{Kind, Type};
+use rsdbc_core::RsdbcType;
+
+/// Map a postgres type to a generic type.
+///
+/// Arrays map to [RsdbcType::Collection], domains to the generic type of their base type and
+/// enums as well as types without a generic counterpart, e.g. `uuid` or `interval`, to
+/// [RsdbcType::Varchar] as they are read in their text representation.
+pub fn to_rsdbc_type(t: &Type) -> RsdbcType {
+    match t.kind() {
+        Kind::Array(_) => return RsdbcType::Collection,
+        Kind::Domain(base) => return to_rsdbc_type(base),
+        _ => {},
+    }
+
+    match *t {
+        Type::BOOL => RsdbcType::Boolean,
+        Type::CHAR | Type::BPCHAR => RsdbcType::Char,
+        Type::VARCHAR | Type::TEXT | Type::NAME => RsdbcType::Varchar,
+        Type::BYTEA => RsdbcType::Varbinary,
+        Type::INT2 => RsdbcType::Smallint,
+        Type::INT4 => RsdbcType::Integer,
+        Type::INT8 | Type::OID => RsdbcType::Bigint,
+        Type::NUMERIC => RsdbcType::Numeric,
+        Type::FLOAT4 => RsdbcType::Real,
+        Type::FLOAT8 => RsdbcType::Double,
+        Type::DATE => RsdbcType::Date,
+        Type::TIME => RsdbcType::Time,
+        Type::TIMETZ => RsdbcType::TimeWithTimeZone,
+        Type::TIMESTAMP => RsdbcType::Timestamp,
+        Type::TIMESTAMPTZ => RsdbcType::TimestampWithTimeZone,
+        _ => RsdbcType::Varchar,
+    }
+}
+
+/// Map a generic type to the postgres type values of that type are stored as.
+///
+/// Returns [None] for [RsdbcType::Collection] as the array type depends on its element type.
+pub fn to_pg_type(t: RsdbcType) -> Option<Type> {
+    let pg_type = match t {
+        RsdbcType::Char | RsdbcType::Nchar => Type::BPCHAR,
+        RsdbcType::Varchar | RsdbcType::Nvarchar => Type::VARCHAR,
+        RsdbcType::Clob | RsdbcType::Nclob => Type::TEXT,
+        RsdbcType::Boolean => Type::BOOL,
+        RsdbcType::Binary | RsdbcType::Varbinary | RsdbcType::Blob => Type::BYTEA,
+        // there is no single byte integer
+        RsdbcType::Tinyint | RsdbcType::Smallint => Type::INT2,
+        RsdbcType::Integer => Type::INT4,
+        RsdbcType::Bigint => Type::INT8,
+        RsdbcType::Numeric | RsdbcType::Decimal => Type::NUMERIC,
+        RsdbcType::Float | RsdbcType::Double => Type::FLOAT8,
+        RsdbcType::Real => Type::FLOAT4,
+        RsdbcType::Date => Type::DATE,
+        RsdbcType::Time => Type::TIME,
+        RsdbcType::TimeWithTimeZone => Type::TIMETZ,
+        RsdbcType::Timestamp => Type::TIMESTAMP,
+        RsdbcType::TimestampWithTimeZone => Type::TIMESTAMPTZ,
+        RsdbcType::Collection => return None,
+    };
+    Some(pg_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_postgres::types::{Kind, Type};
+    use rsdbc_core::RsdbcType;
+    use crate::types::{to_pg_type, to_rsdbc_type};
+
+    #[test]
+    fn every_type_should_map_to_a_pg_type_and_back() {
+        let expected = [
+            (RsdbcType::Char, Some(Type::BPCHAR), RsdbcType::Char),
+            (RsdbcType::Varchar, Some(Type::VARCHAR), RsdbcType::Varchar),
+            (RsdbcType::Nchar, Some(Type::BPCHAR), RsdbcType::Char),
+            (RsdbcType::Nvarchar, Some(Type::VARCHAR), RsdbcType::Varchar),
+            (RsdbcType::Clob, Some(Type::TEXT), RsdbcType::Varchar),
+            (RsdbcType::Nclob, Some(Type::TEXT), RsdbcType::Varchar),
+            (RsdbcType::Boolean, Some(Type::BOOL), RsdbcType::Boolean),
+            (RsdbcType::Binary, Some(Type::BYTEA), RsdbcType::Varbinary),
+            (RsdbcType::Varbinary, Some(Type::BYTEA), RsdbcType::Varbinary),
+            (RsdbcType::Blob, Some(Type::BYTEA), RsdbcType::Varbinary),
+            (RsdbcType::Integer, Some(Type::INT4), RsdbcType::Integer),
+            (RsdbcType::Tinyint, Some(Type::INT2), RsdbcType::Smallint),
+            (RsdbcType::Smallint, Some(Type::INT2), RsdbcType::Smallint),
+            (RsdbcType::Bigint, Some(Type::INT8), RsdbcType::Bigint),
+            (RsdbcType::Numeric, Some(Type::NUMERIC), RsdbcType::Numeric),
+            (RsdbcType::Decimal, Some(Type::NUMERIC), RsdbcType::Numeric),
+            (RsdbcType::Float, Some(Type::FLOAT8), RsdbcType::Double),
+            (RsdbcType::Real, Some(Type::FLOAT4), RsdbcType::Real),
+            (RsdbcType::Double, Some(Type::FLOAT8), RsdbcType::Double),
+            (RsdbcType::Date, Some(Type::DATE), RsdbcType::Date),
+            (RsdbcType::Time, Some(Type::TIME), RsdbcType::Time),
+            (RsdbcType::TimeWithTimeZone, Some(Type::TIMETZ), RsdbcType::TimeWithTimeZone),
+            (RsdbcType::Timestamp, Some(Type::TIMESTAMP), RsdbcType::Timestamp),
+            (RsdbcType::TimestampWithTimeZone, Some(Type::TIMESTAMPTZ), RsdbcType::TimestampWithTimeZone),
+            (RsdbcType::Collection, None, RsdbcType::Collection),
+        ];
+        assert_eq!(RsdbcType::ALL.len(), expected.len());
+
+        for (t, (expected_type, pg_type, back)) in RsdbcType::ALL.iter().zip(expected) {
+            assert_eq!(*t, expected_type);
+            assert_eq!(pg_type, to_pg_type(*t), "{:?}", t);
+            if let Some(pg_type) = pg_type {
+                assert_eq!(back, to_rsdbc_type(&pg_type), "{:?}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn pg_types_should_map_to_generic_types() {
+        assert_eq!(RsdbcType::Collection, to_rsdbc_type(&Type::INT4_ARRAY));
+        assert_eq!(RsdbcType::Collection, to_rsdbc_type(&Type::TEXT_ARRAY));
+        assert_eq!(RsdbcType::Bigint, to_rsdbc_type(&Type::OID));
+        assert_eq!(RsdbcType::Varchar, to_rsdbc_type(&Type::UUID));
+        assert_eq!(RsdbcType::Varchar, to_rsdbc_type(&Type::INTERVAL));
+
+        let domain = Type::new("positive_int".to_string(), 16_385, Kind::Domain(Type::INT4), "public".to_string());
+        assert_eq!(RsdbcType::Integer, to_rsdbc_type(&domain));
+        let enumeration = Type::new("mood".to_string(), 16_386, Kind::Enum(vec!["sad".to_string()]), "public".to_string());
+        assert_eq!(RsdbcType::Varchar, to_rsdbc_type(&enumeration));
+    }
+}
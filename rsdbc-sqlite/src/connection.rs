@@ -1,73 +1,10 @@
 use futures::future::BoxFuture;
-use rsdbc_core::connection::{Batch, Connection, ConnectionFactory, ConnectionFactoryMetadata, ConnectionFactoryOptions, ConnectionFactoryProvider, ConnectionMetadata, IsolationLevel, Statement, ValidationDepth};
+use rsdbc_core::connection::{Connection, ConnectionFactory, ConnectionFactoryMetadata, ConnectionFactoryOptions, ConnectionFactoryProvider, ConnectionMetadata};
 use rsdbc_core::error::RsdbcErrors;
 use rsdbc_core::OptionValue;
 use crate::error::SqliteRsdbcError;
 use crate::options::SqliteConnectOptions;
 
-pub struct SqliteConnection;
-impl Connection for SqliteConnection {
-    fn begin_transaction(&mut self) -> rsdbc_core::Result<()> {
-        todo!()
-    }
-
-    fn close(&mut self) -> rsdbc_core::Result<()> {
-        todo!()
-    }
-
-    fn commit_transaction(&mut self) {
-        todo!()
-    }
-
-    fn create_batch(&mut self) -> rsdbc_core::Result<Box<dyn Batch>> {
-        todo!()
-    }
-
-    fn create_savepoint(&mut self, name: &str) {
-        todo!()
-    }
-
-    fn create_statement(&mut self, sql: &str) -> rsdbc_core::Result<Box<dyn Statement<'_> + '_>> {
-        todo!()
-    }
-
-    fn is_auto_commit(&mut self) -> bool {
-        todo!()
-    }
-
-    fn metadata(&mut self) -> rsdbc_core::Result<Box<dyn ConnectionMetadata>> {
-        todo!()
-    }
-
-    fn transaction_isolation_level(&mut self) -> IsolationLevel {
-        todo!()
-    }
-
-    fn release_savepoint(&mut self, name: &str) {
-        todo!()
-    }
-
-    fn rollback_transaction(&mut self) {
-        todo!()
-    }
-
-    fn rollback_transaction_to_savepoint(&mut self, name: String) {
-        todo!()
-    }
-
-    fn auto_commit(&mut self, commit: bool) {
-        todo!()
-    }
-
-    fn set_transaction_isolation_level(&mut self, isolation_level: IsolationLevel) {
-        todo!()
-    }
-
-    fn validate(&mut self, depth: ValidationDepth) -> bool {
-        todo!()
-    }
-}
-
 pub struct SqliteConnectionMetadata {
 
 }
@@ -90,7 +27,7 @@ pub struct SqliteConnectionFactory {
 
 impl ConnectionFactory for SqliteConnectionFactory {
     fn connect(&self) -> BoxFuture<'_, rsdbc_core::Result<Box<dyn Connection>>> {
-        todo!()
+        self.configuration.connect()
     }
 
     fn get_metadata(&self) -> Box<dyn ConnectionFactoryMetadata> {
@@ -0,0 +1,169 @@
+// https://www.sqlite.org/lang_datefunc.html
+//! Date and time values which are stored as TEXT, REAL Julian day numbers or INTEGER Unix time,
+//! see [SqliteDateTimeStorage].
+
+use rusqlite::types::Value;
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::Result;
+use crate::invalid_column_type;
+use crate::options::SqliteDateTimeStorage;
+
+// Julian day number of 1970-01-01 00:00:00 UTC
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// A stored date and time value.
+pub(crate) enum Stored<'a> {
+    Text(&'a str),
+    /// seconds and nanoseconds since the Unix epoch
+    Unix(i64, u32),
+}
+
+/// Read a date and time value stored in any of the supported storage classes.
+pub(crate) fn stored(i: u64, value: &Value) -> Result<Option<Stored<'_>>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Text(s) => Ok(Some(Stored::Text(s))),
+        Value::Integer(seconds) => Ok(Some(Stored::Unix(*seconds, 0))),
+        Value::Real(day) => Ok(Some(from_julian_day(*day))),
+        v => Err(invalid_column_type(i, v)),
+    }
+}
+
+fn from_julian_day(day: f64) -> Stored<'static> {
+    // SQLite keeps julian day numbers to the millisecond
+    let millis = ((day - UNIX_EPOCH_JULIAN_DAY) * SECONDS_PER_DAY * 1000.0).round() as i64;
+    Stored::Unix(millis.div_euclid(1000), (millis.rem_euclid(1000) * 1_000_000) as u32)
+}
+
+fn to_julian_day(seconds: i64, nanos: u32) -> f64 {
+    (seconds as f64 + f64::from(nanos) / 1e9) / SECONDS_PER_DAY + UNIX_EPOCH_JULIAN_DAY
+}
+
+fn invalid_unix_time(seconds: i64) -> RsdbcErrors {
+    RsdbcErrors::General(format!("invalid unix time {}", seconds))
+}
+
+impl SqliteDateTimeStorage {
+    /// Store a point in time given as its text representation and Unix time.
+    fn store(self, text: String, seconds: i64, nanos: u32) -> Value {
+        match self {
+            SqliteDateTimeStorage::Text => Value::Text(text),
+            SqliteDateTimeStorage::Julian => Value::Real(to_julian_day(seconds, nanos)),
+            SqliteDateTimeStorage::Unix => Value::Integer(seconds),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_support {
+    use rsdbc_core::chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+    use rsdbc_core::datetime::{format_date_time, format_naive_date, format_naive_date_time, format_naive_time, parse_date_time, parse_naive_date_time, parse_naive_time};
+    use rsdbc_core::Result;
+    use rusqlite::types::Value;
+    use crate::options::SqliteDateTimeStorage;
+    use super::{invalid_unix_time, Stored};
+
+    fn from_unix_time(seconds: i64, nanos: u32) -> Result<DateTime<Utc>> {
+        Utc.timestamp_opt(seconds, nanos).single().ok_or_else(|| invalid_unix_time(seconds))
+    }
+
+    /// Dates are read from timestamps as well, e.g. `2021-12-31 23:59:59`
+    pub(crate) fn to_naive_date(stored: Stored) -> Result<NaiveDate> {
+        to_naive_date_time(stored).map(|dt| dt.date())
+    }
+
+    pub(crate) fn to_naive_time(stored: Stored) -> Result<NaiveTime> {
+        match stored {
+            Stored::Text(s) => parse_naive_time(s).or_else(|e| parse_naive_date_time(s).map(|dt| dt.time()).map_err(|_| e)),
+            Stored::Unix(seconds, nanos) => from_unix_time(seconds, nanos).map(|dt| dt.time()),
+        }
+    }
+
+    pub(crate) fn to_naive_date_time(stored: Stored) -> Result<NaiveDateTime> {
+        match stored {
+            Stored::Text(s) => parse_naive_date_time(s),
+            Stored::Unix(seconds, nanos) => from_unix_time(seconds, nanos).map(|dt| dt.naive_utc()),
+        }
+    }
+
+    /// Values stored without an offset are UTC
+    pub(crate) fn to_date_time(stored: Stored) -> Result<DateTime<FixedOffset>> {
+        match stored {
+            Stored::Text(s) => parse_date_time(s),
+            Stored::Unix(seconds, nanos) => {
+                let utc = FixedOffset::east_opt(0).expect("UTC is a valid offset");
+                from_unix_time(seconds, nanos).map(|dt| dt.with_timezone(&utc))
+            },
+        }
+    }
+
+    pub(crate) fn naive_date_to_sql(date: &NaiveDate, storage: SqliteDateTimeStorage) -> Value {
+        let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+        storage.store(format_naive_date(date), Utc.from_utc_datetime(&midnight).timestamp(), 0)
+    }
+
+    pub(crate) fn naive_time_to_sql(time: &NaiveTime) -> Value {
+        Value::Text(format_naive_time(time))
+    }
+
+    /// Naive timestamps are taken to be UTC when stored as Julian day numbers or Unix time
+    pub(crate) fn naive_date_time_to_sql(date_time: &NaiveDateTime, storage: SqliteDateTimeStorage) -> Value {
+        let utc = Utc.from_utc_datetime(date_time);
+        storage.store(format_naive_date_time(date_time), utc.timestamp(), utc.timestamp_subsec_nanos())
+    }
+
+    pub(crate) fn date_time_to_sql<Tz: TimeZone>(date_time: &DateTime<Tz>, storage: SqliteDateTimeStorage) -> Value {
+        storage.store(format_date_time(date_time), date_time.timestamp(), date_time.timestamp_subsec_nanos())
+    }
+}
+
+#[cfg(feature = "chrono")]
+pub(crate) use chrono_support::*;
+
+#[cfg(feature = "time")]
+mod time_support {
+    use rsdbc_core::datetime::{format_offset_date_time, parse_offset_date_time};
+    use rsdbc_core::time::OffsetDateTime;
+    use rsdbc_core::Result;
+    use rusqlite::types::Value;
+    use crate::options::SqliteDateTimeStorage;
+    use super::{invalid_unix_time, Stored};
+
+    pub(crate) fn to_offset_date_time(stored: Stored) -> Result<OffsetDateTime> {
+        match stored {
+            Stored::Text(s) => parse_offset_date_time(s),
+            Stored::Unix(seconds, nanos) => {
+                OffsetDateTime::from_unix_timestamp_nanos(i128::from(seconds) * 1_000_000_000 + i128::from(nanos))
+                    .map_err(|_| invalid_unix_time(seconds))
+            },
+        }
+    }
+
+    pub(crate) fn offset_date_time_to_sql(date_time: &OffsetDateTime, storage: SqliteDateTimeStorage) -> Value {
+        storage.store(format_offset_date_time(date_time), date_time.unix_timestamp(), date_time.nanosecond())
+    }
+}
+
+#[cfg(feature = "time")]
+pub(crate) use time_support::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::datetime::{from_julian_day, to_julian_day, Stored};
+
+    #[test]
+    fn julian_day_numbers() {
+        // SELECT julianday('2000-01-01 12:00:00')
+        assert_eq!(2_451_545.0, to_julian_day(946_728_000, 0));
+        match from_julian_day(2_451_545.0) {
+            Stored::Unix(seconds, nanos) => assert_eq!((946_728_000, 0), (seconds, nanos)),
+            Stored::Text(_) => unreachable!(),
+        }
+        // SELECT julianday('1970-01-01 00:00:00.250')
+        match from_julian_day(2_440_587.500_002_893_5) {
+            Stored::Unix(seconds, nanos) => assert_eq!((0, 250_000_000), (seconds, nanos)),
+            Stored::Text(_) => unreachable!(),
+        }
+    }
+}
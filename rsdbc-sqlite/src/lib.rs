@@ -1,16 +1,28 @@
 pub mod connection;
 pub mod options;
 pub mod error;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
+mod metadata;
+pub mod types;
+mod warning;
 
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::ops::Deref;
-use rusqlite::{Rows, TransactionBehavior};
+use std::io;
+use rusqlite::{ffi, params_from_iter, DatabaseName, ErrorCode, Rows};
+use rusqlite::types::ToSqlOutput;
 use crate::connection::SqliteConnectionMetadata;
 use rusqlite::Error as RusqliteError;
-use std::sync::{Arc, Mutex};
 use std::rc::Rc;
 use rsdbc_core::connection::{Batch, ConnectionMetadata, IsolationLevel, SQLResult, Statement, ValidationDepth};
-use rsdbc_core::{DatabaseMetadata, Result, ResultSetMetaData};
+use rsdbc_core::error::{DatabaseError, DatabaseErrorKind, RsdbcErrors};
+use rsdbc_core::{Blob, Clob, Column, DatabaseMetadata, Dialect, Lob, Result, ResultSet, ResultSetMetaData, RowMetadata, SQLWarning, SQLWarnings, TransactionDefinition, UuidStorage};
+use rsdbc_core::lob::lob_error;
+use crate::metadata::{column_origins, to_column, ColumnOrigin};
+use crate::types::to_rsdbc_type;
+use crate::options::SqliteDateTimeStorage;
+use crate::warning::with_logged_warnings;
 
 // https://tedspence.com/investigating-rust-with-sqlite-53d1f9a41112
 // https://www.reddit.com/r/rust/comments/dqa4t3/how_to_put_two_variables_one_borrows_from_other/
@@ -20,7 +32,61 @@ use rsdbc_core::{DatabaseMetadata, Result, ResultSetMetaData};
 
 /// Convert a Sqlite error into an RSDBC error
 fn to_rsdbc_err(e: rusqlite::Error) -> rsdbc_core::error::RsdbcErrors {
-    rsdbc_core::error::RsdbcErrors::General(format!("{:?}", e))
+    match e {
+        RusqliteError::SqliteFailure(failure, message) => {
+            let message = message.unwrap_or_else(|| failure.to_string());
+            let mut err = DatabaseError::new(database_error_kind(&failure, &message), message);
+            err.vendor_code = Some(failure.extended_code);
+            err.into()
+        },
+        _ => RsdbcErrors::General(format!("{:?}", e)),
+    }
+}
+
+/// Convert a Sqlite error for the given SQL into an RSDBC error.
+///
+/// SQLite reports the token a syntax error was found at rather than its position,
+/// e.g. `near "SELEC": syntax error`, so the position is derived from the token.
+fn to_rsdbc_err_with_sql(e: rusqlite::Error, sql: &str) -> RsdbcErrors {
+    let mut err = to_rsdbc_err(e).with_sql(sql);
+    if let RsdbcErrors::Database(db_error) = &mut err {
+        if let Some(token) = syntax_error_token(&db_error.message) {
+            let token = token.to_string();
+            db_error.locate(&token);
+        }
+    }
+    err
+}
+
+fn syntax_error_token(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("near \"")?
+        .strip_suffix("\": syntax error")
+}
+
+/// SQLite does not provide a SQLSTATE so classify errors by their (extended) result code.
+fn database_error_kind(failure: &rusqlite::ffi::Error, message: &str) -> DatabaseErrorKind {
+    match failure.extended_code {
+        ffi::SQLITE_CONSTRAINT_UNIQUE | ffi::SQLITE_CONSTRAINT_PRIMARYKEY => return DatabaseErrorKind::UniqueViolation,
+        ffi::SQLITE_CONSTRAINT_FOREIGNKEY => return DatabaseErrorKind::ForeignKeyViolation,
+        ffi::SQLITE_CONSTRAINT_NOTNULL => return DatabaseErrorKind::NotNullViolation,
+        ffi::SQLITE_CONSTRAINT_CHECK => return DatabaseErrorKind::CheckViolation,
+        _ => {},
+    }
+
+    match failure.code {
+        ErrorCode::ConstraintViolation => DatabaseErrorKind::IntegrityConstraintViolation,
+        ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => DatabaseErrorKind::LockNotAvailable,
+        ErrorCode::PermissionDenied
+        | ErrorCode::ReadOnly
+        | ErrorCode::AuthorizationForStatementDenied => DatabaseErrorKind::PermissionDenied,
+        ErrorCode::OperationInterrupted => DatabaseErrorKind::QueryCanceled,
+        ErrorCode::CannotOpen | ErrorCode::NotADatabase => DatabaseErrorKind::ConnectionFailure,
+        ErrorCode::TooBig | ErrorCode::TypeMismatch => DatabaseErrorKind::DataException,
+        // SQLITE_ERROR is used for syntax errors and missing tables / columns
+        ErrorCode::Unknown if message.contains("syntax error") || message.starts_with("no such ") => DatabaseErrorKind::SyntaxError,
+        _ => DatabaseErrorKind::Other,
+    }
 }
 
 // #[derive(Debug)]
@@ -74,27 +140,114 @@ fn to_rsdbc_err(e: rusqlite::Error) -> rsdbc_core::error::RsdbcErrors {
 pub struct SqliteConnection {
     // TODO: given Transaction can reference a conn i'm not sure this is feasible
     // conn: Mutex<Option<&'conn rusqlite::Connection>>,
-    // this needs to be a reference to Rusqlite::Connection otherwise we get an error like
-    // move occurs because `self.conn` has type `Option<rusqlite::Connection>`, which does not implement the `Copy` trait
-    // help: consider borrowing the `Option`'s content: `self.conn.as_ref()`
-    //
-    // because of E0106 it requires a lifetime
-    conn: Option<Arc<Mutex<rusqlite::Connection>>>,
+    // statements borrow the underlying rusqlite::Connection so we own it directly rather than
+    // sharing it behind Arc<Mutex<..>> which would require holding a lock guard in the statement.
+    // None once the connection has been closed
+    conn: Option<rusqlite::Connection>,
+    warnings: SQLWarnings,
+    encoding: ValueEncoding,
+    // restores the connection settings changed by begin_transaction_with once the transaction ends
+    end_transaction_statements: Vec<String>,
 }
 
 impl SqliteConnection {
 
     pub fn new(conn: rusqlite::Connection) -> Self {
-        // Self { conn: Mutex::new(Some(conn)), transaction: None }
-        // Self { conn: Some(conn), transaction: None }
-        // Self {
-        //     conn: Some(Rc::new(conn))
-        // }
         Self {
-            conn: Some(Arc::new(Mutex::new(conn))),
+            conn: Some(conn),
+            warnings: SQLWarnings::new(),
+            encoding: ValueEncoding::default(),
+            end_transaction_statements: Vec::new(),
         }
     }
 
+    /// Sets how bound date and time values are stored, see [SqliteDateTimeStorage].
+    pub fn with_date_time_storage(mut self, storage: SqliteDateTimeStorage) -> Self {
+        self.encoding.date_time_storage = storage;
+        self
+    }
+
+    /// Sets how bound UUIDs are stored, see [UuidStorage].
+    pub fn with_uuid_storage(mut self, storage: UuidStorage) -> Self {
+        self.encoding.uuid_storage = storage;
+        self
+    }
+
+    /// Sets whether arrays are bound as JSON arrays, see [rsdbc_core::json::to_json_array].
+    ///
+    /// SQLite has no arrays so binding one returns [RsdbcErrors::Unsupported] unless enabled.
+    #[cfg(feature = "json")]
+    pub fn with_json_arrays(mut self, enabled: bool) -> Self {
+        self.encoding.json_arrays = enabled;
+        self
+    }
+
+    /// Opens the BLOB in `column` of the row `row_id` of `table` to read it incrementally rather
+    /// than materialising it.
+    pub fn open_blob(&self, table: &str, column: &str, row_id: i64) -> Result<Blob<'_>> {
+        let blob = self.blob_open(table, column, row_id, true)?;
+        let len = blob.size() as u64;
+        Ok(Blob::new(blob, Some(len)))
+    }
+
+    /// Opens the TEXT in `column` of the row `row_id` of `table` to read it incrementally rather
+    /// than materialising it.
+    pub fn open_clob(&self, table: &str, column: &str, row_id: i64) -> Result<Clob<'_>> {
+        let blob = self.blob_open(table, column, row_id, true)?;
+        let len = blob.size() as u64;
+        Ok(Clob::new(blob, Some(len)))
+    }
+
+    /// Writes `lob` into `column` of the row `row_id` of `table` incrementally and returns the
+    /// number of bytes written.
+    ///
+    /// Incremental I/O can not change the size of a value so the space has to be reserved first,
+    /// e.g. `INSERT INTO documents (body) VALUES (zeroblob(?))` bound to the length of `lob`.
+    pub fn write_blob(&self, table: &str, column: &str, row_id: i64, lob: &Lob) -> Result<u64> {
+        let mut blob = self.blob_open(table, column, row_id, false)?;
+        io::copy(&mut lob.take()?, &mut blob).map_err(|e| match e.kind() {
+            io::ErrorKind::WriteZero => RsdbcErrors::IllegalArgument(
+                format!("large object is longer than the {} bytes reserved for it", blob.size())
+            ),
+            _ => lob_error(e),
+        })
+    }
+
+    fn blob_open(&self, table: &str, column: &str, row_id: i64, read_only: bool) -> Result<rusqlite::blob::Blob<'_>> {
+        self.conn()?
+            .blob_open(DatabaseName::Main, table, column, row_id, read_only)
+            .map_err(to_rsdbc_err)
+    }
+
+    fn conn(&self) -> Result<&rusqlite::Connection> {
+        self.conn
+            .as_ref()
+            .ok_or_else(|| RsdbcErrors::General("connection is closed".to_string()))
+    }
+
+    fn pragma(&self, name: &str) -> Result<i64> {
+        self.conn()?
+            .query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+            .map_err(to_rsdbc_err)
+    }
+
+    /// Restores the settings changed by begin_transaction_with if the transaction has ended
+    fn end_transaction(&mut self) -> Result<()> {
+        if self.conn.as_ref().is_some_and(|c| c.is_autocommit()) {
+            for statement in std::mem::take(&mut self.end_transaction_statements) {
+                self.execute_batch(&statement)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_batch(&self, sql: &str) -> Result<()> {
+        let conn = self.conn()?;
+        let (result, warnings) = with_logged_warnings(|| conn.execute_batch(sql));
+        self.warnings.extend(warnings);
+        result.map_err(|e| to_rsdbc_err_with_sql(e, sql))
+    }
+
     fn drop(&mut self) {
         // if let Some(transaction) = self.transaction.take() {
         //     let _ = transaction.rollback();
@@ -108,38 +261,44 @@ impl rsdbc_core::connection::Connection for SqliteConnection {
     // TODO: result?
     fn begin_transaction(&mut self) -> Result<()> {
         // TODO: call begin_transaction_with_definition with an empty instance
-        // let mut connection = self.conn.take().unwrap();
-        // let mut connection = self.conn.lock().unwrap().take().unwrap();
-        // connection.transaction().map_err(to_rsdbc_err);
-        // self.transaction = Some(trans);
-        Ok(())
+        self.execute_batch("BEGIN")
     }
 
-    // fn begin_transaction_with_definition(&mut self, definition: &dyn TransactionDefinition) {
-    //     // TODO: convert definition to TransactionBehavior
-    //     // let mut connection = self.conn.take().unwrap();
-    //     // let mut connection = self.conn.lock().unwrap().take().unwrap();
-    //     // connection.transaction_with_behavior(TransactionBehavior::Deferred);
-    // }
+    /// SQLite has no per transaction lock timeout or read only transactions so the lock wait
+    /// timeout is applied as the connection's busy timeout and a read only transaction sets
+    /// `PRAGMA query_only`. Both are restored when the transaction ends.
+    fn begin_transaction_with(&mut self, definition: &dyn TransactionDefinition) -> Result<()> {
+        let sql = begin_transaction_sql(definition)?;
+        if let Some(timeout) = definition.lock_wait_timeout()? {
+            let previous = self.pragma("busy_timeout")?;
+            self.conn()?.busy_timeout(timeout).map_err(to_rsdbc_err)?;
+            self.end_transaction_statements.push(format!("PRAGMA busy_timeout = {}", previous));
+        }
+        if definition.read_only()? == Some(true) {
+            let previous = self.pragma("query_only")?;
+            self.execute_batch("PRAGMA query_only = ON")?;
+            self.end_transaction_statements.push(format!("PRAGMA query_only = {}", previous));
+        }
+
+        let result = self.execute_batch(sql);
+        self.end_transaction()?;
+        result
+    }
 
     // https://www.reddit.com/r/rust/comments/2t8i2s/yet_another_problem_with_mutable_struct_members/
-    // TODO: should return a result
     fn close(&mut self) -> Result<()> {
-        // let close_result = self.conn.get_mut().unwrap().close();
-
-        // self.conn.lock().unwrap().map(|c| c.close());
-        let mut _c = self.conn.take();
-        // let mut _c = self.conn.get_mut().unwrap().take();
-        _c = None;
+        if let Some(conn) = self.conn.take() {
+            conn.close().map_err(|e| to_rsdbc_err(e.1))?;
+        }
 
-        // close_result.map_err(move |e| to_rsdbc_err(e.1))?;
         Ok(())
     }
 
-    fn commit_transaction(&mut self) {
-        // if let Some(transaction) = self.transaction.take() {
-        //     let _ = transaction.commit();
-        // }
+    fn commit_transaction(&mut self) -> Result<()> {
+        // a COMMIT which fails, e.g. because the database is busy, leaves the transaction open
+        let result = self.execute_batch("COMMIT");
+        self.end_transaction()?;
+        result
     }
 
     fn create_batch(&mut self) -> Result<Box<dyn Batch>> {
@@ -148,57 +307,43 @@ impl rsdbc_core::connection::Connection for SqliteConnection {
 
     // TODO: return result
     // UnsupportedOperationException if not supported
-    fn create_savepoint(&mut self, name: &str) {
-        // if self.transaction.is_none() {
-        //     // return error
-        // }
-
-        // let sp = self.conn.savepoint_with_name(name)?;
-        // let savepoint = self.transaction.unwrap().savepoint_with_name(name)?;
+    fn create_savepoint(&mut self, name: &str) -> Result<()> {
+        self.execute_batch(format!("SAVEPOINT {}", self.dialect().quote_identifier(name)).as_str())
     }
 
     // fn create_statement(&mut self, sql: &str) -> Result<Box<Self::Statement>> {
     fn create_statement(&mut self, sql: &str) -> Result<Box<dyn Statement<'_> + '_>> {
-        // let mut c = self.conn.take();
-        // let stmt = c.unwrap()
-        //     .prepare(sql)
-        //     .map_err(to_rsdbc_err)?;
-
-        // let c: &'conn rusqlite::Connection = self.conn.unwrap();
-
-        // let stmt: rusqlite::Statement = self.conn.unwrap()
-        //     .prepare(sql)
-        //     .map_err(to_rsdbc_err)?;
-
-        // let stmt = self.conn.get_mut().unwrap().take().unwrap()
-        //     .prepare(sql)
-        //     .map_err(to_rsdbc_err)?;
-
-        // let stmt = self.conn.lock().unwrap().take().unwrap()
-        //     .prepare(sql)
-        //     .map_err(to_rsdbc_err)?;
-
-        // let stmt = self.conn
-        //     .as_ref()
-        //     .unwrap()
-        //     .clone()
-        //     .deref()
-        //     .lock()
-        //     .unwrap()
-        //     .prepare(sql)
-        //     .map_err(to_rsdbc_err)?;
-        //
-        // Ok(Box::new(SqliteStatement {
-        //     stmt,
-        // }))
+        let conn = self.conn()?;
+        // the query planner logs warnings such as automatic indexes while preparing
+        let (stmt, warnings) = with_logged_warnings(|| conn.prepare(sql));
+        self.warnings.extend(warnings);
+        let stmt = stmt.map_err(|e| to_rsdbc_err_with_sql(e, sql))?;
+
+        let origins = if stmt.column_count() > 0 {
+            // already reported when the statement was prepared above
+            with_logged_warnings(|| column_origins(conn, sql)).0
+        } else {
+            Vec::new()
+        };
+
+        Ok(Box::new(SqliteStatement {
+            stmt,
+            origins,
+            warnings: self.warnings.clone(),
+            encoding: self.encoding,
+        }))
+    }
 
-        todo!()
+    fn execute_unprepared(&mut self, sql: &str) -> Result<()> {
+        self.execute_batch(sql)
+    }
+
+    fn dialect(&self) -> Dialect {
+        Dialect::Sqlite
     }
 
     fn is_auto_commit(&mut self) -> bool {
-        let connection = self.conn.take().unwrap();
-        // let connection = self.conn.lock().unwrap().take().unwrap();
-        connection.clone().deref().lock().unwrap().is_autocommit()
+        self.conn.as_ref().is_none_or(|c| c.is_autocommit())
     }
 
     fn metadata(&mut self) -> Result<Box<dyn ConnectionMetadata>> {
@@ -206,23 +351,24 @@ impl rsdbc_core::connection::Connection for SqliteConnection {
     }
 
     fn transaction_isolation_level(&mut self) -> IsolationLevel {
-        todo!()
+        // all transactions in SQLite are serializable. See set_transaction_isolation_level
+        IsolationLevel::Serializable
     }
 
-    fn release_savepoint(&mut self, name: &str) {
-        todo!()
-        // do we need to keep savepoint? I dont see rusqlite giving us an option to get a savepoint
-
+    fn release_savepoint(&mut self, name: &str) -> Result<()> {
+        self.execute_batch(format!("RELEASE SAVEPOINT {}", self.dialect().quote_identifier(name)).as_str())
     }
 
     /// This is equivalent to `Transaction`'s `Drop` implementation, but provides any error
     /// encountered to the caller.
-    fn rollback_transaction(&mut self) {
-        todo!()
+    fn rollback_transaction(&mut self) -> Result<()> {
+        let result = self.execute_batch("ROLLBACK");
+        self.end_transaction()?;
+        result
     }
 
-    fn rollback_transaction_to_savepoint(&mut self, name: String) {
-        todo!()
+    fn rollback_transaction_to_savepoint(&mut self, name: String) -> Result<()> {
+        self.execute_batch(format!("ROLLBACK TO SAVEPOINT {}", self.dialect().quote_identifier(&name)).as_str())
     }
 
     fn auto_commit(&mut self, commit: bool) {
@@ -245,6 +391,27 @@ impl rsdbc_core::connection::Connection for SqliteConnection {
     fn validate(&mut self, depth: ValidationDepth) -> bool {
         todo!()
     }
+
+    fn warnings(&mut self) -> Vec<SQLWarning> {
+        self.warnings.get()
+    }
+
+    fn clear_warnings(&mut self) {
+        self.warnings.clear();
+    }
+}
+
+/// Translate a [TransactionDefinition] to the statement used to begin the transaction.
+///
+/// All transactions in SQLite are serializable so the isolation level is accepted but not translated.
+/// Read-write transactions are started IMMEDIATE so the write lock is acquired upfront rather than
+/// failing with SQLITE_BUSY when a deferred transaction later attempts to write.
+fn begin_transaction_sql(definition: &dyn TransactionDefinition) -> Result<&'static str> {
+    definition.isolation_level()?;
+    match definition.read_only()? {
+        Some(false) => Ok("BEGIN IMMEDIATE"),
+        _ => Ok("BEGIN DEFERRED"),
+    }
 }
 
 // impl Drop for SqliteConnection {
@@ -256,6 +423,11 @@ impl rsdbc_core::connection::Connection for SqliteConnection {
 // TODO: Do we need this? Can we just use CallableStatement/PreparedStatement
 pub struct SqliteStatement<'a> {
     stmt: rusqlite::Statement<'a>,
+    // table columns the result columns originate from, by result column index
+    origins: Vec<Option<ColumnOrigin>>,
+    // shared with the connection the statement was created from
+    warnings: SQLWarnings,
+    encoding: ValueEncoding,
 }
 
 impl rsdbc_core::connection::Statement<'_> for SqliteStatement<'_> {
@@ -290,10 +462,103 @@ impl rsdbc_core::connection::Statement<'_> for SqliteStatement<'_> {
     fn fetch_size(&mut self, rows: u32) -> &mut Self where Self: Sized {
         todo!()
     }
+
+    fn execute_query(&mut self, params: &[rsdbc_core::Value]) -> Result<Box<dyn ResultSet + '_>> {
+        self.encoding.check(params)?;
+        let params = params_from_iter(to_params(params, self.encoding));
+        let stmt = &mut self.stmt;
+        let (rows, warnings) = with_logged_warnings(move || stmt.query(params));
+        self.warnings.extend(warnings.clone());
+
+        Ok(Box::new(SqliteResultSet {
+            rows: rows.map_err(to_rsdbc_err)?,
+            origins: &self.origins,
+            row: None,
+            error: Cell::new(None),
+            failed: false,
+            connection_warnings: self.warnings.clone(),
+            warnings,
+        }))
+    }
+
+    fn execute_update(&mut self, params: &[rsdbc_core::Value]) -> Result<u64> {
+        self.encoding.check(params)?;
+        let params = params_from_iter(to_params(params, self.encoding));
+        let (result, warnings) = with_logged_warnings(|| self.stmt.execute(params));
+        self.warnings.extend(warnings);
+        result.map(|n| n as u64).map_err(to_rsdbc_err)
+    }
 }
 
 struct SqliteResultSet<'stmt> {
     rows: Rows<'stmt>,
+    origins: &'stmt [Option<ColumnOrigin>],
+    // rusqlite only lends out the current row while the cursor is borrowed so we copy its values
+    // on each call to next
+    row: Option<Vec<rusqlite::types::Value>>,
+    // the error which stopped the last call to next, returned by the next getter as next can only
+    // report whether there is a row
+    error: Cell<Option<RsdbcErrors>>,
+    failed: bool,
+    // warnings reported while producing this result which are also added to the connection's
+    warnings: Vec<SQLWarning>,
+    connection_warnings: SQLWarnings,
+}
+
+impl SqliteResultSet<'_> {
+    fn columns(&self) -> Result<Vec<Column>> {
+        let stmt = self.rows
+            .as_ref()
+            .ok_or_else(|| RsdbcErrors::General("statement has been finalized".to_string()))?;
+
+        Ok(stmt.columns()
+            .iter()
+            .enumerate()
+            .map(|(i, c)| to_column(
+                c.name(),
+                c.decl_type(),
+                to_rsdbc_type(c.decl_type()),
+                self.origins.get(i).and_then(Option::as_ref),
+            ))
+            .collect())
+    }
+
+    fn get_value(&self, i: u64) -> Result<&rusqlite::types::Value> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        let row = self.row
+            .as_ref()
+            .ok_or_else(|| RsdbcErrors::General("result set is not positioned on a row".to_string()))?;
+
+        row.get(i as usize)
+            .ok_or_else(|| RsdbcErrors::General(format!("column index {} out of range", i)))
+    }
+
+    fn get_integer(&self, i: u64) -> Result<Option<i64>> {
+        match self.get_value(i)? {
+            rusqlite::types::Value::Null => Ok(None),
+            rusqlite::types::Value::Integer(v) => Ok(Some(*v)),
+            v => Err(invalid_column_type(i, v)),
+        }
+    }
+
+    fn get_real(&self, i: u64) -> Result<Option<f64>> {
+        match self.get_value(i)? {
+            rusqlite::types::Value::Null => Ok(None),
+            rusqlite::types::Value::Integer(v) => Ok(Some(*v as f64)),
+            rusqlite::types::Value::Real(v) => Ok(Some(*v)),
+            v => Err(invalid_column_type(i, v)),
+        }
+    }
+}
+
+fn invalid_column_type(i: u64, value: &rusqlite::types::Value) -> RsdbcErrors {
+    RsdbcErrors::General(format!("invalid column type {} at index {}", value.data_type(), i))
+}
+
+fn out_of_range<E: std::fmt::Display>(e: E) -> RsdbcErrors {
+    RsdbcErrors::General(e.to_string())
 }
 
 struct SqliteDatabaseMetadata {
@@ -306,78 +571,209 @@ impl DatabaseMetadata for SqliteDatabaseMetadata {
 
 impl<'stmt> rsdbc_core::ResultSet for SqliteResultSet<'stmt> {
     fn meta_data(&self) -> Result<Box<dyn ResultSetMetaData>> {
-        todo!()
+        Ok(Box::new(self.columns()?))
+    }
+
+    fn row_metadata(&self) -> Result<Box<dyn RowMetadata>> {
+        Ok(Box::new(self.columns()?))
     }
 
+    /// A failure to read the next row is reported by the getters, so next returns true once more
+    /// when it fails and false from then on rather than silently ending the result set.
     fn next(&mut self) -> bool {
-        todo!()
+        if self.failed {
+            self.row = None;
+            return false;
+        }
+        let rows = &mut self.rows;
+        let (row, warnings) = with_logged_warnings(move || match rows.next() {
+            Ok(Some(row)) => {
+                let column_count = row.as_ref().column_count();
+                (0..column_count)
+                    .map(|i| row.get::<_, rusqlite::types::Value>(i))
+                    .collect::<rusqlite::Result<Vec<_>>>()
+                    .map(Some)
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        });
+        self.row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                self.failed = true;
+                self.error.set(Some(to_rsdbc_err(e)));
+                None
+            },
+        };
+
+        self.connection_warnings.extend(warnings.clone());
+        self.warnings.extend(warnings);
+
+        self.row.is_some() || self.failed
     }
 
     fn get_bool(&self, i: u64) -> Result<Option<bool>> {
-        todo!()
+        Ok(self.get_integer(i)?.map(|v| v != 0))
     }
 
     fn get_i8(&self, i: u64) -> Result<Option<i8>> {
-        todo!()
+        self.get_integer(i)?.map(i8::try_from).transpose().map_err(out_of_range)
     }
 
     fn get_i16(&self, i: u64) -> Result<Option<i16>> {
-        todo!()
+        self.get_integer(i)?.map(i16::try_from).transpose().map_err(out_of_range)
     }
 
     fn get_i32(&self, i: u64) -> Result<Option<i32>> {
-        todo!()
+        self.get_integer(i)?.map(i32::try_from).transpose().map_err(out_of_range)
     }
 
     fn get_i64(&self, i: u64) -> Result<Option<i64>> {
-        todo!()
+        self.get_integer(i)
     }
 
     fn get_f32(&self, i: u64) -> Result<Option<f32>> {
-        todo!()
+        Ok(self.get_real(i)?.map(|v| v as f32))
     }
 
     fn get_f64(&self, i: u64) -> Result<Option<f64>> {
-        todo!()
+        self.get_real(i)
     }
 
     fn get_string(&self, i: u64) -> Result<Option<String>> {
-        todo!()
+        match self.get_value(i)? {
+            rusqlite::types::Value::Null => Ok(None),
+            rusqlite::types::Value::Integer(v) => Ok(Some(v.to_string())),
+            rusqlite::types::Value::Real(v) => Ok(Some(v.to_string())),
+            rusqlite::types::Value::Text(v) => Ok(Some(v.clone())),
+            v => Err(invalid_column_type(i, v)),
+        }
     }
 
     fn get_bytes(&self, i: u64) -> Result<Option<Vec<u8>>> {
-        todo!()
+        match self.get_value(i)? {
+            rusqlite::types::Value::Null => Ok(None),
+            rusqlite::types::Value::Text(v) => Ok(Some(v.clone().into_bytes())),
+            rusqlite::types::Value::Blob(v) => Ok(Some(v.clone())),
+            v => Err(invalid_column_type(i, v)),
+        }
     }
-}
 
-fn to_rsdbc_type(t: Option<&str>) -> rsdbc_core::DataType {
-    //TODO implement for real
-    match t {
-        Some("INT") => rsdbc_core::DataType::Integer,
-        _ => rsdbc_core::DataType::Utf8,
+    #[cfg(feature = "chrono")]
+    fn get_naive_date(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveDate>> {
+        datetime::stored(i, self.get_value(i)?)?.map(datetime::to_naive_date).transpose()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_time(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveTime>> {
+        datetime::stored(i, self.get_value(i)?)?.map(datetime::to_naive_time).transpose()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_naive_date_time(&self, i: u64) -> Result<Option<rsdbc_core::chrono::NaiveDateTime>> {
+        datetime::stored(i, self.get_value(i)?)?.map(datetime::to_naive_date_time).transpose()
+    }
+
+    #[cfg(feature = "chrono")]
+    fn get_date_time_fixed_offset(&self, i: u64) -> Result<Option<rsdbc_core::chrono::DateTime<rsdbc_core::chrono::FixedOffset>>> {
+        datetime::stored(i, self.get_value(i)?)?.map(datetime::to_date_time).transpose()
     }
-}
 
-struct Values<'a>(&'a [rsdbc_core::Value]);
-struct ValuesIter<'a>(std::slice::Iter<'a, rsdbc_core::Value>);
+    #[cfg(feature = "time")]
+    fn get_offset_date_time(&self, i: u64) -> Result<Option<rsdbc_core::time::OffsetDateTime>> {
+        datetime::stored(i, self.get_value(i)?)?.map(datetime::to_offset_date_time).transpose()
+    }
 
-impl<'a> IntoIterator for &'a Values<'a> {
-    type Item = &'a dyn rusqlite::types::ToSql;
-    type IntoIter = ValuesIter<'a>;
+    fn warnings(&self) -> Vec<SQLWarning> {
+        self.warnings.clone()
+    }
 
-    fn into_iter(self) -> ValuesIter<'a> {
-        ValuesIter(self.0.iter())
+    fn clear_warnings(&mut self) {
+        self.warnings.clear();
     }
 }
-impl<'a> Iterator for ValuesIter<'a> {
-    type Item = &'a dyn rusqlite::types::ToSql;
-
-    fn next(&mut self) -> Option<&'a dyn rusqlite::types::ToSql> {
-        self.0.next().map(|v| match v {
-            rsdbc_core::Value::String(ref s) => s as &dyn rusqlite::types::ToSql,
-            rsdbc_core::Value::Int32(ref n) => n as &dyn rusqlite::types::ToSql,
-            rsdbc_core::Value::UInt32(ref n) => n as &dyn rusqlite::types::ToSql,
-        })
+
+/// How bound values which have no SQLite storage class are stored.
+#[derive(Debug, Default, Copy, Clone)]
+struct ValueEncoding {
+    date_time_storage: SqliteDateTimeStorage,
+    uuid_storage: UuidStorage,
+    json_arrays: bool,
+}
+
+impl ValueEncoding {
+    fn check(&self, values: &[rsdbc_core::Value]) -> Result<()> {
+        let json_arrays = cfg!(feature = "json") && self.json_arrays;
+        if !json_arrays && values.iter().any(|v| matches!(v, rsdbc_core::Value::Array(_))) {
+            return Err(RsdbcErrors::Unsupported(
+                "SQLite has no arrays, enable JSON arrays to bind arrays as JSON".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+struct Param<'a> {
+    value: &'a rsdbc_core::Value,
+    // only dates, times and UUIDs are bound according to the encoding
+    #[cfg_attr(not(any(feature = "chrono", feature = "time", feature = "uuid")), allow(dead_code))]
+    encoding: ValueEncoding,
+}
+
+fn to_params(values: &[rsdbc_core::Value], encoding: ValueEncoding) -> impl Iterator<Item = Param<'_>> {
+    values.iter().map(move |value| Param { value, encoding })
+}
+
+impl rusqlite::types::ToSql for Param<'_> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        match self.value {
+            rsdbc_core::Value::Bool(v) => v.to_sql(),
+            rsdbc_core::Value::Int32(n) => n.to_sql(),
+            rsdbc_core::Value::UInt32(n) => n.to_sql(),
+            rsdbc_core::Value::Int64(n) => n.to_sql(),
+            rsdbc_core::Value::Double(n) => n.to_sql(),
+            rsdbc_core::Value::String(s) => s.to_sql(),
+            rsdbc_core::Value::Bytes(b) => b.to_sql(),
+            #[cfg(feature = "chrono")]
+            rsdbc_core::Value::NaiveDate(v) => Ok(ToSqlOutput::Owned(datetime::naive_date_to_sql(v, self.encoding.date_time_storage))),
+            #[cfg(feature = "chrono")]
+            rsdbc_core::Value::NaiveTime(v) => Ok(ToSqlOutput::Owned(datetime::naive_time_to_sql(v))),
+            #[cfg(feature = "chrono")]
+            rsdbc_core::Value::NaiveDateTime(v) => Ok(ToSqlOutput::Owned(datetime::naive_date_time_to_sql(v, self.encoding.date_time_storage))),
+            #[cfg(feature = "chrono")]
+            rsdbc_core::Value::DateTimeUtc(v) => Ok(ToSqlOutput::Owned(datetime::date_time_to_sql(v, self.encoding.date_time_storage))),
+            #[cfg(feature = "chrono")]
+            rsdbc_core::Value::DateTimeFixedOffset(v) => Ok(ToSqlOutput::Owned(datetime::date_time_to_sql(v, self.encoding.date_time_storage))),
+            #[cfg(feature = "time")]
+            rsdbc_core::Value::OffsetDateTime(v) => Ok(ToSqlOutput::Owned(datetime::offset_date_time_to_sql(v, self.encoding.date_time_storage))),
+            // decimals are stored as TEXT which is lossless unless the column's affinity converts them to REAL
+            #[cfg(feature = "rust_decimal")]
+            rsdbc_core::Value::Decimal(v) => Ok(ToSqlOutput::from(v.to_string())),
+            #[cfg(feature = "bigdecimal")]
+            rsdbc_core::Value::BigDecimal(v) => Ok(ToSqlOutput::from(v.to_string())),
+            #[cfg(feature = "uuid")]
+            rsdbc_core::Value::Uuid(v) => match self.encoding.uuid_storage {
+                UuidStorage::Text => Ok(ToSqlOutput::from(v.hyphenated().to_string())),
+                UuidStorage::Binary => Ok(ToSqlOutput::from(v.as_bytes().to_vec())),
+            },
+            #[cfg(feature = "json")]
+            rsdbc_core::Value::Json(v) => Ok(ToSqlOutput::from(v.to_string())),
+            // only bound when JSON arrays are enabled, see ValueEncoding::check
+            #[cfg(feature = "json")]
+            rsdbc_core::Value::Array(v) => rsdbc_core::json::to_json_array(v)
+                .map(|json| ToSqlOutput::from(json.to_string()))
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+            #[cfg(not(feature = "json"))]
+            rsdbc_core::Value::Array(_) => Err(rusqlite::Error::ToSqlConversionFailure("arrays are not supported".into())),
+            // a bound value is sent in one call, see SqliteConnection::write_blob to stream one
+            rsdbc_core::Value::Blob(v) => v.read_to_vec()
+                .map(ToSqlOutput::from)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+            rsdbc_core::Value::Clob(v) => v.read_to_vec()
+                .and_then(|b| String::from_utf8(b).map_err(|e| RsdbcErrors::IllegalArgument(format!("invalid UTF-8 in clob: {}", e))))
+                .map(ToSqlOutput::from)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+        }
     }
 }
 
@@ -385,6 +781,10 @@ impl<'a> Iterator for ValuesIter<'a> {
 mod tests {
     use super::*;
     use std::{collections::HashMap, sync::Arc};
+    use std::time::Duration;
+    use rsdbc_core::connection::{Connection, Transaction};
+    use rsdbc_core::{Nullability, RsdbcType, TransactionDefinitionBuilder};
+    use rsdbc_core::Value;
     use crate::options::SqliteConnectOptions;
 
     // // low-level, Executor trait
@@ -418,6 +818,497 @@ mod tests {
 
 
 
+    #[test]
+    fn begin_transaction_sql_should_use_immediate_for_read_write() {
+        let definition = TransactionDefinitionBuilder::new().read_only(false).build();
+        assert_eq!("BEGIN IMMEDIATE", begin_transaction_sql(&definition).unwrap());
+
+        let definition = TransactionDefinitionBuilder::new().read_only(true).build();
+        assert_eq!("BEGIN DEFERRED", begin_transaction_sql(&definition).unwrap());
+
+        let definition = TransactionDefinitionBuilder::new().build();
+        assert_eq!("BEGIN DEFERRED", begin_transaction_sql(&definition).unwrap());
+    }
+
+    #[test]
+    fn begin_transaction_with_definition() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let definition = TransactionDefinitionBuilder::new()
+            .isolation_level(IsolationLevel::Serializable)
+            .read_only(false)
+            .lock_wait_timeout(Duration::from_secs(1))
+            .build();
+
+        connection.begin_transaction_with(&definition)?;
+        assert!(!connection.is_auto_commit());
+        connection.commit_transaction()?;
+        assert!(connection.is_auto_commit());
+
+        Ok(())
+    }
+
+    #[test]
+    fn transaction_settings_should_be_restored_when_the_transaction_ends() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        connection.execute_batch("CREATE TABLE t (id INTEGER)")?;
+        let busy_timeout = connection.pragma("busy_timeout")?;
+        let definition = TransactionDefinitionBuilder::new()
+            .read_only(true)
+            .lock_wait_timeout(Duration::from_millis(busy_timeout as u64 + 1234))
+            .build();
+
+        connection.begin_transaction_with(&definition)?;
+        assert_eq!(busy_timeout + 1234, connection.pragma("busy_timeout")?);
+        assert!(connection.execute_batch("INSERT INTO t VALUES (1)").is_err());
+        connection.rollback_transaction()?;
+
+        assert_eq!(busy_timeout, connection.pragma("busy_timeout")?);
+        assert_eq!(0, connection.pragma("query_only")?);
+        connection.execute_batch("INSERT INTO t VALUES (1)")?;
+        Ok(())
+    }
+
+    #[test]
+    fn transaction_guard_should_rollback_savepoint_and_commit() -> rsdbc_core::Result<()> {
+        let mut connection: Box<dyn Connection> = Box::new(SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap()));
+        connection.create_statement("CREATE TABLE t (id INTEGER)")?.execute_update(&[])?;
+
+        {
+            let mut tx = connection.begin()?;
+            tx.create_statement("INSERT INTO t VALUES (1)")?.execute_update(&[])?;
+            {
+                let mut savepoint = tx.create_savepoint("sp1")?;
+                savepoint.create_statement("INSERT INTO t VALUES (2)")?.execute_update(&[])?;
+                // dropped without commit so rolls back to sp1
+            }
+            tx.commit_transaction()?;
+        }
+
+        {
+            let mut tx = connection.begin()?;
+            tx.create_statement("INSERT INTO t VALUES (3)")?.execute_update(&[])?;
+            // dropped without commit so the whole transaction rolls back
+        }
+
+        let mut stmt = connection.create_statement("SELECT count(*) FROM t")?;
+        let mut rs = stmt.execute_query(&[])?;
+        assert!(rs.next());
+        assert_eq!(Some(1), rs.get_i64(0)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn savepoint_names_should_be_quoted() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        connection.execute_batch("CREATE TABLE t (id INTEGER)")?;
+        let name = "sp\"; DROP TABLE t; --";
+
+        connection.begin_transaction()?;
+        connection.create_savepoint(name)?;
+        connection.execute_batch("INSERT INTO t VALUES (1)")?;
+        connection.rollback_transaction_to_savepoint(name.to_string())?;
+        connection.release_savepoint(name)?;
+        connection.commit_transaction()?;
+
+        let mut stmt = connection.create_statement("SELECT count(*) FROM t")?;
+        let mut rs = stmt.execute_query(&[])?;
+        assert!(rs.next());
+        assert_eq!(Some(0), rs.get_i64(0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn failures_reading_rows_should_be_reported_by_the_getters() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        // abs overflows on the second row
+        let mut stmt = connection.create_statement("SELECT abs(x) FROM (SELECT 1 AS x UNION ALL SELECT -9223372036854775808)")?;
+        let mut rs = stmt.execute_query(&[])?;
+
+        assert!(rs.next());
+        assert_eq!(Some(1), rs.get_i64(0)?);
+        assert!(rs.next());
+        let err = rs.get_i64(0).unwrap_err();
+        assert!(err.to_string().contains("integer overflow"), "{}", err);
+        assert!(!rs.next());
+        Ok(())
+    }
+
+    #[test]
+    fn result_set_metadata() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        connection.create_statement("CREATE TABLE person (id INTEGER PRIMARY KEY, name VARCHAR(20) NOT NULL, score DECIMAL(5, 2))")?.execute_update(&[])?;
+
+        let mut stmt = connection.create_statement("SELECT id, name AS person_name, score, 1 + 1 AS two FROM person")?;
+        let rs = stmt.execute_query(&[])?;
+        let metadata = rs.meta_data()?;
+
+        assert_eq!(4, metadata.num_columns());
+        assert_eq!(RsdbcType::Integer, metadata.column_type(0));
+        assert_eq!(RsdbcType::Varchar, metadata.column_type(1));
+        assert_eq!(RsdbcType::Decimal, metadata.column_type(2));
+        assert_eq!(Nullability::NonNull, metadata.is_nullable(0));
+
+        assert_eq!("person_name", metadata.column_name(1));
+        assert_eq!("VARCHAR(20)", metadata.column_type_name(1));
+        assert_eq!(Some(20), metadata.precision(1));
+        assert_eq!(Some("main".to_string()), metadata.schema_name(1));
+        assert_eq!(Some("person".to_string()), metadata.table_name(1));
+        assert_eq!(Some("name".to_string()), metadata.base_column_name(1));
+        assert_eq!(Nullability::NonNull, metadata.is_nullable(1));
+        assert!(!metadata.is_read_only(1));
+
+        assert_eq!(Some(5), metadata.precision(2));
+        assert_eq!(Some(2), metadata.scale(2));
+        assert_eq!(Nullability::Nullable, metadata.is_nullable(2));
+
+        assert_eq!(None, metadata.table_name(3));
+        assert_eq!(Nullability::Unknown, metadata.is_nullable(3));
+        assert!(metadata.is_read_only(3));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn date_time_storage() -> rsdbc_core::Result<()> {
+        use rsdbc_core::chrono::{Duration, NaiveDate, TimeZone, Utc};
+        use crate::options::SqliteDateTimeStorage;
+
+        let date_time = NaiveDate::from_ymd_opt(2021, 12, 31).unwrap().and_hms_milli_opt(23, 59, 59, 250).unwrap();
+        let storages = [
+            (SqliteDateTimeStorage::Text, "text", date_time),
+            (SqliteDateTimeStorage::Julian, "real", date_time),
+            (SqliteDateTimeStorage::Unix, "integer", date_time - Duration::milliseconds(250)),
+        ];
+
+        for (storage, storage_class, expected) in storages {
+            let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap())
+                .with_date_time_storage(storage);
+            connection.create_statement("CREATE TABLE event (at TIMESTAMP)")?.execute_update(&[])?;
+            connection.create_statement("INSERT INTO event VALUES (?)")?.execute_update(&[Value::NaiveDateTime(date_time)])?;
+
+            let mut stmt = connection.create_statement("SELECT at, typeof(at) FROM event")?;
+            let mut rs = stmt.execute_query(&[])?;
+            assert!(rs.next());
+            assert_eq!(Some(storage_class.to_string()), rs.get_string(1)?);
+            assert_eq!(Some(expected), rs.get_naive_date_time(0)?);
+            assert_eq!(Some(expected.date()), rs.get_naive_date(0)?);
+            assert_eq!(Some(expected.time()), rs.get_naive_time(0)?);
+            assert_eq!(Some(Utc.from_utc_datetime(&expected)), rs.get_date_time_utc(0)?);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn date_time_values_from_date_and_time_functions() -> rsdbc_core::Result<()> {
+        use rsdbc_core::chrono::{FixedOffset, NaiveDate, NaiveTime, TimeZone};
+
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let mut stmt = connection.create_statement(
+            "SELECT julianday('2021-12-31 12:00:00'), CAST(strftime('%s', '2021-12-31 12:00:00') AS INTEGER), \
+            datetime('2021-12-31 12:00:00'), date('2021-12-31'), time('12:00:00'), '2021-12-31T12:00:00+02:00', ?"
+        )?;
+
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        let bound = offset.from_local_datetime(&NaiveDate::from_ymd_opt(2021, 12, 31).unwrap().and_hms_opt(12, 0, 0).unwrap()).unwrap();
+        let mut rs = stmt.execute_query(&[Value::DateTimeFixedOffset(bound)])?;
+        assert!(rs.next());
+
+        let noon = NaiveDate::from_ymd_opt(2021, 12, 31).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        for i in 0..3 {
+            assert_eq!(Some(noon), rs.get_naive_date_time(i)?);
+        }
+        assert_eq!(Some(noon.date()), rs.get_naive_date(3)?);
+        assert_eq!(Some(NaiveTime::from_hms_opt(12, 0, 0).unwrap()), rs.get_naive_time(4)?);
+        assert_eq!(Some(bound), rs.get_date_time_fixed_offset(5)?);
+        assert_eq!(Some("2021-12-31 12:00:00+02:00".to_string()), rs.get_string(6)?);
+        assert_eq!(Some(bound), rs.get_date_time_fixed_offset(6)?);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn offset_date_time() -> rsdbc_core::Result<()> {
+        use crate::options::SqliteDateTimeStorage;
+
+        let date_time = rsdbc_core::time::OffsetDateTime::from_unix_timestamp(1_640_995_199).unwrap();
+        for storage in [SqliteDateTimeStorage::Text, SqliteDateTimeStorage::Julian, SqliteDateTimeStorage::Unix] {
+            let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap())
+                .with_date_time_storage(storage);
+            let mut stmt = connection.create_statement("SELECT ?")?;
+            let mut rs = stmt.execute_query(&[Value::OffsetDateTime(date_time)])?;
+            assert!(rs.next());
+            assert_eq!(Some(date_time), rs.get_offset_date_time(0)?);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "rust_decimal", feature = "bigdecimal"))]
+    #[test]
+    fn decimals() -> rsdbc_core::Result<()> {
+        use std::str::FromStr;
+        use rsdbc_core::bigdecimal::BigDecimal;
+        use rsdbc_core::rust_decimal::Decimal;
+
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        connection.create_statement("CREATE TABLE invoice (total TEXT, amount DECIMAL(5, 2))")?.execute_update(&[])?;
+
+        let total = Decimal::from_str("12345678901234567890.1230").unwrap();
+        let amount = BigDecimal::from_str("123.45").unwrap();
+        connection.create_statement("INSERT INTO invoice VALUES (?, ?)")?
+            .execute_update(&[Value::Decimal(total), Value::BigDecimal(amount.clone())])?;
+
+        let mut stmt = connection.create_statement("SELECT total, amount, typeof(amount) FROM invoice")?;
+        let mut rs = stmt.execute_query(&[])?;
+        assert!(rs.next());
+        assert_eq!(Some("12345678901234567890.1230".to_string()), rs.get_string(0)?);
+        assert_eq!(Some(total), rs.get_decimal(0)?);
+        assert_eq!(Some(BigDecimal::from_str("12345678901234567890.1230").unwrap()), rs.get_big_decimal(0)?);
+        // NUMERIC affinity stores the value as REAL
+        assert_eq!(Some("real".to_string()), rs.get_string(2)?);
+        assert_eq!(Some(amount), rs.get_big_decimal(1)?);
+        assert_eq!(Some(Decimal::from_str("123.45").unwrap()), rs.get_decimal(1)?);
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "uuid", feature = "json"))]
+    #[test]
+    fn uuids_and_json() -> rsdbc_core::Result<()> {
+        use std::collections::HashMap;
+        use rsdbc_core::uuid::Uuid;
+        use rsdbc_core::{Json, UuidStorage};
+
+        let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        let payload = Json(HashMap::from([("amount".to_string(), 10)]));
+        for (storage, storage_class) in [(UuidStorage::Text, "text"), (UuidStorage::Binary, "blob")] {
+            let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap())
+                .with_uuid_storage(storage);
+            connection.create_statement("CREATE TABLE event (id, payload)")?.execute_update(&[])?;
+            connection.create_statement("INSERT INTO event VALUES (?, ?)")?
+                .execute_update(&[Value::Uuid(id), payload.to_value()?])?;
+
+            let mut stmt = connection.create_statement("SELECT id, payload, typeof(id), CAST(payload AS BLOB) FROM event")?;
+            let mut rs = stmt.execute_query(&[])?;
+            assert!(rs.next());
+            assert_eq!(Some(storage_class.to_string()), rs.get_string(2)?);
+            assert_eq!(Some(id), rs.get_uuid(0)?);
+            assert_eq!(Some(payload.clone()), rs.get_json(1)?);
+            assert_eq!(Some(payload.clone()), rs.get_json(3)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn arrays_should_be_unsupported_unless_json_arrays_are_enabled() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let mut stmt = connection.create_statement("SELECT ?")?;
+        let result = stmt.execute_query(&[Value::array(vec![Value::Int32(1)])]);
+        assert!(matches!(result.err(), Some(RsdbcErrors::Unsupported(_))));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_arrays() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap())
+            .with_json_arrays(true);
+        let mut stmt = connection.create_statement("SELECT ?, json_array_length(?1), ? IN (SELECT value FROM json_each(?1))")?;
+        let array = Value::array(vec![Some(Value::Int64(1)), None, Some(Value::Int64(3))]);
+        let mut rs = stmt.execute_query(&[array, Value::Int32(3)])?;
+        assert!(rs.next());
+        assert_eq!(Some("[1,null,3]".to_string()), rs.get_string(0)?);
+        assert_eq!(Some(3), rs.get_i32(1)?);
+        assert_eq!(Some(true), rs.get_bool(2)?);
+
+        let values = rs.get_array(0)?.unwrap();
+        assert_eq!(3, values.len());
+        assert!(matches!(values[0], Some(Value::Int64(1))));
+        assert!(values[1].is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn blobs_should_be_streamed_incrementally() -> rsdbc_core::Result<()> {
+        use futures::TryStreamExt;
+
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        connection.create_statement("CREATE TABLE document (id INTEGER PRIMARY KEY, body BLOB, summary TEXT)")?.execute_update(&[])?;
+
+        let body: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        connection.create_statement("INSERT INTO document (id, body, summary) VALUES (1, zeroblob(?), ?)")?
+            .execute_update(&[Value::Int64(body.len() as i64), Value::clob("déjà vu".to_string())])?;
+        assert_eq!(100_000, connection.write_blob("document", "body", 1, &Lob::from(body.clone()))?);
+
+        let blob = connection.open_blob("document", "body", 1)?.with_chunk_size(30_000);
+        assert_eq!(Some(100_000), blob.size());
+        let chunks: Vec<_> = futures::executor::block_on(blob.try_collect()).map_err(lob_error)?;
+        assert_eq!(4, chunks.len());
+        assert_eq!(body, chunks.concat());
+
+        let summary = connection.open_clob("document", "summary", 1)?.with_chunk_size(2);
+        assert_eq!("déjà vu", futures::executor::block_on(summary.try_collect::<Vec<String>>()).map_err(lob_error)?.concat());
+
+        // the reserved space can not grow
+        let too_long = Lob::from(vec![0; 100_001]);
+        assert!(matches!(connection.write_blob("document", "body", 1, &too_long), Err(RsdbcErrors::IllegalArgument(_))));
+        assert!(connection.open_blob("document", "body", 2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn bound_lobs_should_be_read_from_their_source() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let lob = Lob::from_reader(io::Cursor::new(vec![1, 2, 3]));
+        let mut stmt = connection.create_statement("SELECT ?, ?")?;
+        let mut rs = stmt.execute_query(&[Value::Blob(lob.clone()), Value::clob("text".to_string())])?;
+        assert!(rs.next());
+        assert_eq!(vec![1, 2, 3], rs.get_blob(0)?.unwrap().read_to_vec()?);
+        assert_eq!("text", rs.get_clob(1)?.unwrap().read_to_string()?);
+        drop(rs);
+
+        // the source has been consumed
+        assert!(stmt.execute_query(&[Value::Blob(lob), Value::Int32(1)]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn values_should_be_read_as_their_generic_type() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let mut stmt = connection.create_statement("SELECT 1, 'a', x'01', 1.5, NULL")?;
+        let mut rs = stmt.execute_query(&[])?;
+        assert!(rs.next());
+        assert!(matches!(rs.get_value(0, RsdbcType::Bigint)?, Some(Value::Int64(1))));
+        assert!(matches!(rs.get_value(0, RsdbcType::Boolean)?, Some(Value::Bool(true))));
+        assert!(matches!(rs.get_value(1, RsdbcType::Varchar)?, Some(Value::String(s)) if s == "a"));
+        assert!(matches!(rs.get_value(2, RsdbcType::Blob)?, Some(Value::Bytes(b)) if b == vec![1]));
+        assert!(matches!(rs.get_value(3, RsdbcType::Double)?, Some(Value::Double(v)) if v == 1.5));
+        assert!(rs.get_value(4, RsdbcType::Integer)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn stored_procedures_should_be_unsupported() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let mut stmt = connection.create_statement("SELECT ?")?;
+        assert!(matches!(stmt.bind_out(0, RsdbcType::Integer), Err(RsdbcErrors::Unsupported(_))));
+        assert!(matches!(stmt.execute_call(&[Value::Int32(1)]), Err(RsdbcErrors::Unsupported(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn named_parameters_should_be_bound_at_each_position() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let connection: &mut dyn Connection = &mut connection;
+        connection.create_statement("CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT, nickname TEXT)")?.execute_update(&[])?;
+
+        let mut insert = connection.create_named_statement("INSERT INTO person VALUES (:id, @name, $name)")?;
+        assert_eq!(&["id".to_string(), "name".to_string()], insert.names());
+        insert.execute_update(&[("name", Value::String("Ada".to_string())), ("id", Value::Int32(1))])?;
+        assert!(matches!(insert.execute_update(&[("id", Value::Int32(2))]), Err(RsdbcErrors::IllegalArgument(_))));
+        drop(insert);
+
+        let mut select = connection.create_named_statement("SELECT nickname, ':name' FROM person WHERE name = :name")?;
+        let mut rs = select.execute_query(&[("name", Value::String("Ada".to_string()))])?;
+        assert!(rs.next());
+        assert_eq!(Some("Ada".to_string()), rs.get_string(0)?);
+        assert_eq!(Some(":name".to_string()), rs.get_string(1)?);
+        assert!(!rs.next());
+
+        Ok(())
+    }
+
+    #[test]
+    fn scripts_should_be_executed_statement_by_statement() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        let connection: &mut dyn Connection = &mut connection;
+        let script = "CREATE TABLE person (id INTEGER PRIMARY KEY, name TEXT);\n\
+            CREATE TABLE audit (name TEXT);\n\
+            CREATE TRIGGER person_audit AFTER INSERT ON person BEGIN\n\
+              INSERT INTO audit VALUES (new.name || ';');\n\
+            END;\n\
+            -- seed\n\
+            INSERT INTO person (name) VALUES ('Ada');";
+        assert_eq!(4, connection.execute_script(script)?);
+
+        let mut stmt = connection.create_statement("SELECT name FROM audit")?;
+        let mut rs = stmt.execute_query(&[])?;
+        assert!(rs.next());
+        assert_eq!(Some("Ada;".to_string()), rs.get_string(0)?);
+        drop(rs);
+        drop(stmt);
+
+        let err = connection.execute_script("INSERT INTO audit VALUES ('a');\n\nINSERT INTO missing VALUES (1);").unwrap_err();
+        assert!(matches!(&err, RsdbcErrors::Script { line: 3, sql, .. } if sql == "INSERT INTO missing VALUES (1)"));
+        assert_eq!(Some(DatabaseErrorKind::SyntaxError), err.kind());
+
+        Ok(())
+    }
+
+    #[test]
+    fn row_metadata() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        connection.create_statement("CREATE TABLE person (id INTEGER PRIMARY KEY, Name VARCHAR(20) NOT NULL, score DECIMAL(5, 2))")?.execute_update(&[])?;
+
+        let mut stmt = connection.create_statement("SELECT id, Name, score, name AS NAME FROM person")?;
+        let rs = stmt.execute_query(&[])?;
+        let metadata = rs.row_metadata()?;
+
+        assert_eq!(4, metadata.get_column_metadatas().len());
+        assert!(metadata.contains("ID"));
+
+        let name = metadata.get_column_metadata_by_name("name")?;
+        assert_eq!("Name", name.get_name());
+        assert_eq!(Some(20), name.get_precision());
+        assert_eq!(Nullability::NonNull, name.get_nullability());
+        assert_eq!("VARCHAR(20)", name.get_native_type_metadata().unwrap().name);
+
+        assert_eq!("NAME", metadata.get_column_metadata_by_name("\"NAME\"")?.get_name());
+
+        let score = metadata.get_column_metadata(2)?;
+        assert_eq!(Some(5), score.get_precision());
+        assert_eq!(Some(2), score.get_scale());
+        assert!(metadata.get_column_metadata(4).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors_should_be_classified() -> rsdbc_core::Result<()> {
+        let mut connection = SqliteConnection::new(rusqlite::Connection::open_in_memory().unwrap());
+        connection.create_statement("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")?.execute_update(&[])?;
+        connection.create_statement("INSERT INTO t VALUES (1, 'a')")?.execute_update(&[])?;
+
+        let err = connection.create_statement("INSERT INTO t VALUES (1, 'b')")?.execute_update(&[]).unwrap_err();
+        assert_eq!(Some(DatabaseErrorKind::UniqueViolation), err.kind());
+
+        let err = connection.create_statement("INSERT INTO t VALUES (2, NULL)")?.execute_update(&[]).unwrap_err();
+        assert_eq!(Some(DatabaseErrorKind::NotNullViolation), err.kind());
+
+        let err = connection.create_statement("SELEC 1").err().unwrap();
+        let db_error = err.as_database_error().unwrap();
+        assert_eq!(DatabaseErrorKind::SyntaxError, db_error.kind);
+        assert_eq!(Some("SELEC 1"), db_error.sql.as_deref());
+        assert_eq!(Some(1), db_error.position);
+
+        let err = connection.create_statement("SELECT * FROM WHERE").err().unwrap();
+        assert_eq!(Some(15), err.as_database_error().unwrap().position);
+
+        Ok(())
+    }
+
     #[test]
     fn execute_query() -> rsdbc_core::Result<()> {
         // let mut connection = SqliteConnectOptions::new().connect().await?;
@@ -0,0 +1,130 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use rusqlite::ffi;
+use rsdbc_core::{Column, Nullability, RsdbcType};
+
+/// The table column a result column originates from.
+#[derive(Debug, Clone)]
+pub(crate) struct ColumnOrigin {
+    database: String,
+    table: String,
+    column: String,
+    not_null: bool,
+    primary_key: bool,
+}
+
+/// Look up where each result column of the first statement in `sql` originates from. Columns that
+/// are expressions rather than table columns have no origin.
+///
+/// rusqlite does not expose `sqlite3_column_table_name` and friends so the SQL is prepared again
+/// through the raw handle. The bundled SQLite is built with `SQLITE_ENABLE_COLUMN_METADATA`.
+pub(crate) fn column_origins(conn: &rusqlite::Connection, sql: &str) -> Vec<Option<ColumnOrigin>> {
+    let sql = match CString::new(sql) {
+        Ok(sql) => sql,
+        Err(_) => return Vec::new(),
+    };
+
+    // safe as the statement is finalized before returning and the strings SQLite returns are
+    // copied while the statement is still alive
+    unsafe {
+        let db = conn.handle();
+        let mut stmt = ptr::null_mut();
+        if ffi::sqlite3_prepare_v2(db, sql.as_ptr(), -1, &mut stmt, ptr::null_mut()) != ffi::SQLITE_OK || stmt.is_null() {
+            return Vec::new();
+        }
+
+        let origins = (0..ffi::sqlite3_column_count(stmt))
+            .map(|i| column_origin(db, stmt, i))
+            .collect();
+        ffi::sqlite3_finalize(stmt);
+        origins
+    }
+}
+
+unsafe fn column_origin(db: *mut ffi::sqlite3, stmt: *mut ffi::sqlite3_stmt, i: c_int) -> Option<ColumnOrigin> {
+    let database = ffi::sqlite3_column_database_name(stmt, i);
+    let table = ffi::sqlite3_column_table_name(stmt, i);
+    let column = ffi::sqlite3_column_origin_name(stmt, i);
+    if database.is_null() || table.is_null() || column.is_null() {
+        return None;
+    }
+
+    let mut not_null = 0;
+    let mut primary_key = 0;
+    let mut auto_increment = 0;
+    let rc = ffi::sqlite3_table_column_metadata(
+        db,
+        database,
+        table,
+        column,
+        ptr::null_mut(),
+        ptr::null_mut(),
+        &mut not_null,
+        &mut primary_key,
+        &mut auto_increment,
+    );
+
+    Some(ColumnOrigin {
+        database: to_string(database),
+        table: to_string(table),
+        column: to_string(column),
+        not_null: rc == ffi::SQLITE_OK && not_null != 0,
+        primary_key: rc == ffi::SQLITE_OK && primary_key != 0,
+    })
+}
+
+unsafe fn to_string(s: *const c_char) -> String {
+    CStr::from_ptr(s).to_string_lossy().into_owned()
+}
+
+/// Build the metadata for a result column from its name, declared type and origin.
+pub(crate) fn to_column(name: &str, decl_type: Option<&str>, data_type: RsdbcType, origin: Option<&ColumnOrigin>) -> Column {
+    let (precision, scale) = decl_type.map_or((None, None), precision_and_scale);
+    let column = Column::new(name, data_type)
+        .type_name(decl_type.unwrap_or(""))
+        .precision(precision)
+        .scale(scale);
+
+    match origin {
+        Some(origin) => {
+            // an INTEGER PRIMARY KEY is an alias for the rowid which can't be null
+            let rowid = origin.primary_key && decl_type.is_some_and(|t| t.eq_ignore_ascii_case("INTEGER"));
+            column
+                .schema_name(Some(origin.database.clone()))
+                .table_name(Some(origin.table.clone()))
+                .base_column_name(Some(origin.column.clone()))
+                .nullability(if origin.not_null || rowid { Nullability::NonNull } else { Nullability::Nullable })
+        },
+        None => column.read_only(true),
+    }
+}
+
+/// Parse the precision and scale from a declared type such as `VARCHAR(20)` or `DECIMAL(10, 2)`.
+/// SQLite itself ignores them but they reflect the intent of the schema.
+fn precision_and_scale(decl_type: &str) -> (Option<u64>, Option<u64>) {
+    let args = decl_type
+        .split_once('(')
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(args, _)| args);
+
+    match args {
+        Some(args) => {
+            let mut args = args.split(',').map(|arg| arg.trim().parse::<u64>().ok());
+            (args.next().flatten(), args.next().flatten())
+        },
+        None => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metadata::precision_and_scale;
+
+    #[test]
+    fn precision_and_scale_from_declared_type() {
+        assert_eq!((None, None), precision_and_scale("TEXT"));
+        assert_eq!((Some(20), None), precision_and_scale("VARCHAR(20)"));
+        assert_eq!((Some(10), Some(2)), precision_and_scale("DECIMAL(10, 2)"));
+    }
+}
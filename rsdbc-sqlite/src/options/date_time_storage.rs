@@ -0,0 +1,16 @@
+// https://www.sqlite.org/datatype3.html#date_and_time_datatype
+
+/// How date and time values are stored as SQLite has no storage class for them.
+///
+/// Values are read back regardless of how they were stored.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SqliteDateTimeStorage {
+    /// TEXT as ISO-8601 strings, e.g. `2021-12-31 23:59:59.999+00:00`
+    #[default]
+    Text,
+    /// REAL as Julian day numbers, the number of days since noon in Greenwich on November 24, 4714 B.C.
+    Julian,
+    /// INTEGER as Unix time, the number of seconds since 1970-01-01 00:00:00 UTC.
+    /// Fractions of a second are not stored.
+    Unix,
+}
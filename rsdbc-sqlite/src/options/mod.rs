@@ -1,6 +1,7 @@
 // From SQLx - https://github.com/launchbadge/sqlx/blob/master/sqlx-core/src/sqlite/options/mod.rs
 
 mod auto_vacuum;
+mod date_time_storage;
 mod journal_mode;
 mod locking_mode;
 mod synchronous;
@@ -10,23 +11,20 @@ mod mode;
 // TODO: add log settings
 // use crate::connection::LogSettings;
 pub use auto_vacuum::SqliteAutoVacuum;
+pub use date_time_storage::SqliteDateTimeStorage;
 pub use journal_mode::SqliteJournalMode;
 pub use locking_mode::SqliteLockingMode;
 pub use synchronous::SqliteSynchronous;
 
 use std::{borrow::Cow, time::Duration};
-use std::future::Future;
 use std::path::Path;
-use std::pin::Pin;
 use crate::{SqliteConnection, to_rsdbc_err};
+use crate::warning::install_log_callback;
 use crate::Result;
 use futures::future::BoxFuture;
 use rusqlite::{Connection, OpenFlags};
-use std::sync::{Arc, Mutex};
-use rusqlite::params;
-use std::rc::Rc;
-use rsdbc_core::connection::{ConnectionFactory, ConnectionFactoryMetadata, ConnectionFactoryOptions};
-use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::connection::{ConnectionFactory, ConnectionFactoryMetadata};
+use rsdbc_core::UuidStorage;
 
 // // TODO:
 // // - ^ the trait `From<rusqlite::Error>` is not implemented for `rsdbc::Error`
@@ -53,6 +51,10 @@ pub struct SqliteConnectOptions {
     // pub(crate) log_settings: LogSettings,
     pub(crate) synchronous: SqliteSynchronous,
     pub(crate) auto_vacuum: SqliteAutoVacuum,
+    pub(crate) date_time_storage: SqliteDateTimeStorage,
+    pub(crate) uuid_storage: UuidStorage,
+    #[cfg(feature = "json")]
+    pub(crate) json_arrays: bool,
 }
 
 // TODO: document...see new
@@ -80,6 +82,10 @@ impl SqliteConnectOptions {
             // log_settings: Default::default(),
             synchronous: Default::default(),
             auto_vacuum: Default::default(),
+            date_time_storage: Default::default(),
+            uuid_storage: Default::default(),
+            #[cfg(feature = "json")]
+            json_arrays: false,
         }
     }
 
@@ -169,6 +175,31 @@ impl SqliteConnectOptions {
         self
     }
 
+    /// Sets how bound date and time values are stored.
+    ///
+    /// The default is TEXT. Times of day are always stored as TEXT.
+    pub fn date_time_storage(mut self, storage: SqliteDateTimeStorage) -> Self {
+        self.date_time_storage = storage;
+        self
+    }
+
+    /// Sets how bound UUIDs are stored, either as TEXT or as a 16 byte BLOB.
+    ///
+    /// The default is TEXT.
+    pub fn uuid_storage(mut self, storage: UuidStorage) -> Self {
+        self.uuid_storage = storage;
+        self
+    }
+
+    /// Sets whether arrays are bound as JSON arrays which can be read back as arrays.
+    ///
+    /// SQLite has no arrays so binding one is unsupported unless enabled.
+    #[cfg(feature = "json")]
+    pub fn json_arrays(mut self, enabled: bool) -> Self {
+        self.json_arrays = enabled;
+        self
+    }
+
     /// Set the [`SQLITE_OPEN_SHAREDCACHE` flag](https://sqlite.org/sharedcache.html).
     ///
     /// By default, this is disabled.
@@ -179,76 +210,59 @@ impl SqliteConnectOptions {
 }
 
 impl ConnectionFactory for SqliteConnectOptions {
-    // fn connect(&self) -> BoxFuture<'_, Result<Box<SqliteConnection>>>
-    fn connect(&self) -> Pin<Box<dyn Future<Output = Result<Box<(dyn rsdbc_core::connection::Connection + 'static)>>> + Send>>
-    {
-        todo!()
-        // Box::pin(async move {
-        //     let mut flags = OpenFlags::SQLITE_OPEN_NO_MUTEX;
-        //
-        //     flags |= if self.read_only {
-        //         OpenFlags::SQLITE_OPEN_READ_ONLY
-        //     } else if self.create_if_missing {
-        //         OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_READ_WRITE
-        //     } else {
-        //         OpenFlags::SQLITE_OPEN_READ_WRITE
-        //     };
-        //
-        //     if self.in_memory {
-        //         flags |= OpenFlags::SQLITE_OPEN_MEMORY;
-        //     }
-        //
-        //     flags |= if self.shared_cache {
-        //         OpenFlags::SQLITE_OPEN_SHARED_CACHE
-        //     } else {
-        //         OpenFlags::SQLITE_OPEN_PRIVATE_CACHE
-        //     };
-        //
-        //     let conn =
-        //         rusqlite::Connection::open_with_flags(self.filename.to_path_buf(), flags)
-        //             .map_err(to_rsdbc_err)?;
-        //
-        //     conn.busy_timeout(self.busy_timeout);
-        //
-        //     // execute pragma
-        //     let init = format!(
-        //         "PRAGMA locking_mode = {}; PRAGMA journal_mode = {}; PRAGMA foreign_keys = {}; PRAGMA synchronous = {}; PRAGMA auto_vacuum = {}",
-        //         self.locking_mode.as_str(),
-        //         self.journal_mode.as_str(),
-        //         if self.foreign_keys { "ON" } else { "OFF" },
-        //         self.synchronous.as_str(),
-        //         self.auto_vacuum.as_str(),
-        //     );
-        //     conn.execute(init.as_str(), params![]).map_err(to_rsdbc_err)?;
-        //
-        //     // // TODO: make this better
-        //     // Ok(Box::new(SqliteConnection {
-        //     //     // conn: Mutex::new(Some(&conn)),
-        //     //     conn: Some(Arc::new(Mutex::new(conn))),
-        //     // }) as Box<(dyn rsdbc_core::connection::Connection + 'static)>)
-        //
-        //     todo!()
-        //
-        //
-        //     // let mut conn = establish(self).await?;
-        //     //
-        //     // // send an initial sql statement comprised of options
-        //     // //
-        //     // // Note that locking_mode should be set before journal_mode; see
-        //     // // https://www.sqlite.org/wal.html#use_of_wal_without_shared_memory .
-        //     // let init = format!(
-        //     //     "PRAGMA locking_mode = {}; PRAGMA journal_mode = {}; PRAGMA foreign_keys = {}; PRAGMA synchronous = {}; PRAGMA auto_vacuum = {}",
-        //     //     self.locking_mode.as_str(),
-        //     //     self.journal_mode.as_str(),
-        //     //     if self.foreign_keys { "ON" } else { "OFF" },
-        //     //     self.synchronous.as_str(),
-        //     //     self.auto_vacuum.as_str(),
-        //     // );
-        //     //
-        //     // conn.execute(&*init).await?;
-        //     //
-        //     // Ok(conn)
-        // })
+    fn connect(&self) -> BoxFuture<'_, Result<Box<dyn rsdbc_core::connection::Connection>>> {
+        Box::pin(async move {
+            // must happen before the first connection is opened
+            install_log_callback();
+
+            let mut flags = OpenFlags::SQLITE_OPEN_NO_MUTEX;
+
+            flags |= if self.read_only {
+                OpenFlags::SQLITE_OPEN_READ_ONLY
+            } else if self.create_if_missing {
+                OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_READ_WRITE
+            } else {
+                OpenFlags::SQLITE_OPEN_READ_WRITE
+            };
+
+            if self.in_memory {
+                flags |= OpenFlags::SQLITE_OPEN_MEMORY;
+            }
+
+            flags |= if self.shared_cache {
+                OpenFlags::SQLITE_OPEN_SHARED_CACHE
+            } else {
+                OpenFlags::SQLITE_OPEN_PRIVATE_CACHE
+            };
+
+            let conn = Connection::open_with_flags(&self.filename, flags)
+                .map_err(to_rsdbc_err)?;
+
+            conn.busy_timeout(self.busy_timeout).map_err(to_rsdbc_err)?;
+            conn.set_prepared_statement_cache_capacity(self.statement_cache_capacity);
+
+            // send an initial sql statement comprised of options
+            //
+            // Note that locking_mode should be set before journal_mode; see
+            // https://www.sqlite.org/wal.html#use_of_wal_without_shared_memory .
+            let init = format!(
+                "PRAGMA locking_mode = {}; PRAGMA journal_mode = {}; PRAGMA foreign_keys = {}; PRAGMA synchronous = {}; PRAGMA auto_vacuum = {}",
+                self.locking_mode.as_str(),
+                self.journal_mode.as_str(),
+                if self.foreign_keys { "ON" } else { "OFF" },
+                self.synchronous.as_str(),
+                self.auto_vacuum.as_str(),
+            );
+            // execute_batch rather than execute as some of these pragmas return rows
+            conn.execute_batch(init.as_str()).map_err(to_rsdbc_err)?;
+
+            let connection = SqliteConnection::new(conn)
+                .with_date_time_storage(self.date_time_storage)
+                .with_uuid_storage(self.uuid_storage);
+            #[cfg(feature = "json")]
+            let connection = connection.with_json_arrays(self.json_arrays);
+            Ok(Box::new(connection) as Box<dyn rsdbc_core::connection::Connection>)
+        })
     }
 
     // TODO: use SQLite Connection Factory Metadata?
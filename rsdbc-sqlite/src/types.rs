@@ -0,0 +1,146 @@
+use rsdbc_core::RsdbcType;
+
+// https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+/// Map a declared column type to a generic type.
+///
+/// Well known type names map to their generic type, e.g. `VARCHAR(20)` to [RsdbcType::Varchar].
+/// Any other declared type maps according to the affinity SQLite gives columns of that type.
+pub fn to_rsdbc_type(decl_type: Option<&str>) -> RsdbcType {
+    let decl_type = decl_type.unwrap_or("").trim().to_ascii_uppercase();
+    let name = decl_type.split('(').next().unwrap_or("").trim();
+    match name {
+        "CHAR" | "CHARACTER" => RsdbcType::Char,
+        "VARCHAR" | "VARYING CHARACTER" | "CHARACTER VARYING" | "TEXT" => RsdbcType::Varchar,
+        "NCHAR" | "NATIVE CHARACTER" => RsdbcType::Nchar,
+        "NVARCHAR" => RsdbcType::Nvarchar,
+        "CLOB" => RsdbcType::Clob,
+        "NCLOB" => RsdbcType::Nclob,
+        "BOOLEAN" | "BOOL" => RsdbcType::Boolean,
+        "BINARY" => RsdbcType::Binary,
+        "VARBINARY" => RsdbcType::Varbinary,
+        "BLOB" => RsdbcType::Blob,
+        "INTEGER" | "INT" | "MEDIUMINT" => RsdbcType::Integer,
+        "TINYINT" => RsdbcType::Tinyint,
+        "SMALLINT" | "INT2" => RsdbcType::Smallint,
+        "BIGINT" | "UNSIGNED BIG INT" | "INT8" => RsdbcType::Bigint,
+        "NUMERIC" => RsdbcType::Numeric,
+        "DECIMAL" => RsdbcType::Decimal,
+        "FLOAT" => RsdbcType::Float,
+        "REAL" => RsdbcType::Real,
+        "DOUBLE" | "DOUBLE PRECISION" => RsdbcType::Double,
+        "DATE" => RsdbcType::Date,
+        "TIME" => RsdbcType::Time,
+        "TIME WITH TIME ZONE" => RsdbcType::TimeWithTimeZone,
+        "DATETIME" | "TIMESTAMP" => RsdbcType::Timestamp,
+        "TIMESTAMP WITH TIME ZONE" => RsdbcType::TimestampWithTimeZone,
+        _ => affinity(&decl_type),
+    }
+}
+
+/// Apply the column affinity rules, in order, to an upper case declared type.
+fn affinity(decl_type: &str) -> RsdbcType {
+    if decl_type.contains("INT") {
+        RsdbcType::Bigint
+    } else if ["CHAR", "CLOB", "TEXT"].iter().any(|t| decl_type.contains(t)) {
+        RsdbcType::Varchar
+    } else if decl_type.is_empty() || decl_type.contains("BLOB") {
+        RsdbcType::Blob
+    } else if ["REAL", "FLOA", "DOUB"].iter().any(|t| decl_type.contains(t)) {
+        RsdbcType::Double
+    } else {
+        RsdbcType::Numeric
+    }
+}
+
+/// Map a generic type to the type name columns of that type are declared with.
+///
+/// Binary types are declared as `BLOB` to get BLOB affinity.
+/// Returns [None] for [RsdbcType::Collection] which has no column type.
+pub fn to_declared_type(t: RsdbcType) -> Option<&'static str> {
+    let decl_type = match t {
+        RsdbcType::Char => "CHAR",
+        RsdbcType::Varchar => "VARCHAR",
+        RsdbcType::Nchar => "NCHAR",
+        RsdbcType::Nvarchar => "NVARCHAR",
+        RsdbcType::Clob => "CLOB",
+        RsdbcType::Nclob => "NCLOB",
+        RsdbcType::Boolean => "BOOLEAN",
+        RsdbcType::Binary | RsdbcType::Varbinary | RsdbcType::Blob => "BLOB",
+        RsdbcType::Integer => "INTEGER",
+        RsdbcType::Tinyint => "TINYINT",
+        RsdbcType::Smallint => "SMALLINT",
+        RsdbcType::Bigint => "BIGINT",
+        RsdbcType::Numeric => "NUMERIC",
+        RsdbcType::Decimal => "DECIMAL",
+        RsdbcType::Float => "FLOAT",
+        RsdbcType::Real => "REAL",
+        RsdbcType::Double => "DOUBLE",
+        RsdbcType::Date => "DATE",
+        RsdbcType::Time => "TIME",
+        RsdbcType::TimeWithTimeZone => "TIME WITH TIME ZONE",
+        RsdbcType::Timestamp => "DATETIME",
+        RsdbcType::TimestampWithTimeZone => "TIMESTAMP WITH TIME ZONE",
+        RsdbcType::Collection => return None,
+    };
+    Some(decl_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use rsdbc_core::RsdbcType;
+    use crate::types::{to_declared_type, to_rsdbc_type};
+
+    #[test]
+    fn every_type_should_map_to_a_declared_type_and_back() {
+        let expected = [
+            (RsdbcType::Char, Some("CHAR"), RsdbcType::Char),
+            (RsdbcType::Varchar, Some("VARCHAR"), RsdbcType::Varchar),
+            (RsdbcType::Nchar, Some("NCHAR"), RsdbcType::Nchar),
+            (RsdbcType::Nvarchar, Some("NVARCHAR"), RsdbcType::Nvarchar),
+            (RsdbcType::Clob, Some("CLOB"), RsdbcType::Clob),
+            (RsdbcType::Nclob, Some("NCLOB"), RsdbcType::Nclob),
+            (RsdbcType::Boolean, Some("BOOLEAN"), RsdbcType::Boolean),
+            (RsdbcType::Binary, Some("BLOB"), RsdbcType::Blob),
+            (RsdbcType::Varbinary, Some("BLOB"), RsdbcType::Blob),
+            (RsdbcType::Blob, Some("BLOB"), RsdbcType::Blob),
+            (RsdbcType::Integer, Some("INTEGER"), RsdbcType::Integer),
+            (RsdbcType::Tinyint, Some("TINYINT"), RsdbcType::Tinyint),
+            (RsdbcType::Smallint, Some("SMALLINT"), RsdbcType::Smallint),
+            (RsdbcType::Bigint, Some("BIGINT"), RsdbcType::Bigint),
+            (RsdbcType::Numeric, Some("NUMERIC"), RsdbcType::Numeric),
+            (RsdbcType::Decimal, Some("DECIMAL"), RsdbcType::Decimal),
+            (RsdbcType::Float, Some("FLOAT"), RsdbcType::Float),
+            (RsdbcType::Real, Some("REAL"), RsdbcType::Real),
+            (RsdbcType::Double, Some("DOUBLE"), RsdbcType::Double),
+            (RsdbcType::Date, Some("DATE"), RsdbcType::Date),
+            (RsdbcType::Time, Some("TIME"), RsdbcType::Time),
+            (RsdbcType::TimeWithTimeZone, Some("TIME WITH TIME ZONE"), RsdbcType::TimeWithTimeZone),
+            (RsdbcType::Timestamp, Some("DATETIME"), RsdbcType::Timestamp),
+            (RsdbcType::TimestampWithTimeZone, Some("TIMESTAMP WITH TIME ZONE"), RsdbcType::TimestampWithTimeZone),
+            (RsdbcType::Collection, None, RsdbcType::Collection),
+        ];
+        assert_eq!(RsdbcType::ALL.len(), expected.len());
+
+        for (t, (expected_type, decl_type, back)) in RsdbcType::ALL.iter().zip(expected) {
+            assert_eq!(*t, expected_type);
+            assert_eq!(decl_type, to_declared_type(*t), "{:?}", t);
+            if let Some(decl_type) = decl_type {
+                assert_eq!(back, to_rsdbc_type(Some(decl_type)), "{:?}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn declared_types_should_map_by_affinity() {
+        assert_eq!(RsdbcType::Varchar, to_rsdbc_type(Some("varchar(20)")));
+        assert_eq!(RsdbcType::Decimal, to_rsdbc_type(Some("DECIMAL(10, 2)")));
+        assert_eq!(RsdbcType::Bigint, to_rsdbc_type(Some("UNSIGNED INT")));
+        // contains INT so it has INTEGER affinity
+        assert_eq!(RsdbcType::Bigint, to_rsdbc_type(Some("FLOATING POINT")));
+        assert_eq!(RsdbcType::Varchar, to_rsdbc_type(Some("LONGTEXT")));
+        assert_eq!(RsdbcType::Blob, to_rsdbc_type(Some("")));
+        assert_eq!(RsdbcType::Blob, to_rsdbc_type(None));
+        assert_eq!(RsdbcType::Double, to_rsdbc_type(Some("FLOAT8")));
+        assert_eq!(RsdbcType::Numeric, to_rsdbc_type(Some("STRING")));
+    }
+}
@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+use std::os::raw::c_int;
+use std::sync::Once;
+use rusqlite::ffi;
+use rsdbc_core::SQLWarning;
+
+// SQLite only offers a single process wide error log rather than a per connection one.
+// The log callback is invoked synchronously on the thread executing the statement so warnings
+// are buffered per thread, and each call into SQLite is scoped by with_logged_warnings so the
+// warnings are attributed to the connection which made it.
+thread_local! {
+    static LOGGED_WARNINGS: RefCell<Vec<SQLWarning>> = const { RefCell::new(Vec::new()) };
+}
+
+static INSTALL_LOG_CALLBACK: Once = Once::new();
+
+/// Install the SQLite log callback used to collect warnings.
+///
+/// SQLite only accepts the callback before it has been initialized, which happens when the
+/// first connection in the process is opened, so this is a no-op if a connection has already
+/// been opened without going through [crate::options::SqliteConnectOptions].
+pub(crate) fn install_log_callback() {
+    INSTALL_LOG_CALLBACK.call_once(|| {
+        // safe as the callback is thread safe and does not call back into SQLite
+        let _ = unsafe { rusqlite::trace::config_log(Some(log_callback)) };
+    });
+}
+
+fn log_callback(code: c_int, message: &str) {
+    let severity = match code & 0xff {
+        ffi::SQLITE_WARNING => "WARNING",
+        ffi::SQLITE_NOTICE => "NOTICE",
+        // errors are already surfaced as RsdbcErrors
+        _ => return,
+    };
+
+    LOGGED_WARNINGS.with(|warnings| warnings.borrow_mut().push(SQLWarning {
+        severity: Some(severity.to_string()),
+        sql_state: None,
+        vendor_code: Some(code),
+        message: message.to_string(),
+    }));
+}
+
+/// Runs `f`, a call into SQLite, and returns its result with the warnings logged during it.
+///
+/// Anything logged on the thread beforehand, e.g. by a connection opened without the callback's
+/// buffer being drained, is discarded rather than attributed to this call.
+pub(crate) fn with_logged_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<SQLWarning>) {
+    take_logged_warnings();
+    let result = f();
+    (result, take_logged_warnings())
+}
+
+/// Take the warnings logged on the current thread since they were last taken.
+fn take_logged_warnings() -> Vec<SQLWarning> {
+    LOGGED_WARNINGS.with(|warnings| warnings.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::ffi;
+    use crate::warning::{log_callback, take_logged_warnings, with_logged_warnings};
+
+    #[test]
+    fn only_warnings_and_notices_should_be_collected() {
+        take_logged_warnings();
+        log_callback(ffi::SQLITE_WARNING_AUTOINDEX, "automatic index on b(x)");
+        log_callback(ffi::SQLITE_CONSTRAINT_UNIQUE, "UNIQUE constraint failed: t.id");
+        log_callback(ffi::SQLITE_NOTICE_RECOVER_WAL, "recovered 2 frames from WAL file");
+
+        let warnings = take_logged_warnings();
+        assert_eq!(2, warnings.len());
+        assert_eq!(Some("WARNING"), warnings[0].severity.as_deref());
+        assert_eq!(Some(ffi::SQLITE_WARNING_AUTOINDEX), warnings[0].vendor_code);
+        assert_eq!(Some("NOTICE"), warnings[1].severity.as_deref());
+        assert!(take_logged_warnings().is_empty());
+    }
+    #[test]
+    fn only_warnings_logged_during_the_call_should_be_returned() {
+        log_callback(ffi::SQLITE_WARNING, "logged by another connection");
+        let (result, warnings) = with_logged_warnings(|| {
+            log_callback(ffi::SQLITE_WARNING_AUTOINDEX, "automatic index on b(x)");
+            1
+        });
+        assert_eq!(1, result);
+        assert_eq!(1, warnings.len());
+        assert_eq!("automatic index on b(x)", warnings[0].message);
+        assert!(take_logged_warnings().is_empty());
+    }
+}
@@ -12,6 +12,10 @@ use rsdbc_core::connection::{Connection, ConnectionFactory, ConnectionFactoryOpt
 use rsdbc_sqlite::connection::SqliteConnectionFactory;
 use rsdbc_postgres::PostgresqlConnectionFactory;
 
+pub mod template;
+
+pub use template::RsdbcTemplate;
+
 pub struct ConnectionFactories;
 
 // TODO: use Any? https://doc.rust-lang.org/std/any/index.html
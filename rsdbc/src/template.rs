@@ -0,0 +1,291 @@
+use futures::lock::Mutex;
+use rsdbc_core::connection::{Connection, ConnectionFactory};
+use rsdbc_core::error::RsdbcErrors;
+use rsdbc_core::retry::RetryPolicy;
+use rsdbc_core::{Result, ResultSet, Value};
+
+// Inspired by Spring's JdbcTemplate
+// https://docs.spring.io/spring-framework/docs/current/javadoc-api/org/springframework/jdbc/core/JdbcTemplate.html
+/// High level helper that takes care of acquiring and releasing connections from a
+/// [ConnectionFactory] so callers only have to provide SQL, parameters and a row mapper.
+///
+/// Each operation acquires a connection, executes and then closes the connection.
+/// When a transaction has been started via [RsdbcTemplate::begin_transaction] operations join
+/// that transaction and run on its connection until it is committed or rolled back.
+///
+/// Operations outside of a transaction that fail with a retryable error are retried according to
+/// the template's [RetryPolicy], which by default does not retry.
+pub struct RsdbcTemplate {
+    connection_factory: Box<dyn ConnectionFactory>,
+    // connection bound to the current transaction, if any
+    transaction: Mutex<Option<Box<dyn Connection>>>,
+    retry_policy: RetryPolicy,
+}
+
+impl RsdbcTemplate {
+
+    pub fn new(connection_factory: Box<dyn ConnectionFactory>) -> Self {
+        Self {
+            connection_factory,
+            transaction: Mutex::new(None),
+            retry_policy: RetryPolicy::none(),
+        }
+    }
+
+    /// Retry operations that are not part of a transaction according to the given [RetryPolicy].
+    /// Operations within a transaction are never retried individually as the database typically
+    /// aborts the whole transaction, see [Connection::in_transaction_with_retry] instead.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Execute a query binding the given parameters and map each row via the `mapper`.
+    pub async fn query<T, F>(&self, sql: &str, params: &[Value], mut mapper: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&dyn ResultSet) -> Result<T>,
+    {
+        self.with_connection(|connection| {
+            let mut stmt = connection.create_statement(sql)?;
+            let mut rs = stmt.execute_query(params)?;
+            rs.list(&mut mapper)
+        }).await
+    }
+
+    /// Execute a query that is expected to return exactly one row and map it via the `mapper`.
+    ///
+    /// returns [RsdbcErrors::NoRows] or [RsdbcErrors::TooManyRows] when the query does not
+    /// return exactly one row
+    pub async fn query_for_object<T, F>(&self, sql: &str, params: &[Value], mut mapper: F) -> Result<T>
+    where
+        F: FnMut(&dyn ResultSet) -> Result<T>,
+    {
+        self.with_connection(|connection| {
+            let mut stmt = connection.create_statement(sql)?;
+            let mut rs = stmt.execute_query(params)?;
+            rs.one(&mut mapper)
+        }).await
+    }
+
+    /// Execute a single insert, update or delete statement.
+    ///
+    /// returns the number of rows affected
+    pub async fn update(&self, sql: &str, params: &[Value]) -> Result<u64> {
+        self.with_connection(|connection| {
+            connection.create_statement(sql)?.execute_update(params)
+        }).await
+    }
+
+    /// Execute the same statement once for each set of parameters using a single connection.
+    ///
+    /// returns the number of rows affected by each execution
+    pub async fn batch_update(&self, sql: &str, batch_params: &[Vec<Value>]) -> Result<Vec<u64>> {
+        self.with_connection(|connection| {
+            let mut stmt = connection.create_statement(sql)?;
+            batch_params
+                .iter()
+                .map(|params| stmt.execute_update(params))
+                .collect()
+        }).await
+    }
+
+    /// Execute a statement without parameters, typically DDL.
+    pub async fn execute(&self, sql: &str) -> Result<()> {
+        self.update(sql, &[]).await.map(|_| ())
+    }
+
+    /// Begins a new transaction. Subsequent operations on this template join the transaction
+    /// until [RsdbcTemplate::commit_transaction] or [RsdbcTemplate::rollback_transaction] is called.
+    pub async fn begin_transaction(&self) -> Result<()> {
+        let mut transaction = self.transaction.lock().await;
+        if transaction.is_some() {
+            return Err(RsdbcErrors::General("transaction already in progress".to_string()));
+        }
+
+        let mut connection = self.connection_factory.connect().await?;
+        if let Err(e) = connection.begin_transaction() {
+            let _ = connection.close();
+            return Err(e);
+        }
+
+        *transaction = Some(connection);
+        Ok(())
+    }
+
+    /// Commits the current transaction and releases its connection.
+    pub async fn commit_transaction(&self) -> Result<()> {
+        let mut connection = self.take_transaction().await?;
+        let committed = connection.commit_transaction();
+        let closed = connection.close();
+        committed?;
+        closed
+    }
+
+    /// Rolls back the current transaction and releases its connection.
+    pub async fn rollback_transaction(&self) -> Result<()> {
+        let mut connection = self.take_transaction().await?;
+        let rolled_back = connection.rollback_transaction();
+        let closed = connection.close();
+        rolled_back?;
+        closed
+    }
+
+    async fn take_transaction(&self) -> Result<Box<dyn Connection>> {
+        self.transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| RsdbcErrors::General("no transaction in progress".to_string()))
+    }
+
+    async fn with_connection<T, F>(&self, mut f: F) -> Result<T>
+    where
+        F: FnMut(&mut dyn Connection) -> Result<T>,
+    {
+        let mut transaction = self.transaction.lock().await;
+        if let Some(connection) = transaction.as_mut() {
+            return f(connection.as_mut());
+        }
+        drop(transaction);
+
+        let mut attempt = 1;
+        loop {
+            match self.with_new_connection(&mut f).await {
+                Err(e) if self.retry_policy.should_retry(attempt, &e) => {
+                    self.retry_policy.wait(attempt).await;
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn with_new_connection<T, F>(&self, f: &mut F) -> Result<T>
+    where
+        F: FnMut(&mut dyn Connection) -> Result<T>,
+    {
+        let mut connection = self.connection_factory.connect().await?;
+        let result = f(connection.as_mut());
+        let closed = connection.close();
+        // prefer surfacing the error from the operation over one from closing the connection
+        let value = result?;
+        closed?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+    use futures::executor::block_on;
+    use rsdbc_core::{Result, ResultSet, Value};
+    use rsdbc_core::error::RsdbcErrors;
+    use rsdbc_core::retry::RetryPolicy;
+    use rsdbc_sqlite::connection::SqliteConnectionFactory;
+    use rsdbc_sqlite::options::SqliteConnectOptions;
+    use crate::template::RsdbcTemplate;
+
+    // connections are acquired per call so each test needs a file backed database
+    fn template(name: &str) -> (RsdbcTemplate, PathBuf) {
+        let path = std::env::temp_dir().join(format!("rsdbc-template-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let configuration = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true);
+
+        let template = RsdbcTemplate::new(Box::new(SqliteConnectionFactory { configuration }));
+        block_on(template.execute("CREATE TABLE person (id INTEGER NOT NULL, name TEXT NOT NULL)")).unwrap();
+        (template, path)
+    }
+
+    fn name(row: &dyn ResultSet) -> Result<String> {
+        Ok(row.get_string(0)?.unwrap())
+    }
+
+    #[test]
+    fn update_and_query() {
+        let (template, path) = template("update_and_query");
+        block_on(async {
+            let inserted = template.update(
+                "INSERT INTO person (id, name) VALUES (?, ?)",
+                &[Value::Int32(1), Value::String("sean".to_string())]
+            ).await.unwrap();
+            assert_eq!(1, inserted);
+
+            let names = template.query("SELECT name FROM person", &[], name).await.unwrap();
+            assert_eq!(vec!["sean".to_string()], names);
+
+            let found = template.query_for_object("SELECT name FROM person WHERE id = ?", &[Value::Int32(1)], name).await.unwrap();
+            assert_eq!("sean", found);
+
+            let missing = template.query_for_object("SELECT name FROM person WHERE id = ?", &[Value::Int32(2)], name).await;
+            assert!(matches!(missing, Err(RsdbcErrors::NoRows)));
+        });
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn batch_update_should_return_rows_affected_per_execution() {
+        let (template, path) = template("batch_update");
+        block_on(async {
+            let counts = template.batch_update(
+                "INSERT INTO person (id, name) VALUES (?, ?)",
+                &[
+                    vec![Value::Int32(1), Value::String("one".to_string())],
+                    vec![Value::Int32(2), Value::String("two".to_string())],
+                ]
+            ).await.unwrap();
+            assert_eq!(vec![1, 1], counts);
+
+            let deleted = template.update("DELETE FROM person", &[]).await.unwrap();
+            assert_eq!(2, deleted);
+        });
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn operations_should_join_transaction() {
+        let (template, path) = template("join_transaction");
+        block_on(async {
+            template.begin_transaction().await.unwrap();
+            template.update("INSERT INTO person (id, name) VALUES (?, ?)", &[Value::Int32(1), Value::String("sean".to_string())]).await.unwrap();
+            let names = template.query("SELECT name FROM person", &[], name).await.unwrap();
+            assert_eq!(1, names.len());
+            template.rollback_transaction().await.unwrap();
+
+            let names = template.query("SELECT name FROM person", &[], name).await.unwrap();
+            assert!(names.is_empty());
+        });
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn busy_database_should_return_retryable_error() {
+        let (template, path) = template("busy_database");
+        let configuration = SqliteConnectOptions::new()
+            .filename(&path)
+            .busy_timeout(Duration::ZERO);
+        let other = RsdbcTemplate::new(Box::new(SqliteConnectionFactory { configuration }))
+            .with_retry_policy(RetryPolicy::new(2, Duration::from_millis(1), Duration::from_millis(1)));
+
+        block_on(async {
+            template.begin_transaction().await.unwrap();
+            template.update("INSERT INTO person (id, name) VALUES (1, 'one')", &[]).await.unwrap();
+
+            let err = other.update("INSERT INTO person (id, name) VALUES (2, 'two')", &[]).await.unwrap_err();
+            assert!(err.is_retryable());
+
+            template.commit_transaction().await.unwrap();
+            assert_eq!(1, other.update("INSERT INTO person (id, name) VALUES (2, 'two')", &[]).await.unwrap());
+        });
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn commit_without_transaction_should_return_err() {
+        let (template, path) = template("commit_without_transaction");
+        assert!(block_on(template.commit_transaction()).is_err());
+        let _ = std::fs::remove_file(path);
+    }
+}